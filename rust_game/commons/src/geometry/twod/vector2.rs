@@ -13,7 +13,7 @@ use std::ops::{
     Sub,
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
 pub struct Vector2 {
     pub x: f64,
     pub y: f64,
@@ -54,6 +54,19 @@ impl UdpSocket {
         }
     }
 
+    /// Allows this socket to send to the broadcast address
+    pub fn set_broadcast(&self, broadcast: bool) -> Result<(), Error> {
+        match &self.implementation {
+            UdpSocketImplementation::Real(real_udp_socket) => {
+                real_udp_socket.set_broadcast(broadcast)
+            }
+            UdpSocketImplementation::Simulated(udp_socket_simulator) => {
+                udp_socket_simulator.set_broadcast(broadcast);
+                Ok(())
+            }
+        }
+    }
+
     pub fn try_clone(&self) -> Result<Self, Error> {
         match &self.implementation {
             UdpSocketImplementation::Real(real_udp_socket) => Ok(Self {
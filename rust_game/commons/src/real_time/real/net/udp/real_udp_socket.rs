@@ -29,6 +29,10 @@ impl RealUdpSocket {
         return self.udp_socket.send_to(buf, socket_addr);
     }
 
+    pub fn set_broadcast(&self, broadcast: bool) -> Result<(), Error> {
+        return self.udp_socket.set_broadcast(broadcast);
+    }
+
     pub fn try_clone(&self) -> Result<Self, Error> {
         return Ok(Self {
             udp_socket: self.udp_socket.try_clone()?,
@@ -25,9 +25,14 @@ pub struct HostSimulator {
     network_simulator: NetworkSimulator,
 }
 
+/// The port of the first UDP socket a host binds to port 0.  Later sockets
+/// get the ports after it.
+pub const FIRST_EPHEMERAL_UDP_PORT: u16 = 49152;
+
 struct Internal {
     ip_addr: IpAddr,
     next_tcp_port: u16,
+    next_udp_port: u16,
     bound_udp_sockets: HashSet<SocketAddr>,
 }
 
@@ -36,6 +41,7 @@ impl HostSimulator {
         let internal = Internal {
             ip_addr,
             next_tcp_port: 1,
+            next_udp_port: FIRST_EPHEMERAL_UDP_PORT,
             bound_udp_sockets: HashSet::new(),
         };
 
@@ -79,6 +85,18 @@ impl HostSimulator {
     pub fn bind_udp_socket(&self, socket_addr: SocketAddr) -> Result<UdpSocketSimulator, Error> {
         let mut guard = self.internal.lock().unwrap();
 
+        // A socket bound to the unspecified address sends from the host's address
+        let mut socket_addr = socket_addr;
+        if socket_addr.ip().is_unspecified() {
+            socket_addr.set_ip(guard.ip_addr);
+        }
+
+        // Like a real socket, binding to port 0 picks an unused port
+        if socket_addr.port() == 0 {
+            socket_addr.set_port(guard.next_udp_port);
+            guard.next_udp_port = guard.next_udp_port + 1;
+        }
+
         if guard.bound_udp_sockets.contains(&socket_addr) {
             return Err(Error::from(ErrorKind::AddrInUse));
         } else {
@@ -5,4 +5,5 @@ pub mod tcp;
 pub mod udp;
 
 pub use self::host_simulator::HostSimulator;
+pub use self::host_simulator::FIRST_EPHEMERAL_UDP_PORT;
 pub use self::network_simulator::NetworkSimulator;
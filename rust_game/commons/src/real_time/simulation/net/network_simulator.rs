@@ -85,11 +85,16 @@ impl NetworkSimulator {
         connection_handler: TcpConnectionHandler,
         join_call_back: impl FnOnce(()) + Send + 'static,
     ) -> Result<(), Error> {
-        let network_simulator = receiver
-            .get_factory()
-            .get_host_simulator()
-            .get_network_simulator()
-            .clone();
+        let host_simulator = receiver.get_factory().get_host_simulator().clone();
+
+        // A listener bound to the unspecified address accepts connections to
+        // the host's address
+        let mut socket_addr = socket_addr;
+        if socket_addr.ip().is_unspecified() {
+            socket_addr.set_ip(host_simulator.get_ip_addr());
+        }
+
+        let network_simulator = host_simulator.get_network_simulator().clone();
         let mut guard = network_simulator.internal.lock().unwrap();
 
         if guard.tcp_listeners.contains_key(&socket_addr) {
@@ -221,14 +226,25 @@ impl NetworkSimulator {
     pub(super) fn send_udp(&self, from: &SocketAddr, to: &SocketAddr, buf: &[u8]) {
         let guard = self.internal.lock().unwrap();
 
-        if let Some(sender) = guard.udp_readers.get(to) {
-            let buf = Vec::from(buf);
+        if is_broadcast(to) {
+            // A broadcast reaches every reader bound to the destination port
+            for (socket_addr, sender) in guard.udp_readers.iter() {
+                if socket_addr.port() == to.port() {
+                    Self::send_to_reader(sender, from, buf);
+                }
+            }
+        } else if let Some(sender) = guard.udp_readers.get(to) {
+            Self::send_to_reader(sender, from, buf);
+        }
+    }
 
-            let send_result = sender.send_event((from.clone(), buf));
+    fn send_to_reader(sender: &EventSender<(SocketAddr, Vec<u8>)>, from: &SocketAddr, buf: &[u8]) {
+        let buf = Vec::from(buf);
 
-            if send_result.is_err() {
-                panic!("Failed to send event");
-            }
+        let send_result = sender.send_event((from.clone(), buf));
+
+        if send_result.is_err() {
+            panic!("Failed to send event");
         }
     }
 
@@ -244,3 +260,10 @@ impl NetworkSimulator {
             .remove(socket_addr);
     }
 }
+
+pub(super) fn is_broadcast(socket_addr: &SocketAddr) -> bool {
+    return match socket_addr.ip() {
+        IpAddr::V4(ip_addr_v4) => ip_addr_v4.is_broadcast(),
+        IpAddr::V6(_) => false,
+    };
+}
@@ -1,5 +1,9 @@
 use crate::real_time::simulation::net::host_simulator::HostSimulator;
-use std::io::Error;
+use crate::real_time::simulation::net::network_simulator::is_broadcast;
+use std::io::{
+    Error,
+    ErrorKind,
+};
 use std::net::SocketAddr;
 use std::sync::{
     Arc,
@@ -14,6 +18,7 @@ pub struct UdpSocketSimulator {
 struct Internal {
     socket_addr: SocketAddr,
     host_simulator: HostSimulator,
+    broadcast: bool,
 }
 
 impl UdpSocketSimulator {
@@ -21,6 +26,7 @@ impl UdpSocketSimulator {
         let internal = Internal {
             socket_addr,
             host_simulator: host_simulator.clone(),
+            broadcast: false,
         };
 
         return Self {
@@ -32,8 +38,17 @@ impl UdpSocketSimulator {
         return self.internal.lock().unwrap().socket_addr;
     }
 
+    pub fn set_broadcast(&self, broadcast: bool) {
+        self.internal.lock().unwrap().broadcast = broadcast;
+    }
+
     pub fn send_to(&mut self, buf: &[u8], socket_addr: &SocketAddr) -> Result<usize, Error> {
         let guard = self.internal.lock().unwrap();
+
+        // Like a real socket, sending to the broadcast address must be enabled first
+        if is_broadcast(socket_addr) && !guard.broadcast {
+            return Err(Error::from(ErrorKind::PermissionDenied));
+        }
         guard
             .host_simulator
             .send_udp(&guard.socket_addr, socket_addr, buf);
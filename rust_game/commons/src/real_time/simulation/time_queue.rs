@@ -67,7 +67,9 @@ impl TimeQueue {
     }
 
     pub fn remove_event(&self, id: usize) {
-        self.internal.lock().unwrap().remove_event(id);
+        // The event is dropped after the lock is released because dropping
+        // its function can add events
+        let _event = self.internal.lock().unwrap().remove_event(id);
     }
 
     pub fn run_events(&self) {
@@ -125,26 +127,12 @@ impl TimeQueueInternal {
 
         self.queue.insert(index, event);
 
-        return index;
+        return event_id;
     }
 
-    fn remove_event(&mut self, id: usize) {
-        let mut index = 0;
-        while index < self.queue.len() {
-            let mut remove = false;
-
-            if let Some(event) = self.queue.get(index) {
-                if event.get_id() == id {
-                    remove = true;
-                }
-            }
-
-            if remove {
-                self.queue.remove(index);
-            } else {
-                index = index + 1;
-            }
-        }
+    fn remove_event(&mut self, id: usize) -> Option<Event> {
+        let index = self.queue.iter().position(|event| event.get_id() == id)?;
+        return self.queue.remove(index);
     }
 
     fn pop_next_event_at_or_before(&mut self, time_value: TimeValue) -> Option<Event> {
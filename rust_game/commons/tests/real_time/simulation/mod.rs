@@ -1,3 +1,4 @@
 mod queue_test;
 mod simulated_time_provider_test;
 mod tcp_test;
+mod udp_test;
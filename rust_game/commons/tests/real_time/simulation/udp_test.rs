@@ -0,0 +1,83 @@
+use commons::logging::LoggingConfigBuilder;
+use commons::real_time::net::udp::UdpReadHandlerBuilder;
+use commons::real_time::simulation::SingleThreadedFactory;
+use commons::real_time::EventHandlerStopper;
+use log::LevelFilter;
+use std::io::ErrorKind;
+use std::net::{
+    IpAddr,
+    Ipv4Addr,
+    SocketAddr,
+};
+use std::ops::ControlFlow::Continue;
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+const PORT: u16 = 1234;
+const OTHER_PORT: u16 = 1235;
+
+#[test]
+fn test_udp_broadcast() {
+    LoggingConfigBuilder::new()
+        .add_console_appender()
+        .init(LevelFilter::Info);
+
+    let factory = SingleThreadedFactory::new();
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let _first_stopper = spawn_reader(&factory, 1, PORT, &received);
+    let _second_stopper = spawn_reader(&factory, 2, PORT, &received);
+    let _other_port_stopper = spawn_reader(&factory, 3, OTHER_PORT, &received);
+
+    let client_factory = factory.clone_for_new_host(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 4)));
+    let mut udp_socket = client_factory
+        .bind_udp_socket(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), PORT))
+        .unwrap();
+
+    let broadcast_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::BROADCAST), PORT);
+
+    // Broadcasting must be enabled on the socket first
+    let error = udp_socket.send_to(&[1], &broadcast_addr).unwrap_err();
+    assert_eq!(ErrorKind::PermissionDenied, error.kind());
+
+    udp_socket.set_broadcast(true).unwrap();
+    udp_socket.send_to(&[2], &broadcast_addr).unwrap();
+
+    factory.get_time_queue().run_events();
+
+    let mut received = received.lock().unwrap().clone();
+    received.sort();
+
+    let source = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 4)), PORT);
+    assert_eq!(vec![(1, source, 2), (2, source, 2)], received);
+}
+
+fn spawn_reader(
+    factory: &SingleThreadedFactory,
+    host: u8,
+    port: u16,
+    received: &Arc<Mutex<Vec<(u8, SocketAddr, u8)>>>,
+) -> EventHandlerStopper {
+    let host_factory = factory.clone_for_new_host(IpAddr::V4(Ipv4Addr::new(127, 0, 0, host)));
+
+    // Bound to the unspecified address so that it can receive broadcasts
+    let udp_socket = host_factory
+        .bind_udp_socket(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port))
+        .unwrap();
+
+    let received = received.clone();
+
+    return UdpReadHandlerBuilder::new_thread(
+        &host_factory.into(),
+        format!("UdpReader-{}", host),
+        udp_socket,
+        move |source, buf: &[u8]| {
+            received.lock().unwrap().push((host, source, buf[0]));
+            return Continue(());
+        },
+    )
+    .unwrap();
+}
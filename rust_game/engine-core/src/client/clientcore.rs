@@ -14,12 +14,25 @@ use crate::game_time::{
     GameTimerScheduler,
 };
 use crate::interface::{
+    ConnectConfig,
+    ConnectionState,
+    EngineError,
+    FrameObserver,
     GameTrait,
     InitialInformation,
+    InputEventHandler,
     RenderReceiverMessage,
 };
-use crate::messaging::ToServerInputMessage;
-use commons::real_time::net::tcp::TcpReadHandlerBuilder;
+use crate::messaging::{
+    ToServerInputMessage,
+    ToServerMessageTCP,
+};
+use crate::server::RoomId;
+use commons::real_time::net::tcp::{
+    TcpReadHandlerBuilder,
+    TcpReader,
+    TcpStream,
+};
 use commons::real_time::net::udp::UdpReadHandlerBuilder;
 use commons::real_time::timer_service::{
     IdleTimerService,
@@ -35,10 +48,14 @@ use commons::real_time::{
     ReceiveMetaData,
     Sender,
 };
+use commons::time::TimeValue;
 use log::{
+    debug,
+    info,
     trace,
     warn,
 };
+use std::mem::take;
 use std::net::{
     Ipv4Addr,
     SocketAddr,
@@ -47,25 +64,53 @@ use std::net::{
 
 pub enum ClientCoreEvent<Game: GameTrait> {
     OnInitialInformation(InitialInformation<Game>),
-    OnInputEvent(Game::ClientInputEvent),
+    /// An input event for the local player at an index into the input
+    /// handlers of the client
+    OnInputEvent(usize, Game::ClientInputEvent),
     GameTimerTick,
     CompletedPing(CompletedPing),
+    AddFrameObserver(Box<dyn FrameObserver<Game>>),
+    SendMessage(Game::ClientMessage),
+    /// The connection with this number to the server closed
+    ConnectionLost(usize),
 }
 
-pub struct ClientCore<Game: GameTrait> {
+pub struct ClientCore<Game: GameTrait, InputHandler: InputEventHandler<Game = Game>> {
     factory: Factory,
     sender: EventSender<ClientCoreEvent<Game>>,
     server_ip: Ipv4Addr,
-    tcp_input_sender: EventHandlerStopper,
-    tcp_output_sender: EventSender<()>,
+    room_id: Option<RoomId>,
+    message_sender: Sender<Game::ServerMessage>,
+    connect_config: ConnectConfig,
+    connection_state_sender: Sender<ConnectionState>,
+    connection: Connection<Game>,
+    // Counts the connections made, so that the closing of a replaced
+    // connection can be told apart
+    connection_count: usize,
+    // Messages sent before the connection to the server is established
+    pending_messages: Vec<Game::ClientMessage>,
     render_receiver_sender: Sender<RenderReceiverMessage<Game>>,
+    // One per local player
+    input_event_handlers: Vec<InputHandler>,
+    // Frame observers added before the game starts
+    pending_frame_observers: Vec<Box<dyn FrameObserver<Game>>>,
     running_state: Option<RunningState<Game>>,
 }
 
+enum Connection<Game: GameTrait> {
+    Connecting {
+        attempt: usize,
+        next_attempt_time: TimeValue,
+    },
+    Connected {
+        _tcp_input_sender: EventHandlerStopper,
+        tcp_output_sender: EventSender<ToServerMessageTCP<Game>>,
+    },
+}
+
 //TODO: don't start client core before hello
 struct RunningState<Game: GameTrait> {
     frame_manager: FrameManager<Game>,
-    input_event_handler: Game::ClientInputEventHandler,
     timer_service: TimerService<(), ClientGameTimerObserver<Game>>,
     game_timer: GameTimerScheduler,
     udp_input_sender: EventHandlerStopper,
@@ -74,46 +119,172 @@ struct RunningState<Game: GameTrait> {
     input_grace_period_frames: usize,
 }
 
-impl<Game: GameTrait> ClientCore<Game> {
+impl<Game: GameTrait, InputHandler: InputEventHandler<Game = Game>> ClientCore<Game, InputHandler> {
     pub fn new(
         factory: Factory,
         server_ip: Ipv4Addr,
         sender: EventSender<ClientCoreEvent<Game>>,
         render_receiver_sender: Sender<RenderReceiverMessage<Game>>,
+        input_event_handlers: Vec<InputHandler>,
+        room_id: Option<RoomId>,
+        message_sender: Sender<Game::ServerMessage>,
+        connect_config: ConnectConfig,
+        connection_state_sender: Sender<ConnectionState>,
     ) -> Self {
-        let socket_addr_v4 = SocketAddrV4::new(server_ip.clone(), Game::TCP_PORT);
-        let socket_addr = SocketAddr::from(socket_addr_v4);
-
-        let (tcp_sender, tcp_receiver) = factory.connect_tcp(socket_addr).unwrap();
-
-        let tcp_input = TcpInput::<Game>::new(sender.clone(), render_receiver_sender.clone());
-
-        let tcp_input_sender = TcpReadHandlerBuilder::new_thread(
-            &factory,
-            "ClientTcpInput".to_string(),
-            tcp_receiver,
-            tcp_input,
-        )
-        .unwrap();
-
-        let tcp_output_sender = EventHandlerBuilder::new_thread(
-            &factory,
-            "ClientTcpOutput".to_string(),
-            TcpOutput::new(tcp_sender),
-        )
-        .unwrap();
+        let next_attempt_time = factory.get_time_source().now();
 
         return Self {
             factory,
             sender,
             server_ip,
-            tcp_input_sender,
-            tcp_output_sender,
+            room_id,
+            message_sender,
+            connect_config,
+            connection_state_sender,
+            connection: Connection::Connecting {
+                attempt: 1,
+                next_attempt_time,
+            },
+            connection_count: 0,
+            pending_messages: Vec::new(),
             render_receiver_sender,
+            input_event_handlers,
+            pending_frame_observers: Vec::new(),
             running_state: None,
         };
     }
 
+    /// Connects to the server if the next attempt is due, otherwise waits
+    /// until it is
+    fn on_none_pending(&mut self) -> EventHandleResult {
+        let (attempt, next_attempt_time) = match self.connection {
+            Connection::Connecting {
+                attempt,
+                next_attempt_time,
+            } => (attempt, next_attempt_time),
+            Connection::Connected { .. } => return EventHandleResult::WaitForNextEvent,
+        };
+
+        let now = self.factory.get_time_source().now();
+        if next_attempt_time.is_after(&now) {
+            return EventHandleResult::WaitForNextEventOrTimeout(
+                next_attempt_time.duration_since(&now),
+            );
+        }
+
+        let socket_addr = SocketAddr::V4(SocketAddrV4::new(self.server_ip, Game::TCP_PORT));
+
+        let error = match self.factory.connect_tcp(socket_addr) {
+            Ok((tcp_stream, tcp_reader)) => return self.on_connected(tcp_stream, tcp_reader),
+            Err(error) => error,
+        };
+
+        if let Some(max_attempts) = self.connect_config.get_max_attempts() {
+            if attempt >= max_attempts {
+                warn!(
+                    "Failed to connect to the server after {:?} attempts: {:?}",
+                    attempt, error
+                );
+                self.send_connection_state(ConnectionState::Failed(EngineError::Connect(error)));
+                return EventHandleResult::StopThread;
+            }
+        }
+
+        let backoff = self.connect_config.get_backoff(attempt);
+
+        info!(
+            "Failed to connect to the server: {:?}.  Retrying in {:?}",
+            error, backoff
+        );
+
+        self.connection = Connection::Connecting {
+            attempt: attempt + 1,
+            next_attempt_time: now + backoff,
+        };
+        self.send_connection_state(ConnectionState::Connecting {
+            attempt: attempt + 1,
+        });
+
+        return EventHandleResult::WaitForNextEventOrTimeout(backoff);
+    }
+
+    fn on_connected(&mut self, tcp_stream: TcpStream, tcp_reader: TcpReader) -> EventHandleResult {
+        self.send_connection_state(ConnectionState::Connected);
+
+        self.connection_count += 1;
+        let connection_number = self.connection_count;
+
+        let tcp_input = TcpInput::<Game>::new(
+            self.sender.clone(),
+            self.render_receiver_sender.clone(),
+            self.message_sender.clone(),
+        );
+
+        // The read handler stops when the connection closes
+        let connection_lost_sender = self.sender.clone();
+        let tcp_input_sender = match TcpReadHandlerBuilder::new(&self.factory)
+            .spawn_thread_with_call_back(
+                "ClientTcpInput".to_string(),
+                tcp_reader,
+                tcp_input,
+                move |_| {
+                    let _ = connection_lost_sender
+                        .send_event(ClientCoreEvent::ConnectionLost(connection_number));
+                },
+            ) {
+            Ok(tcp_input_sender) => tcp_input_sender,
+            Err(error) => return self.fail(EngineError::Io(error)),
+        };
+
+        let tcp_output_sender = match EventHandlerBuilder::new_thread(
+            &self.factory,
+            "ClientTcpOutput".to_string(),
+            TcpOutput::<Game>::new(tcp_stream),
+        ) {
+            Ok(tcp_output_sender) => tcp_output_sender,
+            Err(error) => return self.fail(EngineError::Io(error)),
+        };
+
+        // Tell the server which room to join before anything else
+        let mut messages = vec![ToServerMessageTCP::JoinRoom {
+            room_id: self.room_id,
+            local_player_count: self.input_event_handlers.len(),
+            session_token: self.connect_config.get_session_token(),
+        }];
+        messages.extend(
+            take(&mut self.pending_messages)
+                .into_iter()
+                .map(ToServerMessageTCP::Message),
+        );
+
+        for message in messages {
+            if tcp_output_sender.send_event(message).is_err() {
+                return self.fail(EngineError::ThreadStopped);
+            }
+        }
+
+        self.connection = Connection::Connected {
+            _tcp_input_sender: tcp_input_sender,
+            tcp_output_sender,
+        };
+
+        self.send_connection_state(ConnectionState::WaitingForGameStart);
+
+        return EventHandleResult::WaitForNextEvent;
+    }
+
+    fn fail(&mut self, error: EngineError) -> EventHandleResult {
+        warn!("The client failed: {:?}", error);
+        self.send_connection_state(ConnectionState::Failed(error));
+        return EventHandleResult::StopThread;
+    }
+
+    fn send_connection_state(&self, connection_state: ConnectionState) {
+        if self.connection_state_sender.send(connection_state).is_err() {
+            debug!("The Client is no longer reading the connection state");
+        }
+    }
+
     fn on_initial_information(
         &mut self,
         initial_information: InitialInformation<Game>,
@@ -123,16 +294,25 @@ impl<Game: GameTrait> ClientCore<Game> {
             return EventHandleResult::TryForNextEvent;
         }
 
+        if initial_information.get_local_player_indices().len() != self.input_event_handlers.len() {
+            return self.fail(EngineError::ProtocolViolation(
+                "The server assigned a different number of local players than requested",
+            ));
+        }
+
         //TODO: maybe consolidate building of the manager into its own method
         let client_manager_observer =
             ClientManagerObserver::<Game>::new(self.render_receiver_sender.clone());
 
-        let frame_manager = FrameManager::new(
+        let frame_manager = match FrameManager::new(
             &self.factory,
             client_manager_observer,
+            take(&mut self.pending_frame_observers),
             initial_information.clone(),
-        )
-        .unwrap();
+        ) {
+            Ok(frame_manager) => frame_manager,
+            Err(error) => return self.fail(error),
+        };
 
         let mut idle_timer_service = IdleTimerService::new();
 
@@ -144,38 +324,65 @@ impl<Game: GameTrait> ClientCore<Game> {
             ClientGameTimerObserver::new(self.sender.clone()),
         );
 
-        let timer_service = idle_timer_service.start(&self.factory).unwrap();
+        let timer_service = match idle_timer_service.start(&self.factory) {
+            Ok(timer_service) => timer_service,
+            Err(error) => return self.fail(EngineError::Io(error)),
+        };
 
         let server_udp_socket_addr =
             SocketAddr::V4(SocketAddrV4::new(self.server_ip, Game::UDP_PORT));
 
-        let udp_socket = self.factory.bind_udp_ephemeral_port().unwrap();
+        let udp_socket = match self.factory.bind_udp_ephemeral_port() {
+            Ok(udp_socket) => udp_socket,
+            Err(error) => return self.fail(EngineError::Bind(error)),
+        };
 
-        let udp_input_sender = UdpReadHandlerBuilder::new_thread(
+        let udp_input_socket = match udp_socket.try_clone() {
+            Ok(udp_input_socket) => udp_input_socket,
+            Err(error) => return self.fail(EngineError::Io(error)),
+        };
+
+        let udp_input = match UdpInput::<Game>::new(
+            self.factory.get_time_source().clone(),
+            self.sender.clone(),
+            frame_manager.clone(),
+        ) {
+            Ok(udp_input) => udp_input,
+            Err(error) => return self.fail(EngineError::Io(error)),
+        };
+
+        let udp_input_sender = match UdpReadHandlerBuilder::new_thread(
             &self.factory,
             "ClientUdpInput".to_string(),
-            udp_socket.try_clone().unwrap(),
-            UdpInput::<Game>::new(
-                self.factory.get_time_source().clone(),
-                self.sender.clone(),
-                frame_manager.clone(),
-            )
-            .unwrap(),
-        )
-        .unwrap();
-
-        //TODO: unwrap after try_clone is not good
-        let udp_output_sender = EventHandlerBuilder::new_thread(
+            udp_input_socket,
+            udp_input,
+        ) {
+            Ok(udp_input_sender) => udp_input_sender,
+            Err(error) => return self.fail(EngineError::Io(error)),
+        };
+
+        let udp_output_socket = match udp_socket.try_clone() {
+            Ok(udp_output_socket) => udp_output_socket,
+            Err(error) => return self.fail(EngineError::Io(error)),
+        };
+
+        let udp_output_sender = match EventHandlerBuilder::new_thread(
             &self.factory,
             "ClientUdpOutput".to_string(),
             UdpOutput::<Game>::new(
                 self.factory.get_time_source().clone(),
                 server_udp_socket_addr,
-                udp_socket.try_clone().unwrap(),
+                udp_output_socket,
                 initial_information.clone(),
             ),
-        )
-        .unwrap();
+        ) {
+            Ok(udp_output_sender) => udp_output_sender,
+            Err(error) => return self.fail(EngineError::Io(error)),
+        };
+
+        let first_frame_index = initial_information
+            .get_server_config()
+            .get_first_frame_index();
 
         let input_grace_period_frames = initial_information
             .get_server_config()
@@ -185,7 +392,6 @@ impl<Game: GameTrait> ClientCore<Game> {
 
         self.running_state = Some(RunningState {
             frame_manager,
-            input_event_handler: Game::new_input_event_handler(),
             timer_service,
             game_timer,
             udp_input_sender,
@@ -194,17 +400,111 @@ impl<Game: GameTrait> ClientCore<Game> {
             input_grace_period_frames,
         });
 
+        self.send_connection_state(ConnectionState::InGame);
+
         // TODO: this causes the first client ping to be requested.  If the first
         // ping is dropped (its udp), then the client clock will never start.
         // There should probably be some retry logic for this.
-        return self.send_new_frame_index(FrameIndex::zero());
+        return self.send_new_frame_index(first_frame_index);
     }
 
-    fn on_input_event(&mut self, input_event: Game::ClientInputEvent) -> EventHandleResult {
-        if let Some(ref mut running_state) = self.running_state {
-            Game::handle_input_event(&mut running_state.input_event_handler, input_event);
+    /// Reconnects to the standby server, if there is one, when the
+    /// connection to the server closes during the game
+    fn on_connection_lost(&mut self, connection_number: usize) -> EventHandleResult {
+        if connection_number != self.connection_count {
+            return EventHandleResult::TryForNextEvent;
+        }
+
+        let session_token = match &self.running_state {
+            Some(running_state) => running_state.initial_information.get_session_token(),
+            None => None,
+        };
+
+        let (standby_server_ip, session_token) =
+            match (self.connect_config.get_standby_server_ip(), session_token) {
+                (Some(standby_server_ip), Some(session_token)) => {
+                    (standby_server_ip, session_token)
+                }
+                _ => {
+                    info!("The connection to the server closed");
+                    return EventHandleResult::TryForNextEvent;
+                }
+            };
+
+        info!(
+            "The connection to the server closed.  Reconnecting to the standby server at {:?}",
+            standby_server_ip
+        );
+
+        // The standby sends new InitialInformation, so the game starts over
+        // from the last authoritative frame it was sent
+        self.running_state = None;
+        self.server_ip = standby_server_ip;
+        self.connect_config.set_standby_server_ip(None);
+        self.connect_config.set_session_token(Some(session_token));
+        self.connection = Connection::Connecting {
+            attempt: 1,
+            next_attempt_time: self.factory.get_time_source().now(),
+        };
+        self.send_connection_state(ConnectionState::Connecting { attempt: 1 });
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_add_frame_observer(
+        &mut self,
+        frame_observer: Box<dyn FrameObserver<Game>>,
+    ) -> EventHandleResult {
+        match &self.running_state {
+            Some(running_state) => {
+                if running_state
+                    .frame_manager
+                    .add_frame_observer(frame_observer)
+                    .is_err()
+                {
+                    warn!("Failed to send FrameObserver to the FrameManager");
+                    return EventHandleResult::StopThread;
+                }
+            }
+            None => self.pending_frame_observers.push(frame_observer),
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_send_message(&mut self, message: Game::ClientMessage) -> EventHandleResult {
+        let tcp_output_sender = match &self.connection {
+            Connection::Connecting { .. } => {
+                self.pending_messages.push(message);
+                return EventHandleResult::TryForNextEvent;
+            }
+            Connection::Connected {
+                tcp_output_sender, ..
+            } => tcp_output_sender,
+        };
+
+        let send_result = tcp_output_sender.send_event(ToServerMessageTCP::Message(message));
+
+        if send_result.is_err() {
+            warn!("Failed to send ClientMessage to TcpOutput");
+            return EventHandleResult::StopThread;
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_input_event(
+        &mut self,
+        local_player: usize,
+        input_event: Game::ClientInputEvent,
+    ) -> EventHandleResult {
+        match self.input_event_handlers.get_mut(local_player) {
+            Some(input_event_handler) => input_event_handler.handle_input_event(input_event),
+            None => warn!(
+                "Received an input event for local player {:?}, which does not exist",
+                local_player
+            ),
         }
-        //Else: No-op, just discard the input
 
         return EventHandleResult::TryForNextEvent;
     }
@@ -229,26 +529,6 @@ impl<Game: GameTrait> ClientCore<Game> {
         if let Some(ref mut running_state) = self.running_state {
             trace!("TimeMessage step_index: {:?}", frame_index);
 
-            let message = ToServerInputMessage::<Game>::new(
-                //TODO: message or last message?
-                //TODO: define strict and consistent rules for how real time relates to ticks, input deadlines and display states
-                frame_index,
-                running_state.initial_information.get_player_index(),
-                Game::get_input(&mut running_state.input_event_handler),
-            );
-
-            let send_result = running_state.frame_manager.insert_input(
-                message.get_frame_index(),
-                message.get_player_index(),
-                message.get_input().clone(),
-                false,
-            );
-
-            if send_result.is_err() {
-                warn!("Failed to send InputMessage to Game Manager");
-                return EventHandleResult::StopThread;
-            }
-
             let send_result = running_state
                 .udp_output_sender
                 .send_event(UdpOutputEvent::FrameIndex(frame_index));
@@ -258,13 +538,42 @@ impl<Game: GameTrait> ClientCore<Game> {
                 return EventHandleResult::StopThread;
             }
 
-            let send_result = running_state
-                .udp_output_sender
-                .send_event(UdpOutputEvent::InputMessageEvent(message));
+            let player_indices = running_state
+                .initial_information
+                .get_local_player_indices()
+                .iter();
 
-            if send_result.is_err() {
-                warn!("Failed to send InputMessage to Udp Output");
-                return EventHandleResult::StopThread;
+            for (input_event_handler, player_index) in
+                self.input_event_handlers.iter_mut().zip(player_indices)
+            {
+                let message = ToServerInputMessage::<Game>::new(
+                    //TODO: message or last message?
+                    //TODO: define strict and consistent rules for how real time relates to ticks, input deadlines and display states
+                    frame_index,
+                    *player_index,
+                    input_event_handler.get_input(frame_index),
+                );
+
+                let send_result = running_state.frame_manager.insert_input(
+                    message.get_frame_index(),
+                    message.get_player_index(),
+                    message.get_input().clone(),
+                    false,
+                );
+
+                if send_result.is_err() {
+                    warn!("Failed to send InputMessage to Game Manager");
+                    return EventHandleResult::StopThread;
+                }
+
+                let send_result = running_state
+                    .udp_output_sender
+                    .send_event(UdpOutputEvent::InputMessageEvent(message));
+
+                if send_result.is_err() {
+                    warn!("Failed to send InputMessage to Udp Output");
+                    return EventHandleResult::StopThread;
+                }
             }
 
             let send_result = running_state.frame_manager.advance_frame_index(frame_index);
@@ -318,7 +627,9 @@ impl<Game: GameTrait> ClientCore<Game> {
     }
 }
 
-impl<Game: GameTrait> HandleEvent for ClientCore<Game> {
+impl<Game: GameTrait, InputHandler: InputEventHandler<Game = Game>> HandleEvent
+    for ClientCore<Game, InputHandler>
+{
     type Event = ClientCoreEvent<Game>;
     type ThreadReturn = ();
 
@@ -327,16 +638,31 @@ impl<Game: GameTrait> HandleEvent for ClientCore<Game> {
             ClientCoreEvent::OnInitialInformation(initial_information) => {
                 self.on_initial_information(initial_information)
             }
-            ClientCoreEvent::OnInputEvent(client_input_event) => {
-                self.on_input_event(client_input_event)
+            ClientCoreEvent::OnInputEvent(local_player, client_input_event) => {
+                self.on_input_event(local_player, client_input_event)
             }
             ClientCoreEvent::GameTimerTick => self.on_game_timer_tick(),
             ClientCoreEvent::CompletedPing(completed_ping) => {
                 self.on_completed_ping(completed_ping)
             }
+            ClientCoreEvent::AddFrameObserver(frame_observer) => {
+                self.on_add_frame_observer(frame_observer)
+            }
+            ClientCoreEvent::SendMessage(message) => self.on_send_message(message),
+            ClientCoreEvent::ConnectionLost(connection_number) => {
+                self.on_connection_lost(connection_number)
+            }
         };
     }
 
+    fn on_timeout(&mut self) -> EventHandleResult {
+        return self.on_none_pending();
+    }
+
+    fn on_channel_empty(&mut self) -> EventHandleResult {
+        return self.on_none_pending();
+    }
+
     fn on_stop_self(self) -> Self::ThreadReturn {
         return ();
     }
@@ -28,12 +28,15 @@ impl<Game: GameTrait> ObserveFrames for ClientManagerObserver<Game> {
 
     fn new_state(
         &self,
-        _is_state_authoritative: bool,
-        step_message: FrameIndexAndState<Game>,
+        is_state_authoritative: bool,
+        state_message: FrameIndexAndState<Game>,
     ) -> ControlFlow<()> {
         let result = self
             .render_receiver_sender
-            .send(RenderReceiverMessage::StepMessage(step_message));
+            .send(RenderReceiverMessage::StepMessage {
+                is_authoritative: is_state_authoritative,
+                state_message,
+            });
 
         if result.is_err() {
             warn!("Failed to send new state to render receiver");
@@ -9,6 +9,7 @@ use commons::real_time::{
     Sender,
 };
 use log::{
+    debug,
     info,
     warn,
 };
@@ -19,17 +20,20 @@ pub struct TcpInput<Game: GameTrait> {
     player_index: Option<usize>,
     client_core_sender: EventSender<ClientCoreEvent<Game>>,
     render_data_sender: Sender<RenderReceiverMessage<Game>>,
+    message_sender: Sender<Game::ServerMessage>,
 }
 
 impl<Game: GameTrait> TcpInput<Game> {
     pub fn new(
         client_core_sender: EventSender<ClientCoreEvent<Game>>,
         render_data_sender: Sender<RenderReceiverMessage<Game>>,
+        message_sender: Sender<Game::ServerMessage>,
     ) -> Self {
         return Self {
             player_index: None,
             client_core_sender,
             render_data_sender,
+            message_sender,
         };
     }
 }
@@ -67,6 +71,16 @@ impl<Game: GameTrait> HandleTcpRead for TcpInput<Game> {
                     return Break(());
                 }
             }
+            ToClientMessageTCP::Message(message) => {
+                // The game may have dropped its message receiver, which is
+                // no reason to stop reading from the server.
+                if self.message_sender.send(message).is_err() {
+                    debug!("Dropped a ServerMessage with no receiver");
+                }
+            }
+            ToClientMessageTCP::Snapshot(_) | ToClientMessageTCP::StandbyState(_) => {
+                warn!("Ignoring a snapshot, which is only sent to standby servers");
+            }
         }
 
         return Continue(());
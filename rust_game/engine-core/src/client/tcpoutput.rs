@@ -1,26 +1,44 @@
+use crate::interface::GameTrait;
+use crate::messaging::ToServerMessageTCP;
 use commons::real_time::{
     net::tcp::TcpStream,
     EventHandleResult,
     HandleEvent,
     ReceiveMetaData,
 };
+use log::warn;
+use std::marker::PhantomData;
 
 //TODO: Send response to time messages to calculate ping
-pub struct TcpOutput {
+pub struct TcpOutput<Game: GameTrait> {
     tcp_stream: TcpStream,
+    phantom: PhantomData<Game>,
 }
 
-impl TcpOutput {
+impl<Game: GameTrait> TcpOutput<Game> {
     pub fn new(tcp_stream: TcpStream) -> Self {
-        return Self { tcp_stream };
+        return Self {
+            tcp_stream,
+            phantom: PhantomData,
+        };
     }
 }
 
-impl HandleEvent for TcpOutput {
-    type Event = ();
+impl<Game: GameTrait> HandleEvent for TcpOutput<Game> {
+    type Event = ToServerMessageTCP<Game>;
     type ThreadReturn = ();
 
-    fn on_event(&mut self, _: ReceiveMetaData, _: Self::Event) -> EventHandleResult {
+    fn on_event(&mut self, _: ReceiveMetaData, event: Self::Event) -> EventHandleResult {
+        if let Err(error) = self.tcp_stream.write(&event) {
+            warn!("Failed to write to the server: {:?}", error);
+            return EventHandleResult::StopThread;
+        }
+
+        if let Err(error) = self.tcp_stream.flush() {
+            warn!("Failed to flush to the server: {:?}", error);
+            return EventHandleResult::StopThread;
+        }
+
         EventHandleResult::TryForNextEvent
     }
 
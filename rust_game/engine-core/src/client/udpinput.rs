@@ -80,6 +80,17 @@ impl<Game: GameTrait> UdpInput<Game> {
                     return ControlFlow::Break(());
                 }
             }
+            UdpToClientMessage::ServerInputMessage(server_input_message) => {
+                let result = self.frame_manager.insert_server_inputs(
+                    server_input_message.get_first_frame_index(),
+                    server_input_message.take_server_inputs(),
+                );
+
+                if result.is_err() {
+                    warn!("Failed to send ServerInputMessage to Game Manager");
+                    return ControlFlow::Break(());
+                }
+            }
             UdpToClientMessage::StateMessage(state_message) => {
                 let result = self
                     .frame_manager
@@ -101,9 +112,21 @@ impl<Game: GameTrait> UdpInput<Game> {
 
 impl<Game: GameTrait> HandleUdpRead for UdpInput<Game> {
     fn on_read(&mut self, peer_addr: SocketAddr, buf: &[u8]) -> ControlFlow<()> {
-        let fragment = MessageFragment::from_vec(buf.to_vec());
+        let result = MessageFragment::from_vec(buf.to_vec())
+            .and_then(|fragment| self.fragment_assembler.add_fragment(fragment));
+
+        let message_buf = match result {
+            Ok(message_buf) => message_buf,
+            Err(error) => {
+                warn!(
+                    "Dropping a malformed fragment from {:?}: {:?}",
+                    peer_addr, error
+                );
+                return ControlFlow::Continue(());
+            }
+        };
 
-        if let Some(message_buf) = self.fragment_assembler.add_fragment(fragment) {
+        if let Some(message_buf) = message_buf {
             match rmp_serde::from_slice(&message_buf) {
                 Ok(message) => {
                     //Why does this crash the client?
@@ -7,6 +7,7 @@ use crate::messaging::{
     Fragmenter,
     ToServerInputMessage,
     UdpToServerMessage,
+    UdpToServerPacket,
 };
 use crate::FrameIndex;
 use commons::real_time::net::udp::UdpSocket;
@@ -62,14 +63,17 @@ impl<Game: GameTrait> UdpOutput<Game> {
 
     fn on_input_message(&mut self, input_message: ToServerInputMessage<Game>) -> EventHandleResult {
         let message = UdpToServerMessage::<Game>::Input(input_message);
-        self.send_message(&message);
+        self.send_message(message);
 
         return EventHandleResult::TryForNextEvent;
     }
 
-    fn send_message(&mut self, message: &UdpToServerMessage<Game>) {
+    fn send_message(&mut self, message: UdpToServerMessage<Game>) {
+        let room_id = self.initial_information.get_server_config().get_room_id();
+        let packet = UdpToServerPacket::new(room_id, message);
+
         //TODO: use write instead of to_vec
-        let buf = rmp_serde::to_vec(&message).unwrap();
+        let buf = rmp_serde::to_vec(&packet).unwrap();
         let fragments = self.fragmenter.make_fragments(buf);
 
         for fragment in fragments {
@@ -98,7 +102,7 @@ impl<Game: GameTrait> UdpOutput<Game> {
             self.time_source.now(),
         );
         let ping_request = UdpToServerMessage::PingRequest(ping_request);
-        self.send_message(&ping_request);
+        self.send_message(ping_request);
         return EventHandleResult::TryForNextEvent;
     }
 }
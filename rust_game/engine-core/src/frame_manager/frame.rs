@@ -18,7 +18,12 @@ pub struct Frame<Game: GameTrait> {
     frame_index: FrameIndex,
     state: State<Game::State>,
     inputs: Vec<Input<Game::ClientInput>>,
+    predicted_inputs: Vec<Option<Game::ClientInput>>,
+    server_input: Input<Vec<Game::ServerInput>>,
     authoritative_input_count: usize,
+    // Whether the inputs and the predictions of missing inputs are all
+    // authoritative, as of the last time the next state was computed
+    are_inputs_or_predictions_authoritative: bool,
     need_to_compute_next_state: bool,
 }
 
@@ -44,6 +49,16 @@ pub enum Input<T> {
 }
 
 impl<T> Input<T> {
+    /// Returns the input value, if it is known
+    pub fn get_value(&self) -> Option<&T> {
+        match self {
+            Input::Pending => None,
+            Input::NonAuthoritative(value) => Some(value),
+            Input::Authoritative(value) => Some(value),
+            Input::AuthoritativeMissing => None,
+        }
+    }
+
     /// Returns true if the status of the input is authoritatively known.
     pub fn is_authoritative(&self) -> bool {
         match self {
@@ -80,24 +95,49 @@ impl<Game: GameTrait> Frame<Game> {
             frame_index: step_index,
             state: State::None,
             inputs,
+            predicted_inputs: vec![None; player_count],
+            server_input: Input::Pending,
             authoritative_input_count: 0,
+            are_inputs_or_predictions_authoritative: false,
             need_to_compute_next_state: true,
         };
     }
 
-    pub fn set_input(&mut self, player_index: usize, input: Input<Game::ClientInput>) {
+    /// Sets the [Input] of a player.  Returns true if the input replaces a
+    /// prediction that it doesn't match.
+    pub fn set_input(&mut self, player_index: usize, input: Input<Game::ClientInput>) -> bool {
         let current_input = &mut self.inputs[player_index];
 
         if current_input.is_authoritative() {
             warn!("Received a duplicate input where an authoritative one has already been received, ignorning it");
-            return;
+            return false;
         }
 
+        let is_mispredicted = match (&self.predicted_inputs[player_index], input.get_value()) {
+            (Some(predicted), Some(value)) => predicted != value,
+            _ => false,
+        };
+
         if input.is_authoritative() {
             self.authoritative_input_count = self.authoritative_input_count + 1;
         }
 
         *current_input = input;
+        self.predicted_inputs[player_index] = None;
+        self.need_to_compute_next_state = true;
+
+        return is_mispredicted;
+    }
+
+    /// Sets the authoritative [server inputs](GameTrait::ServerInput) for this
+    /// frame.  An empty vector means the server has no input for this frame.
+    pub fn set_server_input(&mut self, server_inputs: Vec<Game::ServerInput>) {
+        if self.server_input.is_authoritative() {
+            warn!("Received a duplicate server input where an authoritative one has already been received, ignorning it");
+            return;
+        }
+
+        self.server_input = Input::Authoritative(server_inputs);
         self.need_to_compute_next_state = true;
     }
 
@@ -119,8 +159,16 @@ impl<Game: GameTrait> Frame<Game> {
         ControlFlow::Continue(())
     }
 
+    pub fn is_input_authoritative(&self, player_index: usize) -> bool {
+        return self.inputs[player_index].is_authoritative();
+    }
+
+    pub fn is_server_input_authoritative(&self) -> bool {
+        return self.server_input.is_authoritative();
+    }
+
     pub fn are_inputs_complete(&self) -> bool {
-        self.authoritative_input_count == self.inputs.len()
+        self.authoritative_input_count == self.inputs.len() && self.server_input.is_authoritative()
     }
 
     pub fn set_state(
@@ -148,9 +196,31 @@ impl<Game: GameTrait> Frame<Game> {
         )
     }
 
+    /// Returns the known or predicted input of each player
+    pub fn get_inputs_or_predictions(&self) -> Vec<Option<Game::ClientInput>> {
+        return self
+            .inputs
+            .iter()
+            .zip(self.predicted_inputs.iter())
+            .map(|(input, predicted)| input.get_value().or(predicted.as_ref()).cloned())
+            .collect();
+    }
+
+    /// Returns true if [get_inputs_or_predictions](Self::get_inputs_or_predictions)
+    /// are the same on the server and every client
+    pub fn are_inputs_or_predictions_authoritative(&self) -> bool {
+        return self.are_inputs_or_predictions_authoritative;
+    }
+
+    /// Calculates the next state.  `previous_inputs` are the known or
+    /// predicted inputs of the previous frame, used to predict any inputs of
+    /// this frame that are not known.
     pub fn calculate_next_state(
         &mut self,
         initial_information: &InitialInformation<Game>,
+        is_server: bool,
+        previous_inputs: &[Option<Game::ClientInput>],
+        are_previous_inputs_authoritative: bool,
     ) -> Option<(Game::State, bool)> {
         if !self.need_to_compute_next_state {
             return None;
@@ -164,9 +234,42 @@ impl<Game: GameTrait> Frame<Game> {
             State::NonAuthoritative(state) => (state, false),
         };
 
-        let is_next_state_authoritative = self.are_inputs_complete() && is_authoritative;
+        // A missing input is predicted from the previous inputs, which a
+        // client may have predicted differently than the server
+        let has_missing_input = self
+            .inputs
+            .iter()
+            .any(|input| matches!(input, Input::AuthoritativeMissing));
+
+        self.are_inputs_or_predictions_authoritative = self.are_inputs_complete()
+            && (is_server || !has_missing_input || are_previous_inputs_authoritative);
+
+        // A client can't know the whole next state from a filtered state, or
+        // when the server runs logic that the client doesn't, so it waits for
+        // the server to send it
+        let is_next_state_authoritative = self.are_inputs_or_predictions_authoritative
+            && is_authoritative
+            && (is_server || !(Game::IS_STATE_FILTERED || Game::HAS_SERVER_ONLY_LOGIC));
+
+        for (player_index, input) in self.inputs.iter().enumerate() {
+            self.predicted_inputs[player_index] = match input {
+                Input::Pending | Input::AuthoritativeMissing => {
+                    Game::predict_input(previous_inputs, player_index, self.frame_index)
+                }
+                Input::NonAuthoritative(_) | Input::Authoritative(_) => None,
+            };
+        }
 
-        let arg = UpdateArg::new(initial_information, self.frame_index, state, &self.inputs);
+        let arg = UpdateArg::new(
+            initial_information,
+            self.frame_index,
+            state,
+            &self.inputs,
+            &self.predicted_inputs,
+            &self.server_input,
+            is_server,
+            is_authoritative,
+        );
 
         let next_state = Game::get_next_state(&arg);
 
@@ -179,6 +282,22 @@ impl<Game: GameTrait> Frame<Game> {
         return self.frame_index;
     }
 
+    pub fn get_state(&self) -> Option<&Game::State> {
+        match &self.state {
+            State::None => None,
+            State::Authoritative(state) => Some(state),
+            State::NonAuthoritative(state) => Some(state),
+        }
+    }
+
+    pub fn has_state(&self) -> bool {
+        match self.state {
+            State::None => false,
+            State::Authoritative(_) => true,
+            State::NonAuthoritative(_) => true,
+        }
+    }
+
     pub fn is_state_authoritative(&self) -> bool {
         match self.state {
             State::None => false,
@@ -1,13 +1,15 @@
-use crate::frame_manager::frame::Frame;
-use crate::frame_manager::ObserveFrames;
+use crate::frame_manager::{
+    FrameManagerCore,
+    ObserveFrames,
+};
 use crate::interface::{
+    EngineError,
+    FrameObserver,
     GameTrait,
     InitialInformation,
 };
-use crate::{
-    FrameIndex,
-    Input,
-};
+use crate::server::DroppedTrafficCounts;
+use crate::FrameIndex;
 use commons::real_time::{
     EventHandleResult,
     EventHandlerBuilder,
@@ -16,24 +18,23 @@ use commons::real_time::{
     HandleEvent,
     ReceiveMetaData,
 };
-use commons::utils::{
-    log_error,
-    unit_error,
-};
-use std::collections::vec_deque::VecDeque;
 use std::ops::ControlFlow;
 
-/// The [FrameManager] manages [Frames](Frame) and calculates new
-/// [states](GameTrait::State) from [Inputs](Input) in another thread.
+/// The [FrameManager] manages Frames and calculates new
+/// [states](GameTrait::State) from [Inputs](crate::Input) in another thread.
 ///
-/// The [FrameManager] inserts [Inputs](Input) from local and remote clients as
+/// The [FrameManager] inserts [Inputs](crate::Input) from local and remote clients as
 /// well as authoritative [States](GameTrait::State) from the server into a vector
-/// of [Frames](Frame).  When there are no queued events, the [FrameManager] will
+/// of Frames.  When there are no queued events, the [FrameManager] will
 /// iterate over the Frames, calculating new States, up to the current
-/// [FrameIndex] + 1.
+/// [FrameIndex] + 1, checking for new events after every
+/// [GameTrait::MAX_SIMULATED_FRAMES_PER_PASS] States.
+///
+/// The frame logic is implemented by [FrameManagerCore], which this runs in
+/// its own thread.
 ///
 /// New States and Inputs which are declared authoritatively missing can be observerd
-/// by implementing [ObserveFrames].
+/// by implementing [ObserveFrames], or by registering a [FrameObserver].
 #[derive(Clone)]
 pub struct FrameManager<Game: GameTrait> {
     sender: EventSender<Event<Game>>,
@@ -44,9 +45,17 @@ impl<Game: GameTrait> FrameManager<Game> {
     pub fn new<T: ObserveFrames<Game = Game>>(
         factory: &Factory,
         manager_observer: T,
+        frame_observers: Vec<Box<dyn FrameObserver<Game>>>,
         initial_information: InitialInformation<T::Game>,
-    ) -> Result<Self, ()> {
-        let event_handler = EventHandler::new(manager_observer, initial_information)?;
+    ) -> Result<Self, EngineError> {
+        let core = FrameManagerCore::new(
+            factory.get_time_source().clone(),
+            manager_observer,
+            frame_observers,
+            initial_information,
+        )?;
+
+        let event_handler = EventHandler { core };
 
         let thread_name = if T::IS_SERVER {
             "ServerManager"
@@ -56,19 +65,21 @@ impl<Game: GameTrait> FrameManager<Game> {
 
         let sender =
             EventHandlerBuilder::new_thread(factory, thread_name.to_string(), event_handler)
-                .map_err(log_error)?;
+                .map_err(EngineError::Io)?;
 
         Ok(Self { sender })
     }
 
     /// Advances the current [FrameIndex] of the [FrameManager].  The [FrameManager]
     /// will compute frames up to `frame_index + 1`.
-    pub fn advance_frame_index(&self, frame_index: FrameIndex) -> Result<(), ()> {
+    pub fn advance_frame_index(&self, frame_index: FrameIndex) -> Result<(), EngineError> {
         let event = Event::AdvanceFrameIndex(frame_index);
-        self.sender.send_event(event).map_err(unit_error)
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
     }
 
-    /// Inserts a [Input] with a [GameTrait::ClientInput] into the [Frame] at
+    /// Inserts a [Input](crate::Input) with a [GameTrait::ClientInput] into the frame at
     /// [FrameIndex].  If the [FrameIndex] is too far in the past, it input will
     /// be ignored.
     pub fn insert_input(
@@ -77,7 +88,7 @@ impl<Game: GameTrait> FrameManager<Game> {
         player_index: usize,
         input: Game::ClientInput,
         is_authoritative: bool,
-    ) -> Result<(), ()> {
+    ) -> Result<(), EngineError> {
         let event = Event::Input {
             frame_index,
             player_index,
@@ -85,34 +96,123 @@ impl<Game: GameTrait> FrameManager<Game> {
             is_authoritative,
         };
 
-        self.sender.send_event(event).map_err(unit_error)
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Validates an input from a remote player with
+    /// [GameTrait::validate_input] and inserts it as authoritative if it is
+    /// accepted.  This is only used by the server.
+    pub fn validate_and_insert_input(
+        &self,
+        frame_index: FrameIndex,
+        player_index: usize,
+        input: Game::ClientInput,
+    ) -> Result<(), EngineError> {
+        let event = Event::ValidatedInput {
+            frame_index,
+            player_index,
+            input,
+        };
+
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
     }
 
-    /// Inserts an [Input::AuthoritativeMissing] into the [Frame] at [FrameIndex].
+    /// Inserts an [Input::AuthoritativeMissing](crate::Input::AuthoritativeMissing) into the frame at [FrameIndex].
     /// If the [FrameIndex] is too far in the past, it input will be ignored.
     pub fn insert_missing_input(
         &self,
         frame_index: FrameIndex,
         player_index: usize,
-    ) -> Result<(), ()> {
+    ) -> Result<(), EngineError> {
         let event = Event::AuthoritativeMissingInput {
             frame_index,
             player_index,
         };
 
-        self.sender.send_event(event).map_err(unit_error)
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
     }
 
-    /// Inserts a [State](GameTrait::State) into the [Frame] at [FrameIndex].  
+    /// Inserts the authoritative [server inputs](GameTrait::ServerInput) into
+    /// the frame at [FrameIndex].  If the [FrameIndex] is too far in the past,
+    /// the inputs will be ignored.
+    pub fn insert_server_input(
+        &self,
+        frame_index: FrameIndex,
+        server_inputs: Vec<Game::ServerInput>,
+    ) -> Result<(), EngineError> {
+        let event = Event::ServerInput {
+            frame_index,
+            server_inputs,
+        };
+
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Inserts the resent authoritative [server inputs](GameTrait::ServerInput)
+    /// of consecutive frames, starting at `first_frame_index`.  Frames that
+    /// already have their server inputs or have been dropped are skipped.
+    pub fn insert_server_inputs(
+        &self,
+        first_frame_index: FrameIndex,
+        server_inputs: Vec<Vec<Game::ServerInput>>,
+    ) -> Result<(), EngineError> {
+        let event = Event::ServerInputs {
+            first_frame_index,
+            server_inputs,
+        };
+
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Inserts a [State](GameTrait::State) into the frame at [FrameIndex].  
     /// If the [FrameIndex] is too far in the past, it state will be ignored.
-    pub fn insert_state(&self, frame_index: FrameIndex, state: Game::State) -> Result<(), ()> {
+    pub fn insert_state(
+        &self,
+        frame_index: FrameIndex,
+        state: Game::State,
+    ) -> Result<(), EngineError> {
         let event = Event::State { frame_index, state };
 
-        self.sender.send_event(event).map_err(unit_error)
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Reports the traffic dropped by the server's
+    /// [RateLimits](crate::RateLimits) to the observers
+    pub fn report_dropped_traffic(&self, counts: DroppedTrafficCounts) -> Result<(), EngineError> {
+        let event = Event::DroppedTraffic(counts);
+
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Registers a [FrameObserver] that is notified of all subsequent events
+    pub fn add_frame_observer(
+        &self,
+        frame_observer: Box<dyn FrameObserver<Game>>,
+    ) -> Result<(), EngineError> {
+        let event = Event::AddFrameObserver(frame_observer);
+
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
     }
 }
 
 enum Event<Game: GameTrait> {
+    AddFrameObserver(Box<dyn FrameObserver<Game>>),
     AdvanceFrameIndex(FrameIndex),
     Input {
         frame_index: FrameIndex,
@@ -120,205 +220,40 @@ enum Event<Game: GameTrait> {
         input: Game::ClientInput,
         is_authoritative: bool,
     },
+    ValidatedInput {
+        frame_index: FrameIndex,
+        player_index: usize,
+        input: Game::ClientInput,
+    },
     AuthoritativeMissingInput {
         frame_index: FrameIndex,
         player_index: usize,
     },
+    ServerInput {
+        frame_index: FrameIndex,
+        server_inputs: Vec<Game::ServerInput>,
+    },
+    ServerInputs {
+        first_frame_index: FrameIndex,
+        server_inputs: Vec<Vec<Game::ServerInput>>,
+    },
     State {
         frame_index: FrameIndex,
         state: Game::State,
     },
+    DroppedTraffic(DroppedTrafficCounts),
 }
 
 struct EventHandler<ManagerObserver: ObserveFrames> {
-    current_frame_index: FrameIndex,
-    initial_information: InitialInformation<ManagerObserver::Game>,
-    //New states at the back, old at the front (index 0)
-    frames: VecDeque<Frame<ManagerObserver::Game>>,
-    manager_observer: ManagerObserver,
+    core: FrameManagerCore<ManagerObserver>,
 }
 
 impl<ManagerObserver: ObserveFrames> EventHandler<ManagerObserver> {
-    fn new(
-        manager_observer: ManagerObserver,
-        initial_information: InitialInformation<ManagerObserver::Game>,
-    ) -> Result<Self, ()> {
-        let state = initial_information.get_state().clone();
-
-        let mut manager = Self {
-            current_frame_index: FrameIndex::zero(),
-            initial_information,
-            frames: VecDeque::new(),
-            manager_observer,
-        };
-
-        // Set state for FrameIndex 0 and send it as authoritative
-        let index = match manager.get_frame_queue_index(FrameIndex::zero()) {
-            Some(index) => index,
-            None => panic!("Getting the zero frame should never fail"),
-        };
-
-        let frame = &mut manager.frames[index];
-        let result = frame.set_state(state, true, &manager.manager_observer);
-
-        match result {
-            ControlFlow::Continue(()) => Ok(manager),
-            ControlFlow::Break(()) => Err(()),
-        }
-    }
-
-    fn get_frame_queue_index(&mut self, frame_index: FrameIndex) -> Option<usize> {
-        let first_frame = match self.frames.get(0) {
-            Some(frame) => frame,
-            None => {
-                let step = Frame::blank(frame_index, self.initial_information.get_player_count());
-                self.frames.push_back(step);
-                &self.frames[0]
-            }
-        };
-
-        if frame_index < first_frame.get_frame_index() {
-            return None;
-        }
-
-        let index_to_get = frame_index.usize() - first_frame.get_frame_index().usize();
-
-        while self.frames.len() <= index_to_get {
-            self.frames.push_back(Frame::blank(
-                self.frames[self.frames.len() - 1].get_frame_index() + 1,
-                self.initial_information.get_player_count(),
-            ));
-        }
-        return Some(index_to_get);
-    }
-
-    fn get_frame(&mut self, frame_index: FrameIndex) -> Option<&mut Frame<ManagerObserver::Game>> {
-        match self.get_frame_queue_index(frame_index) {
-            Some(index) => Some(&mut self.frames[index]),
-            None => None,
-        }
-    }
-
-    fn advance_frame_index(&mut self, frame_index: FrameIndex) -> ControlFlow<()> {
-        self.current_frame_index = frame_index;
-
-        if ManagerObserver::IS_SERVER {
-            let last_open_frame_index = self
-                .initial_information
-                .get_server_config()
-                .get_last_open_frame_index(self.current_frame_index);
-
-            let mut index = 0;
-
-            while index < self.frames.len()
-                && self.frames[index].get_frame_index() < last_open_frame_index
-            {
-                self.frames[index].timeout_remaining_inputs(&self.manager_observer)?;
-                index += 1;
-            }
-        }
-
-        ControlFlow::Continue(())
-    }
-
     fn on_none_pending(&mut self) -> EventHandleResult {
-        // Expand Frame queue to hold up current + 1
-        self.get_frame_queue_index(self.current_frame_index.next());
-
-        let mut index = 0;
-
-        while index < self.frames.len() - 1 {
-            let frame = &mut self.frames[index];
-
-            if let Some((state, is_authoritative)) =
-                frame.calculate_next_state(&self.initial_information)
-            {
-                let next_frame_index = {
-                    let next_frame = &mut self.frames[index + 1];
-
-                    let result =
-                        next_frame.set_state(state, is_authoritative, &self.manager_observer);
-
-                    if result.is_break() {
-                        return EventHandleResult::StopThread;
-                    }
-
-                    next_frame.get_frame_index()
-                };
-
-                if is_authoritative {
-                    self.drop_all_frames_before(next_frame_index);
-                    index = 0;
-                    continue;
-                }
-            }
-            index = index + 1;
-        }
-
-        return EventHandleResult::WaitForNextEvent;
-    }
-
-    fn on_input_message(
-        &mut self,
-        frame_index: FrameIndex,
-        player_index: usize,
-        input: <<ManagerObserver as ObserveFrames>::Game as GameTrait>::ClientInput,
-        is_authoritative: bool,
-    ) {
-        if let Some(step) = self.get_frame(frame_index) {
-            let input = match is_authoritative {
-                true => Input::Authoritative(input),
-                false => Input::NonAuthoritative(input),
-            };
-            step.set_input(player_index, input);
-        }
-    }
-
-    fn on_authoritative_missing_input_message(
-        &mut self,
-        frame_index: FrameIndex,
-        player_index: usize,
-    ) {
-        #[cfg(debug_assertions)]
-        if ManagerObserver::IS_SERVER {
-            panic!("The server received an authoritative missing message")
-        }
-
-        if let Some(step) = self.get_frame(frame_index) {
-            step.set_input(player_index, Input::AuthoritativeMissing);
-        }
-    }
-
-    fn on_state_message(
-        &mut self,
-        frame_index: FrameIndex,
-        state: <<ManagerObserver as ObserveFrames>::Game as GameTrait>::State,
-    ) -> ControlFlow<()> {
-        #[cfg(debug_assertions)]
-        if ManagerObserver::IS_SERVER {
-            panic!("Remote states should only be received by the client")
-        }
-
-        let index = match self.get_frame_queue_index(frame_index) {
-            Some(index) => index,
-            None => return ControlFlow::Continue(()),
-        };
-
-        let frame = &mut self.frames[index];
-        frame.set_state(state, true, &self.manager_observer)?;
-
-        // Drop frames that are no longer needed to calculate updates over to get
-        // to an authoritative state.
-        if frame_index <= self.current_frame_index {
-            self.drop_all_frames_before(frame_index);
-        }
-
-        ControlFlow::Continue(())
-    }
-
-    fn drop_all_frames_before(&mut self, frame_index: FrameIndex) {
-        while self.frames[0].get_frame_index() < frame_index {
-            self.frames.pop_front().unwrap();
+        match self.core.update() {
+            ControlFlow::Continue(true) => EventHandleResult::WaitForNextEvent,
+            ControlFlow::Continue(false) => EventHandleResult::TryForNextEvent,
+            ControlFlow::Break(()) => EventHandleResult::StopThread,
         }
     }
 }
@@ -329,8 +264,9 @@ impl<ManagerObserver: ObserveFrames> HandleEvent for EventHandler<ManagerObserve
 
     fn on_event(&mut self, _: ReceiveMetaData, event: Self::Event) -> EventHandleResult {
         match event {
+            Event::AddFrameObserver(frame_observer) => self.core.add_frame_observer(frame_observer),
             Event::AdvanceFrameIndex(frame_index) => {
-                let result = self.advance_frame_index(frame_index);
+                let result = self.core.advance_frame_index(frame_index);
                 if result.is_break() {
                     return EventHandleResult::StopThread;
                 }
@@ -340,13 +276,63 @@ impl<ManagerObserver: ObserveFrames> HandleEvent for EventHandler<ManagerObserve
                 player_index,
                 input,
                 is_authoritative,
-            } => self.on_input_message(frame_index, player_index, input, is_authoritative),
+            } => {
+                let result =
+                    self.core
+                        .insert_input(frame_index, player_index, input, is_authoritative);
+                if result.is_break() {
+                    return EventHandleResult::StopThread;
+                }
+            }
+            Event::ValidatedInput {
+                frame_index,
+                player_index,
+                input,
+            } => {
+                let result = self
+                    .core
+                    .validate_and_insert_input(frame_index, player_index, input);
+                if result.is_break() {
+                    return EventHandleResult::StopThread;
+                }
+            }
             Event::AuthoritativeMissingInput {
                 frame_index,
                 player_index,
-            } => self.on_authoritative_missing_input_message(frame_index, player_index),
+            } => {
+                let result = self.core.insert_missing_input(frame_index, player_index);
+                if result.is_break() {
+                    return EventHandleResult::StopThread;
+                }
+            }
+            Event::ServerInput {
+                frame_index,
+                server_inputs,
+            } => {
+                let result = self.core.insert_server_input(frame_index, server_inputs);
+                if result.is_break() {
+                    return EventHandleResult::StopThread;
+                }
+            }
+            Event::ServerInputs {
+                first_frame_index,
+                server_inputs,
+            } => {
+                let result = self
+                    .core
+                    .insert_server_inputs(first_frame_index, server_inputs);
+                if result.is_break() {
+                    return EventHandleResult::StopThread;
+                }
+            }
             Event::State { frame_index, state } => {
-                let result = self.on_state_message(frame_index, state);
+                let result = self.core.insert_state(frame_index, state);
+                if result.is_break() {
+                    return EventHandleResult::StopThread;
+                }
+            }
+            Event::DroppedTraffic(counts) => {
+                let result = self.core.report_dropped_traffic(&counts);
                 if result.is_break() {
                     return EventHandleResult::StopThread;
                 }
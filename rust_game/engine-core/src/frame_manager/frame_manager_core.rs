@@ -0,0 +1,626 @@
+use crate::frame_manager::frame::Frame;
+use crate::frame_manager::observer_set::ObserverSet;
+use crate::frame_manager::{
+    InputRejectionCounts,
+    ObserveFrames,
+    RejectionCounts,
+    RejectionReason,
+    SimulationMetrics,
+};
+use crate::interface::{
+    EngineError,
+    FrameObserver,
+    GameTrait,
+    InitialInformation,
+    InputValidation,
+};
+use crate::server::DroppedTrafficCounts;
+use crate::{
+    FrameIndex,
+    Input,
+};
+use commons::real_time::TimeSource;
+use std::collections::vec_deque::VecDeque;
+use std::ops::ControlFlow;
+
+/// The synchronous core of the [FrameManager](super::FrameManager).
+///
+/// A [FrameManagerCore] holds a queue of [Frames](Frame), inserts
+/// [Inputs](Input) and [States](GameTrait::State) into it, and computes new
+/// States when [update](Self::update) is called.  It doesn't start any
+/// threads, so it can be driven directly by tests or offline games.
+///
+/// Only frames from the earliest changed frame onward are re-simulated.
+/// Inputs and States more than [GameTrait::MAX_FUTURE_FRAMES] ahead of the
+/// current [FrameIndex] are rejected.  Frames more than
+/// [GameTrait::MAX_PAST_FRAMES] behind the current [FrameIndex] are dropped
+/// even if they are not authoritative, which keeps memory bounded when
+/// authoritative States stop arriving.
+pub struct FrameManagerCore<ManagerObserver: ObserveFrames> {
+    time_source: TimeSource,
+    current_frame_index: FrameIndex,
+    initial_information: InitialInformation<ManagerObserver::Game>,
+    //New states at the back, old at the front (index 0)
+    frames: VecDeque<Frame<ManagerObserver::Game>>,
+    // Known or predicted inputs of the frame before the oldest frame
+    dropped_frame_inputs: Vec<Option<<ManagerObserver::Game as GameTrait>::ClientInput>>,
+    // Whether the dropped frame's inputs can be used to predict inputs that
+    // are authoritatively missing in an authoritative state
+    are_dropped_frame_inputs_authoritative: bool,
+    // The earliest frame that may need to be re-simulated
+    first_dirty_frame_index: Option<FrameIndex>,
+    simulation_metrics: SimulationMetrics,
+    rejection_counts: RejectionCounts,
+    input_rejection_counts: InputRejectionCounts,
+    manager_observer: ObserverSet<ManagerObserver>,
+}
+
+impl<ManagerObserver: ObserveFrames> FrameManagerCore<ManagerObserver> {
+    /// Creates a new [FrameManagerCore] with the authoritative initial
+    /// [State](GameTrait::State) in the first [FrameIndex] of the session,
+    /// which is 0 unless it resumed a [ServerSnapshot](crate::ServerSnapshot)
+    pub fn new(
+        time_source: TimeSource,
+        manager_observer: ManagerObserver,
+        frame_observers: Vec<Box<dyn FrameObserver<ManagerObserver::Game>>>,
+        initial_information: InitialInformation<ManagerObserver::Game>,
+    ) -> Result<Self, EngineError> {
+        let input_grace_period_frames = initial_information
+            .get_server_config()
+            .get_input_grace_period_frames();
+
+        // On a client, the grace period comes from the server
+        if ManagerObserver::Game::MAX_PAST_FRAMES <= input_grace_period_frames {
+            return Err(EngineError::InvalidConfiguration(
+                "MAX_PAST_FRAMES must be more than the GRACE_PERIOD in frames",
+            ));
+        }
+
+        let state = initial_information.get_state().clone();
+        let first_frame_index = initial_information
+            .get_server_config()
+            .get_first_frame_index();
+        let dropped_frame_inputs = vec![None; initial_information.get_player_count()];
+        let input_rejection_counts =
+            InputRejectionCounts::new(initial_information.get_player_count());
+
+        let mut manager = Self {
+            time_source,
+            current_frame_index: first_frame_index,
+            initial_information,
+            frames: VecDeque::new(),
+            dropped_frame_inputs,
+            are_dropped_frame_inputs_authoritative: true,
+            first_dirty_frame_index: None,
+            simulation_metrics: SimulationMetrics::new(),
+            rejection_counts: RejectionCounts::default(),
+            input_rejection_counts,
+            manager_observer: ObserverSet::new(manager_observer, frame_observers),
+        };
+
+        // Set state for the first FrameIndex and send it as authoritative
+        let index = match manager.get_frame_queue_index(first_frame_index) {
+            Some(index) => index,
+            None => panic!("Getting the first frame should never fail"),
+        };
+
+        let frame = &mut manager.frames[index];
+        let result = frame.set_state(state, true, &manager.manager_observer);
+
+        match result {
+            ControlFlow::Continue(()) => Ok(manager),
+            ControlFlow::Break(()) => Err(EngineError::ThreadStopped),
+        }
+    }
+
+    fn get_frame_queue_index(&mut self, frame_index: FrameIndex) -> Option<usize> {
+        let first_frame = match self.frames.get(0) {
+            Some(frame) => frame,
+            None => {
+                let step = Frame::blank(frame_index, self.initial_information.get_player_count());
+                self.frames.push_back(step);
+                &self.frames[0]
+            }
+        };
+
+        if frame_index < first_frame.get_frame_index() {
+            return None;
+        }
+
+        let index_to_get = frame_index.usize() - first_frame.get_frame_index().usize();
+
+        while self.frames.len() <= index_to_get {
+            // The previous last frame can now compute the state of the new frame
+            let last_frame_index = self.frames[self.frames.len() - 1].get_frame_index();
+            self.mark_dirty(last_frame_index);
+
+            self.frames.push_back(Frame::blank(
+                last_frame_index + 1,
+                self.initial_information.get_player_count(),
+            ));
+        }
+        return Some(index_to_get);
+    }
+
+    fn mark_dirty(&mut self, frame_index: FrameIndex) {
+        self.first_dirty_frame_index = match self.first_dirty_frame_index {
+            Some(first_dirty_frame_index) if first_dirty_frame_index <= frame_index => {
+                Some(first_dirty_frame_index)
+            }
+            _ => Some(frame_index),
+        };
+    }
+
+    /// Gets the index of the [Frame] in the queue if it is within the past and
+    /// future windows
+    fn get_frame_queue_index_in_window(
+        &mut self,
+        frame_index: FrameIndex,
+    ) -> Result<usize, RejectionReason> {
+        if frame_index > self.current_frame_index + ManagerObserver::Game::MAX_FUTURE_FRAMES {
+            return Err(RejectionReason::TooFarInFuture);
+        }
+
+        match self.get_frame_queue_index(frame_index) {
+            Some(index) => Ok(index),
+            None => Err(RejectionReason::TooFarInPast),
+        }
+    }
+
+    fn reject(&mut self, frame_index: FrameIndex, reason: RejectionReason) -> ControlFlow<()> {
+        self.rejection_counts.increment(reason);
+        self.manager_observer
+            .rejected(frame_index, reason, &self.rejection_counts)
+    }
+
+    /// Reports the traffic dropped by the server's
+    /// [RateLimits](crate::RateLimits) to the observers
+    pub fn report_dropped_traffic(&self, counts: &DroppedTrafficCounts) -> ControlFlow<()> {
+        self.manager_observer.traffic_dropped(counts)
+    }
+
+    /// Registers a [FrameObserver] that is notified of all subsequent events
+    pub fn add_frame_observer(
+        &mut self,
+        frame_observer: Box<dyn FrameObserver<ManagerObserver::Game>>,
+    ) {
+        self.manager_observer.add_frame_observer(frame_observer);
+    }
+
+    pub fn get_current_frame_index(&self) -> FrameIndex {
+        return self.current_frame_index;
+    }
+
+    /// Returns the [State](GameTrait::State) of the [Frame] at [FrameIndex],
+    /// if it has been computed and the frame hasn't been dropped
+    pub fn get_state(
+        &self,
+        frame_index: FrameIndex,
+    ) -> Option<&<ManagerObserver::Game as GameTrait>::State> {
+        let first_frame_index = self.frames.get(0)?.get_frame_index();
+
+        if frame_index < first_frame_index {
+            return None;
+        }
+
+        return self
+            .frames
+            .get(frame_index.usize() - first_frame_index.usize())?
+            .get_state();
+    }
+
+    pub fn get_simulation_metrics(&self) -> &SimulationMetrics {
+        return &self.simulation_metrics;
+    }
+
+    pub fn get_rejection_counts(&self) -> &RejectionCounts {
+        return &self.rejection_counts;
+    }
+
+    pub fn get_input_rejection_counts(&self) -> &InputRejectionCounts {
+        return &self.input_rejection_counts;
+    }
+
+    /// Advances the current [FrameIndex].  Frames will be computed up to
+    /// `frame_index + 1`.
+    pub fn advance_frame_index(&mut self, frame_index: FrameIndex) -> ControlFlow<()> {
+        self.current_frame_index = frame_index;
+
+        if ManagerObserver::IS_SERVER {
+            let last_open_frame_index = self
+                .initial_information
+                .get_server_config()
+                .get_last_open_frame_index(self.current_frame_index);
+
+            let mut index = 0;
+
+            while index < self.frames.len()
+                && self.frames[index].get_frame_index() < last_open_frame_index
+            {
+                let frame = &mut self.frames[index];
+
+                if !frame.are_inputs_complete() {
+                    frame.timeout_remaining_inputs(&self.manager_observer)?;
+                    let frame_index = frame.get_frame_index();
+                    self.mark_dirty(frame_index);
+                }
+
+                index += 1;
+            }
+        }
+
+        self.drop_frames_outside_past_window()
+    }
+
+    /// Computes States from the earliest changed frame up to the current
+    /// [FrameIndex] + 1, stopping after
+    /// [GameTrait::MAX_SIMULATED_FRAMES_PER_PASS] States.  Returns
+    /// `Continue(true)` when all frames are up to date and `Continue(false)`
+    /// when frames remain to be computed.
+    pub fn update(&mut self) -> ControlFlow<(), bool> {
+        // Expand Frame queue to hold up current + 1
+        self.get_frame_queue_index(self.current_frame_index.next());
+
+        let first_dirty_frame_index = match self.first_dirty_frame_index.take() {
+            Some(first_dirty_frame_index) => first_dirty_frame_index,
+            None => return ControlFlow::Continue(true),
+        };
+
+        // Frames before the first frame have been dropped and can't change
+        let mut index = first_dirty_frame_index
+            .usize()
+            .saturating_sub(self.frames[0].get_frame_index().usize());
+
+        // The number of already computed states that will be re-simulated
+        let rollback_depth = match self.frames.iter().rev().find(|frame| frame.has_state()) {
+            Some(frame) => frame
+                .get_frame_index()
+                .usize()
+                .saturating_sub(first_dirty_frame_index.usize()),
+            None => 0,
+        };
+
+        let start_time = self.time_source.now();
+        let mut simulated_frames = 0;
+        let mut is_budget_exhausted = false;
+
+        while index < self.frames.len() - 1 {
+            if simulated_frames >= ManagerObserver::Game::MAX_SIMULATED_FRAMES_PER_PASS {
+                // Resume from this frame after checking for new events
+                let frame_index = self.frames[index].get_frame_index();
+                self.mark_dirty(frame_index);
+                is_budget_exhausted = true;
+                break;
+            }
+
+            let (previous_inputs, are_previous_inputs_authoritative) = match index {
+                0 => (
+                    self.dropped_frame_inputs.clone(),
+                    self.are_dropped_frame_inputs_authoritative,
+                ),
+                _ => {
+                    let previous_frame = &self.frames[index - 1];
+                    (
+                        previous_frame.get_inputs_or_predictions(),
+                        previous_frame.are_inputs_or_predictions_authoritative(),
+                    )
+                }
+            };
+
+            let frame = &mut self.frames[index];
+
+            if let Some((state, is_authoritative)) = frame.calculate_next_state(
+                &self.initial_information,
+                ManagerObserver::IS_SERVER,
+                &previous_inputs,
+                are_previous_inputs_authoritative,
+            ) {
+                simulated_frames += 1;
+
+                let next_frame_index = {
+                    let next_frame = &mut self.frames[index + 1];
+
+                    let result =
+                        next_frame.set_state(state, is_authoritative, &self.manager_observer);
+
+                    if result.is_break() {
+                        return ControlFlow::Break(());
+                    }
+
+                    next_frame.get_frame_index()
+                };
+
+                if is_authoritative {
+                    if self.drop_all_frames_before(next_frame_index).is_break() {
+                        return ControlFlow::Break(());
+                    }
+
+                    index = 0;
+                    continue;
+                }
+            }
+            index = index + 1;
+        }
+
+        if simulated_frames > 0 {
+            let simulation_duration = self.time_source.now().duration_since(&start_time);
+
+            self.simulation_metrics.record_pass(
+                rollback_depth,
+                simulated_frames,
+                simulation_duration,
+                is_budget_exhausted,
+            );
+
+            if rollback_depth > 0 {
+                let result = self
+                    .manager_observer
+                    .rolled_back(first_dirty_frame_index, rollback_depth);
+
+                if result.is_break() {
+                    return ControlFlow::Break(());
+                }
+            }
+
+            let result = self
+                .manager_observer
+                .simulation_metrics(&self.simulation_metrics);
+
+            if result.is_break() {
+                return ControlFlow::Break(());
+            }
+        }
+
+        if is_budget_exhausted {
+            return ControlFlow::Continue(false);
+        }
+
+        return ControlFlow::Continue(true);
+    }
+
+    /// Calls [update](Self::update) until all frames are up to date
+    pub fn update_until_complete(&mut self) -> ControlFlow<()> {
+        while !self.update()? {}
+
+        ControlFlow::Continue(())
+    }
+
+    /// Inserts a [Input] with a [GameTrait::ClientInput] into the [Frame] at
+    /// [FrameIndex]
+    pub fn insert_input(
+        &mut self,
+        frame_index: FrameIndex,
+        player_index: usize,
+        input: <<ManagerObserver as ObserveFrames>::Game as GameTrait>::ClientInput,
+        is_authoritative: bool,
+    ) -> ControlFlow<()> {
+        if player_index >= self.initial_information.get_player_count() {
+            return self.reject(frame_index, RejectionReason::InvalidPlayerIndex);
+        }
+
+        let index = match self.get_frame_queue_index_in_window(frame_index) {
+            Ok(index) => index,
+            Err(reason) => return self.reject(frame_index, reason),
+        };
+
+        let input = match is_authoritative {
+            true => Input::Authoritative(input),
+            false => Input::NonAuthoritative(input),
+        };
+
+        let is_mispredicted = self.frames[index].set_input(player_index, input);
+        self.mark_dirty(frame_index);
+
+        if is_mispredicted {
+            self.simulation_metrics.record_misprediction();
+            self.manager_observer
+                .input_mispredicted(frame_index, player_index)?;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Checks an input from a remote player with [GameTrait::validate_input]
+    /// and inserts it as authoritative if it is accepted.  Accepted inputs are
+    /// passed to [ObserveFrames::input_accepted] to be sent to the clients.
+    /// This is only used by the server.
+    pub fn validate_and_insert_input(
+        &mut self,
+        frame_index: FrameIndex,
+        player_index: usize,
+        input: <<ManagerObserver as ObserveFrames>::Game as GameTrait>::ClientInput,
+    ) -> ControlFlow<()> {
+        #[cfg(debug_assertions)]
+        if !ManagerObserver::IS_SERVER {
+            panic!("Only the server validates inputs")
+        }
+
+        if player_index >= self.initial_information.get_player_count() {
+            return self.reject(frame_index, RejectionReason::InvalidPlayerIndex);
+        }
+
+        let index = match self.get_frame_queue_index_in_window(frame_index) {
+            Ok(index) => index,
+            Err(reason) => return self.reject(frame_index, reason),
+        };
+
+        // Inputs may be received more than once, but only the first is used
+        if self.frames[index].is_input_authoritative(player_index) {
+            return ControlFlow::Continue(());
+        }
+
+        // The oldest frame always has a state
+        let state = match self
+            .frames
+            .range(..=index)
+            .rev()
+            .find_map(|frame| frame.get_state())
+        {
+            Some(state) => state,
+            None => panic!("There should always be a state to validate inputs against"),
+        };
+
+        let input = match ManagerObserver::Game::validate_input(state, player_index, &input) {
+            InputValidation::Accept => input,
+            InputValidation::Clamp(clamped_input) => clamped_input,
+            InputValidation::Reject => {
+                self.input_rejection_counts.increment(player_index);
+                return self.manager_observer.input_rejected(
+                    frame_index,
+                    player_index,
+                    &self.input_rejection_counts,
+                );
+            }
+        };
+
+        self.insert_input(frame_index, player_index, input.clone(), true)?;
+        self.manager_observer
+            .input_accepted(frame_index, player_index, &input)
+    }
+
+    /// Inserts an [Input::AuthoritativeMissing] into the [Frame] at
+    /// [FrameIndex].  This is only used by clients.
+    pub fn insert_missing_input(
+        &mut self,
+        frame_index: FrameIndex,
+        player_index: usize,
+    ) -> ControlFlow<()> {
+        #[cfg(debug_assertions)]
+        if ManagerObserver::IS_SERVER {
+            panic!("The server received an authoritative missing message")
+        }
+
+        if player_index >= self.initial_information.get_player_count() {
+            return self.reject(frame_index, RejectionReason::InvalidPlayerIndex);
+        }
+
+        let index = match self.get_frame_queue_index_in_window(frame_index) {
+            Ok(index) => index,
+            Err(reason) => return self.reject(frame_index, reason),
+        };
+
+        self.frames[index].set_input(player_index, Input::AuthoritativeMissing);
+        self.mark_dirty(frame_index);
+
+        self.manager_observer
+            .input_missing_received(frame_index, player_index)
+    }
+
+    /// Inserts the authoritative [server inputs](GameTrait::ServerInput) into
+    /// the [Frame] at [FrameIndex]
+    pub fn insert_server_input(
+        &mut self,
+        frame_index: FrameIndex,
+        server_inputs: Vec<<<ManagerObserver as ObserveFrames>::Game as GameTrait>::ServerInput>,
+    ) -> ControlFlow<()> {
+        let index = match self.get_frame_queue_index_in_window(frame_index) {
+            Ok(index) => index,
+            Err(reason) => return self.reject(frame_index, reason),
+        };
+
+        self.frames[index].set_server_input(server_inputs);
+        self.mark_dirty(frame_index);
+
+        ControlFlow::Continue(())
+    }
+
+    /// Inserts the authoritative [server inputs](GameTrait::ServerInput) of
+    /// consecutive frames, starting at `first_frame_index`.  The server resends
+    /// the server inputs of every open frame, so frames that have been dropped
+    /// or already have their server inputs are skipped.  This is only used by
+    /// clients.
+    pub fn insert_server_inputs(
+        &mut self,
+        first_frame_index: FrameIndex,
+        server_inputs: Vec<
+            Vec<<<ManagerObserver as ObserveFrames>::Game as GameTrait>::ServerInput>,
+        >,
+    ) -> ControlFlow<()> {
+        let mut frame_index = first_frame_index;
+
+        for server_inputs in server_inputs {
+            let oldest_frame_index = self.frames[0].get_frame_index();
+
+            let has_server_input = frame_index < oldest_frame_index
+                || self
+                    .frames
+                    .get(frame_index.usize() - oldest_frame_index.usize())
+                    .is_some_and(|frame| frame.is_server_input_authoritative());
+
+            if !has_server_input {
+                self.insert_server_input(frame_index, server_inputs)?;
+            }
+
+            frame_index = frame_index.next();
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Inserts an authoritative [State](GameTrait::State) into the [Frame] at
+    /// [FrameIndex].  This is only used by clients.
+    pub fn insert_state(
+        &mut self,
+        frame_index: FrameIndex,
+        state: <<ManagerObserver as ObserveFrames>::Game as GameTrait>::State,
+    ) -> ControlFlow<()> {
+        #[cfg(debug_assertions)]
+        if ManagerObserver::IS_SERVER {
+            panic!("Remote states should only be received by the client")
+        }
+
+        let index = match self.get_frame_queue_index_in_window(frame_index) {
+            Ok(index) => index,
+            Err(reason) => return self.reject(frame_index, reason),
+        };
+
+        let frame = &mut self.frames[index];
+        frame.set_state(state, true, &self.manager_observer)?;
+        self.mark_dirty(frame_index);
+
+        // Drop frames that are no longer needed to calculate updates over to get
+        // to an authoritative state.
+        if frame_index <= self.current_frame_index {
+            self.drop_all_frames_before(frame_index)?;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn drop_all_frames_before(&mut self, frame_index: FrameIndex) -> ControlFlow<()> {
+        while self.frames[0].get_frame_index() < frame_index {
+            self.drop_first_frame()?;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Drops frames older than [GameTrait::MAX_PAST_FRAMES], even if they are
+    /// not authoritative.  A frame is only dropped if the frame after it has a
+    /// state to continue computing from.
+    fn drop_frames_outside_past_window(&mut self) -> ControlFlow<()> {
+        let oldest_frame_index = self
+            .current_frame_index
+            .usize()
+            .saturating_sub(ManagerObserver::Game::MAX_PAST_FRAMES);
+
+        while self.frames.len() > 1
+            && self.frames[0].get_frame_index().usize() < oldest_frame_index
+            && self.frames[1].has_state()
+        {
+            self.drop_first_frame()?;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn drop_first_frame(&mut self) -> ControlFlow<()> {
+        let frame = self.frames.pop_front().unwrap();
+        self.dropped_frame_inputs = frame.get_inputs_or_predictions();
+        self.are_dropped_frame_inputs_authoritative =
+            frame.are_inputs_or_predictions_authoritative();
+
+        self.manager_observer
+            .frame_dropped(frame.get_frame_index(), frame.is_state_authoritative())
+    }
+}
@@ -0,0 +1,26 @@
+/// The number of [Inputs](super::Input) from each player rejected by
+/// [GameTrait::validate_input](crate::GameTrait::validate_input)
+#[derive(Clone, Debug)]
+pub struct InputRejectionCounts {
+    counts: Vec<usize>,
+}
+
+impl InputRejectionCounts {
+    pub(super) fn new(player_count: usize) -> Self {
+        return Self {
+            counts: vec![0; player_count],
+        };
+    }
+
+    pub(super) fn increment(&mut self, player_index: usize) {
+        self.counts[player_index] += 1;
+    }
+
+    pub fn get_count(&self, player_index: usize) -> usize {
+        return self.counts.get(player_index).copied().unwrap_or(0);
+    }
+
+    pub fn get_total_count(&self) -> usize {
+        return self.counts.iter().sum();
+    }
+}
@@ -1,7 +1,22 @@
 pub use self::frame::Input;
 pub use self::frame_manager::FrameManager;
+pub use self::frame_manager_core::FrameManagerCore;
+pub use self::input_rejection_counts::InputRejectionCounts;
 pub use self::observe_frames::ObserveFrames;
+pub use self::rejection_reason::{
+    RejectionCounts,
+    RejectionReason,
+};
+pub use self::simulation_metrics::SimulationMetrics;
 
 mod frame;
 mod frame_manager;
+mod frame_manager_core;
+mod input_rejection_counts;
 mod observe_frames;
+mod observer_set;
+mod rejection_reason;
+mod simulation_metrics;
+
+#[cfg(test)]
+mod tests;
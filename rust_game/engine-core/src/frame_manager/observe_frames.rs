@@ -1,5 +1,12 @@
+use crate::frame_manager::{
+    InputRejectionCounts,
+    RejectionCounts,
+    RejectionReason,
+    SimulationMetrics,
+};
 use crate::interface::GameTrait;
 use crate::messaging::FrameIndexAndState;
+use crate::server::DroppedTrafficCounts;
 use crate::FrameIndex;
 use std::ops::ControlFlow;
 
@@ -28,4 +35,95 @@ pub trait ObserveFrames: 'static + Send {
         is_state_authoritative: bool,
         state_message: FrameIndexAndState<Self::Game>,
     ) -> ControlFlow<()>;
+
+    /// Called on a client when the server declares an [Input](super::Input)
+    /// authoritatively missing.
+    fn input_missing_received(
+        &self,
+        _frame_index: FrameIndex,
+        _player_index: usize,
+    ) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called when an input arrives for a slot that had been filled by
+    /// [predict_input](GameTrait::predict_input) and the input differs from
+    /// the prediction.
+    fn input_mispredicted(
+        &self,
+        _frame_index: FrameIndex,
+        _player_index: usize,
+    ) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called when a pass re-simulates already computed States, starting at
+    /// `first_frame_index`.  `rollback_depth` is the number of computed States
+    /// that are invalidated.
+    fn rolled_back(
+        &self,
+        _first_frame_index: FrameIndex,
+        _rollback_depth: usize,
+    ) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called when a frame is dropped from the
+    /// [FrameManager](super::frame_manager::FrameManager) and can no longer be
+    /// re-simulated.
+    fn frame_dropped(
+        &self,
+        _frame_index: FrameIndex,
+        _is_state_authoritative: bool,
+    ) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called after each pass in which States were re-simulated, with metrics
+    /// accumulated since the [FrameManager](super::frame_manager::FrameManager)
+    /// started.
+    fn simulation_metrics(&self, _metrics: &SimulationMetrics) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called when an [Input](super::Input) or State for `frame_index` is
+    /// rejected because it is outside the frames kept by the
+    /// [FrameManager](super::frame_manager::FrameManager)
+    fn rejected(
+        &self,
+        _frame_index: FrameIndex,
+        _reason: RejectionReason,
+        _counts: &RejectionCounts,
+    ) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called on the server when an input from a remote player passes
+    /// [GameTrait::validate_input] and becomes authoritative.  `input` is the
+    /// input after any clamping.
+    fn input_accepted(
+        &self,
+        _frame_index: FrameIndex,
+        _player_index: usize,
+        _input: &<Self::Game as GameTrait>::ClientInput,
+    ) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called on the server when an input from a remote player is rejected by
+    /// [GameTrait::validate_input]
+    fn input_rejected(
+        &self,
+        _frame_index: FrameIndex,
+        _player_index: usize,
+        _counts: &InputRejectionCounts,
+    ) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    /// Called on the server, at most once per frame, when traffic was dropped
+    /// for going over the [RateLimits](crate::RateLimits)
+    fn traffic_dropped(&self, _counts: &DroppedTrafficCounts) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
 }
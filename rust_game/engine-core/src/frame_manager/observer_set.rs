@@ -0,0 +1,149 @@
+use crate::frame_manager::{
+    InputRejectionCounts,
+    ObserveFrames,
+    RejectionCounts,
+    RejectionReason,
+    SimulationMetrics,
+};
+use crate::interface::FrameObserver;
+use crate::messaging::FrameIndexAndState;
+use crate::server::DroppedTrafficCounts;
+use crate::{
+    FrameIndex,
+    GameTrait,
+};
+use std::cell::RefCell;
+use std::ops::ControlFlow;
+
+type BoxedFrameObserver<T> = Box<dyn FrameObserver<<T as ObserveFrames>::Game>>;
+
+/// Combines the engine's [ObserveFrames] with any [FrameObservers](FrameObserver)
+/// registered by the game.  The FrameObservers are notified of each event
+/// before the engine's observer, which decides whether the
+/// [FrameManager](super::FrameManager) continues.
+pub struct ObserverSet<T: ObserveFrames> {
+    observer: T,
+    frame_observers: RefCell<Vec<BoxedFrameObserver<T>>>,
+}
+
+impl<T: ObserveFrames> ObserverSet<T> {
+    pub fn new(observer: T, frame_observers: Vec<BoxedFrameObserver<T>>) -> Self {
+        return Self {
+            observer,
+            frame_observers: RefCell::new(frame_observers),
+        };
+    }
+
+    pub fn add_frame_observer(&self, frame_observer: BoxedFrameObserver<T>) {
+        self.frame_observers.borrow_mut().push(frame_observer);
+    }
+
+    fn for_each(&self, f: impl Fn(&mut BoxedFrameObserver<T>)) {
+        for frame_observer in self.frame_observers.borrow_mut().iter_mut() {
+            f(frame_observer);
+        }
+    }
+}
+
+impl<T: ObserveFrames> ObserveFrames for ObserverSet<T> {
+    type Game = T::Game;
+
+    const IS_SERVER: bool = T::IS_SERVER;
+
+    fn input_authoritatively_missing(
+        &self,
+        frame_index: FrameIndex,
+        player_index: usize,
+    ) -> ControlFlow<()> {
+        self.for_each(|observer| observer.on_input_missing(frame_index, player_index));
+        self.observer
+            .input_authoritatively_missing(frame_index, player_index)
+    }
+
+    fn new_state(
+        &self,
+        is_state_authoritative: bool,
+        state_message: FrameIndexAndState<Self::Game>,
+    ) -> ControlFlow<()> {
+        self.for_each(|observer| {
+            observer.on_new_state(
+                state_message.get_frame_index(),
+                state_message.get_state(),
+                is_state_authoritative,
+            )
+        });
+        self.observer
+            .new_state(is_state_authoritative, state_message)
+    }
+
+    fn input_missing_received(
+        &self,
+        frame_index: FrameIndex,
+        player_index: usize,
+    ) -> ControlFlow<()> {
+        self.for_each(|observer| observer.on_input_missing(frame_index, player_index));
+        self.observer
+            .input_missing_received(frame_index, player_index)
+    }
+
+    fn input_mispredicted(&self, frame_index: FrameIndex, player_index: usize) -> ControlFlow<()> {
+        self.for_each(|observer| observer.on_input_mispredicted(frame_index, player_index));
+        self.observer.input_mispredicted(frame_index, player_index)
+    }
+
+    fn rolled_back(&self, first_frame_index: FrameIndex, rollback_depth: usize) -> ControlFlow<()> {
+        self.for_each(|observer| observer.on_rollback(first_frame_index, rollback_depth));
+        self.observer.rolled_back(first_frame_index, rollback_depth)
+    }
+
+    fn frame_dropped(
+        &self,
+        frame_index: FrameIndex,
+        is_state_authoritative: bool,
+    ) -> ControlFlow<()> {
+        self.for_each(|observer| observer.on_frame_dropped(frame_index, is_state_authoritative));
+        self.observer
+            .frame_dropped(frame_index, is_state_authoritative)
+    }
+
+    fn simulation_metrics(&self, metrics: &SimulationMetrics) -> ControlFlow<()> {
+        self.for_each(|observer| observer.on_simulation_metrics(metrics));
+        self.observer.simulation_metrics(metrics)
+    }
+
+    fn rejected(
+        &self,
+        frame_index: FrameIndex,
+        reason: RejectionReason,
+        counts: &RejectionCounts,
+    ) -> ControlFlow<()> {
+        self.for_each(|observer| observer.on_rejected(frame_index, reason, counts));
+        self.observer.rejected(frame_index, reason, counts)
+    }
+
+    fn input_accepted(
+        &self,
+        frame_index: FrameIndex,
+        player_index: usize,
+        input: &<Self::Game as GameTrait>::ClientInput,
+    ) -> ControlFlow<()> {
+        self.observer
+            .input_accepted(frame_index, player_index, input)
+    }
+
+    fn input_rejected(
+        &self,
+        frame_index: FrameIndex,
+        player_index: usize,
+        counts: &InputRejectionCounts,
+    ) -> ControlFlow<()> {
+        self.for_each(|observer| observer.on_input_rejected(frame_index, player_index, counts));
+        self.observer
+            .input_rejected(frame_index, player_index, counts)
+    }
+
+    fn traffic_dropped(&self, counts: &DroppedTrafficCounts) -> ControlFlow<()> {
+        self.for_each(|observer| observer.on_traffic_dropped(counts));
+        self.observer.traffic_dropped(counts)
+    }
+}
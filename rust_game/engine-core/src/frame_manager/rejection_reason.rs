@@ -0,0 +1,46 @@
+/// The reason the [FrameManager](super::frame_manager::FrameManager) rejected
+/// an [Input](super::Input) or [State](crate::GameTrait::State)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RejectionReason {
+    /// The frame is older than the oldest frame kept by the
+    /// [FrameManager](super::frame_manager::FrameManager)
+    TooFarInPast,
+
+    /// The frame is more than [GameTrait::MAX_FUTURE_FRAMES](crate::GameTrait::MAX_FUTURE_FRAMES)
+    /// ahead of the current [FrameIndex](crate::FrameIndex)
+    TooFarInFuture,
+
+    /// The player index is not less than the player count
+    InvalidPlayerIndex,
+}
+
+/// The number of rejected [Inputs](super::Input) and [States](crate::GameTrait::State)
+/// for each [RejectionReason]
+#[derive(Clone, Debug, Default)]
+pub struct RejectionCounts {
+    too_far_in_past: usize,
+    too_far_in_future: usize,
+    invalid_player_index: usize,
+}
+
+impl RejectionCounts {
+    pub(super) fn increment(&mut self, reason: RejectionReason) {
+        match reason {
+            RejectionReason::TooFarInPast => self.too_far_in_past += 1,
+            RejectionReason::TooFarInFuture => self.too_far_in_future += 1,
+            RejectionReason::InvalidPlayerIndex => self.invalid_player_index += 1,
+        }
+    }
+
+    pub fn get_count(&self, reason: RejectionReason) -> usize {
+        match reason {
+            RejectionReason::TooFarInPast => self.too_far_in_past,
+            RejectionReason::TooFarInFuture => self.too_far_in_future,
+            RejectionReason::InvalidPlayerIndex => self.invalid_player_index,
+        }
+    }
+
+    pub fn get_total_count(&self) -> usize {
+        self.too_far_in_past + self.too_far_in_future + self.invalid_player_index
+    }
+}
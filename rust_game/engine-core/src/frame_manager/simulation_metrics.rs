@@ -0,0 +1,95 @@
+use commons::time::TimeDuration;
+
+/// Metrics describing how much work the
+/// [FrameManager](super::frame_manager::FrameManager) does to re-simulate
+/// frames after their [Inputs](super::Input) or [States](crate::GameTrait::State)
+/// change.
+#[derive(Clone, Debug)]
+pub struct SimulationMetrics {
+    last_rollback_depth: usize,
+    max_rollback_depth: usize,
+    last_simulated_frames: usize,
+    total_simulated_frames: usize,
+    last_simulation_duration: TimeDuration,
+    total_simulation_duration: TimeDuration,
+    budget_exhausted_count: usize,
+    mispredicted_input_count: usize,
+}
+
+impl SimulationMetrics {
+    pub(super) fn new() -> Self {
+        return Self {
+            last_rollback_depth: 0,
+            max_rollback_depth: 0,
+            last_simulated_frames: 0,
+            total_simulated_frames: 0,
+            last_simulation_duration: TimeDuration::new(0, 0),
+            total_simulation_duration: TimeDuration::new(0, 0),
+            budget_exhausted_count: 0,
+            mispredicted_input_count: 0,
+        };
+    }
+
+    pub(super) fn record_pass(
+        &mut self,
+        rollback_depth: usize,
+        simulated_frames: usize,
+        simulation_duration: TimeDuration,
+        is_budget_exhausted: bool,
+    ) {
+        self.last_rollback_depth = rollback_depth;
+        self.max_rollback_depth = self.max_rollback_depth.max(rollback_depth);
+        self.last_simulated_frames = simulated_frames;
+        self.total_simulated_frames += simulated_frames;
+        self.last_simulation_duration = simulation_duration;
+        self.total_simulation_duration = self.total_simulation_duration + &simulation_duration;
+
+        if is_budget_exhausted {
+            self.budget_exhausted_count += 1;
+        }
+    }
+
+    pub(super) fn record_misprediction(&mut self) {
+        self.mispredicted_input_count += 1;
+    }
+
+    /// The number of already computed States that the last pass started to
+    /// re-simulate
+    pub fn get_last_rollback_depth(&self) -> usize {
+        return self.last_rollback_depth;
+    }
+
+    pub fn get_max_rollback_depth(&self) -> usize {
+        return self.max_rollback_depth;
+    }
+
+    /// The number of states computed in the last pass
+    pub fn get_last_simulated_frames(&self) -> usize {
+        return self.last_simulated_frames;
+    }
+
+    pub fn get_total_simulated_frames(&self) -> usize {
+        return self.total_simulated_frames;
+    }
+
+    /// The time spent computing states in the last pass
+    pub fn get_last_simulation_duration(&self) -> TimeDuration {
+        return self.last_simulation_duration;
+    }
+
+    pub fn get_total_simulation_duration(&self) -> TimeDuration {
+        return self.total_simulation_duration;
+    }
+
+    /// The number of passes that stopped early because they reached
+    /// [GameTrait::MAX_SIMULATED_FRAMES_PER_PASS](crate::GameTrait::MAX_SIMULATED_FRAMES_PER_PASS)
+    pub fn get_budget_exhausted_count(&self) -> usize {
+        return self.budget_exhausted_count;
+    }
+
+    /// The number of inputs that differed from the input predicted by
+    /// [GameTrait::predict_input](crate::GameTrait::predict_input)
+    pub fn get_mispredicted_input_count(&self) -> usize {
+        return self.mispredicted_input_count;
+    }
+}
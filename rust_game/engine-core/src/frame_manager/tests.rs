@@ -0,0 +1,449 @@
+use crate::frame_manager::FrameManagerCore;
+use crate::interface::{
+    GameTrait,
+    InitialInformation,
+};
+use crate::messaging::FrameIndexAndState;
+use crate::server::{
+    RoomId,
+    ServerConfig,
+};
+use crate::test_utils::{
+    FilteredSumGame,
+    ServerBonusSumGame,
+    SumGame,
+};
+use crate::{
+    EngineError,
+    FrameIndex,
+    ObserveFrames,
+    RejectionReason,
+};
+use commons::logging;
+use commons::real_time::simulation::SingleThreadedFactory;
+use commons::real_time::Factory;
+use std::marker::PhantomData;
+use std::ops::ControlFlow;
+
+struct NoOpObserver<Game: GameTrait>(PhantomData<Game>);
+
+impl<Game: GameTrait> ObserveFrames for NoOpObserver<Game> {
+    type Game = Game;
+
+    const IS_SERVER: bool = true;
+
+    fn input_authoritatively_missing(&self, _: FrameIndex, _: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    fn new_state(&self, _: bool, _: FrameIndexAndState<Game>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+struct ClientNoOpObserver<Game: GameTrait>(PhantomData<Game>);
+
+impl<Game: GameTrait> ObserveFrames for ClientNoOpObserver<Game> {
+    type Game = Game;
+
+    const IS_SERVER: bool = false;
+
+    fn input_authoritatively_missing(&self, _: FrameIndex, _: usize) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    fn new_state(&self, _: bool, _: FrameIndexAndState<Game>) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+}
+
+fn new_core() -> FrameManagerCore<NoOpObserver<SumGame>> {
+    logging::setup_test_logging();
+
+    let factory: Factory = SingleThreadedFactory::new().into();
+    let initial_information = InitialInformation::<SumGame>::new(
+        ServerConfig::new::<SumGame>(&factory, RoomId::default()),
+        1,
+        Vec::new(),
+        SumGame::get_initial_state(1),
+    );
+
+    FrameManagerCore::new(
+        factory.get_time_source().clone(),
+        NoOpObserver(PhantomData),
+        Vec::new(),
+        initial_information,
+    )
+    .unwrap()
+}
+
+fn new_client_core<Game: GameTrait>() -> FrameManagerCore<ClientNoOpObserver<Game>> {
+    logging::setup_test_logging();
+
+    let factory: Factory = SingleThreadedFactory::new().into();
+    let initial_information = InitialInformation::<Game>::new(
+        ServerConfig::new::<Game>(&factory, RoomId::default()),
+        1,
+        vec![0],
+        Game::get_initial_state(1),
+    );
+
+    FrameManagerCore::new(
+        factory.get_time_source().clone(),
+        ClientNoOpObserver(PhantomData),
+        Vec::new(),
+        initial_information,
+    )
+    .unwrap()
+}
+
+fn get_state(core: &FrameManagerCore<NoOpObserver<SumGame>>, frame_index: usize) -> i64 {
+    *core.get_state(FrameIndex::from(frame_index)).unwrap()
+}
+
+#[test]
+fn test_prediction_and_rollback() {
+    let mut core = new_core();
+
+    assert!(core
+        .insert_input(FrameIndex::zero(), 0, 1, true)
+        .is_continue());
+    assert!(core.advance_frame_index(FrameIndex::from(3)).is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    // Frames 1 through 3 repeat the input of frame 0
+    assert_eq!(1, get_state(&core, 1));
+    assert_eq!(2, get_state(&core, 2));
+    assert_eq!(4, get_state(&core, 4));
+
+    // A late input for frame 1 causes frames 2 through 4 to be re-simulated
+    assert!(core
+        .insert_input(FrameIndex::from(1), 0, 5, true)
+        .is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    assert_eq!(1, get_state(&core, 1));
+    assert_eq!(6, get_state(&core, 2));
+    assert_eq!(11, get_state(&core, 3));
+    assert_eq!(16, get_state(&core, 4));
+
+    let metrics = core.get_simulation_metrics();
+    assert_eq!(7, metrics.get_total_simulated_frames());
+    assert_eq!(3, metrics.get_max_rollback_depth());
+    assert_eq!(1, metrics.get_mispredicted_input_count());
+}
+
+#[test]
+fn test_simulation_budget() {
+    let mut core = new_core();
+
+    assert!(core.advance_frame_index(FrameIndex::from(4)).is_continue());
+
+    // Five frames need to be computed, two at a time
+    assert_eq!(ControlFlow::Continue(false), core.update());
+    assert_eq!(ControlFlow::Continue(false), core.update());
+    assert_eq!(ControlFlow::Continue(true), core.update());
+    assert_eq!(
+        2,
+        core.get_simulation_metrics().get_budget_exhausted_count()
+    );
+}
+
+#[test]
+fn test_simulation_budget_after_rollback() {
+    let mut core = new_core();
+
+    assert!(core
+        .insert_input(FrameIndex::zero(), 0, 1, true)
+        .is_continue());
+    assert!(core.advance_frame_index(FrameIndex::from(4)).is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    // A late input for frame 1 re-simulates frames 2 through 5 over two passes
+    assert!(core
+        .insert_input(FrameIndex::from(1), 0, 5, true)
+        .is_continue());
+
+    assert_eq!(ControlFlow::Continue(false), core.update());
+    assert_eq!(11, get_state(&core, 3));
+    assert_eq!(4, get_state(&core, 4));
+
+    assert_eq!(ControlFlow::Continue(true), core.update());
+    assert_eq!(16, get_state(&core, 4));
+    assert_eq!(21, get_state(&core, 5));
+}
+
+#[test]
+fn test_past_window() {
+    // Nothing becomes authoritative on a client without the server's inputs
+    let mut core = new_client_core::<SumGame>();
+
+    assert!(core
+        .insert_input(FrameIndex::zero(), 0, 1, false)
+        .is_continue());
+    assert!(core.advance_frame_index(FrameIndex::from(30)).is_continue());
+    assert!(core.update_until_complete().is_continue());
+    assert!(core.advance_frame_index(FrameIndex::from(31)).is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    // Frames more than MAX_PAST_FRAMES behind are dropped
+    let oldest_frame_index = 31 - SumGame::MAX_PAST_FRAMES;
+    assert!(core
+        .get_state(FrameIndex::from(oldest_frame_index - 1))
+        .is_none());
+    assert_eq!(
+        oldest_frame_index as i64,
+        *core
+            .get_state(FrameIndex::from(oldest_frame_index))
+            .unwrap()
+    );
+    assert_eq!(32, *core.get_state(FrameIndex::from(32)).unwrap());
+
+    assert!(core
+        .insert_input(FrameIndex::from(oldest_frame_index - 1), 0, 1, true)
+        .is_continue());
+    assert!(core
+        .insert_input(FrameIndex::from(oldest_frame_index), 0, 1, true)
+        .is_continue());
+
+    assert_eq!(
+        1,
+        core.get_rejection_counts()
+            .get_count(RejectionReason::TooFarInPast)
+    );
+}
+
+#[test]
+fn test_grace_period_beyond_past_window() {
+    logging::setup_test_logging();
+
+    // The server's grace period doesn't fit in the client's past window
+    let factory: Factory = SingleThreadedFactory::new().into();
+    let mut server_config = ServerConfig::new::<SumGame>(&factory, RoomId::default());
+    server_config.set_input_grace_period_frames(SumGame::MAX_PAST_FRAMES);
+
+    let initial_information = InitialInformation::<SumGame>::new(
+        server_config,
+        1,
+        vec![0],
+        SumGame::get_initial_state(1),
+    );
+
+    let result = FrameManagerCore::new(
+        factory.get_time_source().clone(),
+        ClientNoOpObserver::<SumGame>(PhantomData),
+        Vec::new(),
+        initial_information,
+    );
+
+    assert!(matches!(result, Err(EngineError::InvalidConfiguration(_))));
+}
+
+#[test]
+fn test_future_window() {
+    let mut core = new_core();
+
+    assert!(core
+        .insert_input(FrameIndex::from(5), 0, 1, true)
+        .is_continue());
+    assert!(core
+        .insert_input(FrameIndex::from(6), 0, 1, true)
+        .is_continue());
+
+    let rejection_counts = core.get_rejection_counts();
+    assert_eq!(
+        1,
+        rejection_counts.get_count(RejectionReason::TooFarInFuture)
+    );
+    assert_eq!(0, rejection_counts.get_count(RejectionReason::TooFarInPast));
+}
+
+#[test]
+fn test_invalid_player_index() {
+    let mut core = new_core();
+
+    assert!(core
+        .insert_input(FrameIndex::from(1), 1, 1, true)
+        .is_continue());
+
+    assert_eq!(
+        1,
+        core.get_rejection_counts()
+            .get_count(RejectionReason::InvalidPlayerIndex)
+    );
+}
+
+#[test]
+fn test_filtered_state_on_client() {
+    let mut core = new_client_core::<FilteredSumGame>();
+
+    assert!(core
+        .insert_input(FrameIndex::zero(), 0, 1, true)
+        .is_continue());
+    assert!(core
+        .insert_server_input(FrameIndex::zero(), Vec::new())
+        .is_continue());
+    assert!(core.advance_frame_index(FrameIndex::from(1)).is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    // The state computed from a partial view is not authoritative, so the
+    // complete state from the server replaces it
+    assert_eq!(1, *core.get_state(FrameIndex::from(1)).unwrap());
+
+    assert!(core.insert_state(FrameIndex::from(1), 10).is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    assert_eq!(10, *core.get_state(FrameIndex::from(1)).unwrap());
+    assert_eq!(11, *core.get_state(FrameIndex::from(2)).unwrap());
+}
+
+#[test]
+fn test_server_only_logic_on_client() {
+    let mut core = new_client_core::<ServerBonusSumGame>();
+
+    assert!(core
+        .insert_input(FrameIndex::zero(), 0, 1, true)
+        .is_continue());
+    assert!(core
+        .insert_server_input(FrameIndex::zero(), Vec::new())
+        .is_continue());
+    assert!(core.advance_frame_index(FrameIndex::from(1)).is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    // The client doesn't add the server's bonus, so its state is not
+    // authoritative even though every input is
+    assert_eq!(1, *core.get_state(FrameIndex::from(1)).unwrap());
+
+    assert!(core.insert_state(FrameIndex::from(1), 101).is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    assert_eq!(101, *core.get_state(FrameIndex::from(1)).unwrap());
+    assert_eq!(102, *core.get_state(FrameIndex::from(2)).unwrap());
+}
+
+#[test]
+fn test_missing_input_predicted_from_dropped_frame() {
+    let mut core = new_client_core::<SumGame>();
+
+    // The client's own input for frame 0 never reaches the server
+    assert!(core
+        .insert_input(FrameIndex::zero(), 0, 5, false)
+        .is_continue());
+    assert!(core
+        .insert_server_input(FrameIndex::zero(), Vec::new())
+        .is_continue());
+    assert!(core.advance_frame_index(FrameIndex::from(1)).is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    // The server's state for frame 1 drops frame 0 and its non-authoritative
+    // input
+    assert!(core.insert_state(FrameIndex::from(1), 7).is_continue());
+    assert!(core
+        .insert_missing_input(FrameIndex::from(1), 0)
+        .is_continue());
+    assert!(core
+        .insert_server_input(FrameIndex::from(1), Vec::new())
+        .is_continue());
+    assert!(core.advance_frame_index(FrameIndex::from(2)).is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    // The missing input is predicted from the dropped input, so the state is
+    // not authoritative and the server's state replaces it
+    assert_eq!(12, *core.get_state(FrameIndex::from(2)).unwrap());
+
+    assert!(core.insert_state(FrameIndex::from(2), 10).is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    assert_eq!(10, *core.get_state(FrameIndex::from(2)).unwrap());
+
+    // A missing input predicted from an authoritative input gives an
+    // authoritative state, which the server's state doesn't replace
+    assert!(core
+        .insert_input(FrameIndex::from(2), 0, 3, true)
+        .is_continue());
+    assert!(core
+        .insert_server_input(FrameIndex::from(2), Vec::new())
+        .is_continue());
+    assert!(core
+        .insert_missing_input(FrameIndex::from(3), 0)
+        .is_continue());
+    assert!(core
+        .insert_server_input(FrameIndex::from(3), Vec::new())
+        .is_continue());
+    assert!(core.advance_frame_index(FrameIndex::from(3)).is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    assert_eq!(16, *core.get_state(FrameIndex::from(4)).unwrap());
+
+    assert!(core.insert_state(FrameIndex::from(4), 0).is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    assert_eq!(16, *core.get_state(FrameIndex::from(4)).unwrap());
+}
+
+#[test]
+fn test_resent_server_inputs() {
+    let mut core = new_client_core::<SumGame>();
+
+    for frame_index in 0..3 {
+        assert!(core
+            .insert_input(FrameIndex::from(frame_index), 0, 1, true)
+            .is_continue());
+    }
+
+    // The server inputs of frame 0 are lost, so its state can't become
+    // authoritative and the frame is kept
+    assert!(core
+        .insert_server_inputs(FrameIndex::from(1), vec![Vec::new()])
+        .is_continue());
+    assert!(core.advance_frame_index(FrameIndex::from(1)).is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    assert!(core.get_state(FrameIndex::zero()).is_some());
+
+    // The next message resends them, which completes frames 0 and 1
+    assert!(core
+        .insert_server_inputs(FrameIndex::zero(), vec![Vec::new(), Vec::new()])
+        .is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    assert!(core.get_state(FrameIndex::zero()).is_none());
+    assert_eq!(2, *core.get_state(FrameIndex::from(2)).unwrap());
+
+    // Server inputs of dropped frames and frames that already have them are
+    // skipped without being rejected
+    assert!(core
+        .insert_server_inputs(FrameIndex::zero(), vec![Vec::new(), Vec::new(), Vec::new()])
+        .is_continue());
+    assert!(core.advance_frame_index(FrameIndex::from(2)).is_continue());
+    assert!(core.update_until_complete().is_continue());
+
+    assert!(core.get_state(FrameIndex::from(2)).is_none());
+    assert_eq!(3, *core.get_state(FrameIndex::from(3)).unwrap());
+    assert_eq!(0, core.get_rejection_counts().get_total_count());
+}
+
+#[test]
+fn test_input_validation() {
+    let mut core = new_core();
+
+    // Negative inputs are rejected and counted
+    assert!(core
+        .validate_and_insert_input(FrameIndex::zero(), 0, -5)
+        .is_continue());
+    assert_eq!(1, core.get_input_rejection_counts().get_count(0));
+
+    // Inputs above 10 are clamped
+    assert!(core
+        .validate_and_insert_input(FrameIndex::zero(), 0, 20)
+        .is_continue());
+    assert!(core.advance_frame_index(FrameIndex::from(1)).is_continue());
+    assert!(core.update_until_complete().is_continue());
+    assert_eq!(10, get_state(&core, 1));
+
+    // Once a frame has an authoritative input, later ones are ignored
+    assert!(core
+        .validate_and_insert_input(FrameIndex::zero(), 0, -1)
+        .is_continue());
+    assert_eq!(1, core.get_input_rejection_counts().get_total_count());
+}
@@ -63,15 +63,17 @@ impl GameTimerScheduler {
     ) -> Self {
         let timer_id = idle_timer_service.create_timer(call_back, Schedule::Never);
 
-        // start is now, which is the same as the time of occurance of FrameIndex 0
-        let start_time = StartTime::new(time_source.now());
+        // start is now, which is the same as the time of occurance of the
+        // first FrameIndex
+        let first_frame_index = server_config.get_start_time().get_first_frame_index();
+        let start_time = StartTime::new_at_frame(time_source.now(), first_frame_index);
 
         return Self {
             time_source,
             frame_duration: *server_config.get_frame_duration(),
             remote_start_time: *server_config.get_start_time(),
             start_time,
-            current_frame_index: FrameIndex::zero(),
+            current_frame_index: first_frame_index,
             rolling_average: RollingAverage::new(rolling_average_size),
             timer_id,
         };
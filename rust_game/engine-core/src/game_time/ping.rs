@@ -113,6 +113,9 @@ impl CompletedPing {
 
     /// Converts the server's [`StartTime`] into the client's by adding the offset to the client's [`StartTime`].
     pub(super) fn get_local_start_time(offset: f64, remote_start_time: &StartTime) -> StartTime {
-        StartTime::new(remote_start_time.get_time_value() + TimeDuration::from_secs_f64(offset))
+        StartTime::new_at_frame(
+            remote_start_time.get_time_value() + TimeDuration::from_secs_f64(offset),
+            remote_start_time.get_first_frame_index(),
+        )
     }
 }
@@ -1,4 +1,7 @@
-use commons::time::TimeValue;
+use commons::time::{
+    TimeDuration,
+    TimeValue,
+};
 use serde::{
     Deserialize,
     Serialize,
@@ -9,13 +12,19 @@ use crate::{
     FrameIndex,
 };
 
-/// The time of occurance for frame index 0.  On clients, this time can float around to slave the client's clock to the server's clock.
+/// The time of occurance for the first frame index of a session, which is 0
+/// unless the session resumed a [ServerSnapshot](crate::ServerSnapshot).  On clients, this time can float around to slave the client's clock to the server's clock.
 #[derive(Serialize, Deserialize, Clone, Debug, Copy)]
-pub struct StartTime(TimeValue);
+pub struct StartTime(TimeValue, FrameIndex);
 
 impl StartTime {
     pub fn new(start_time: TimeValue) -> Self {
-        Self(start_time)
+        Self(start_time, FrameIndex::zero())
+    }
+
+    /// Creates a [`StartTime`] for a session whose first frame is `first_frame_index`
+    pub fn new_at_frame(start_time: TimeValue, first_frame_index: FrameIndex) -> Self {
+        Self(start_time, first_frame_index)
     }
 
     /// Calculates the time of occurance of a particular [`FrameIndex`]
@@ -24,7 +33,11 @@ impl StartTime {
         frame_duration: &FrameDuration,
         frame_index: &FrameIndex,
     ) -> TimeValue {
-        self.0 + frame_duration.duration_from_start(frame_index)
+        let frames_since_start = frame_index.usize() as f64 - self.1.usize() as f64;
+        self.0
+            + frame_duration
+                .get_frame_duration()
+                .mul_f64(frames_since_start)
     }
 
     /// Calculates a floating point number that represents the fractional [`FrameIndex`] of a [`TimeValue`]
@@ -34,7 +47,18 @@ impl StartTime {
         time_value: &TimeValue,
     ) -> f64 {
         let duration_since_start = time_value.duration_since(&self.0);
-        duration_since_start.as_secs_f64() / frame_duration.get_frame_duration().as_secs_f64()
+        self.1.usize() as f64
+            + duration_since_start.as_secs_f64() / frame_duration.get_frame_duration().as_secs_f64()
+    }
+
+    /// Calculates the game time elapsed at a [`TimeValue`] since [`FrameIndex`] 0,
+    /// including the frames played before a resumed session
+    pub fn get_duration_since_game_start(
+        &self,
+        frame_duration: &FrameDuration,
+        time_value: &TimeValue,
+    ) -> TimeDuration {
+        frame_duration.duration_from_start(&self.1) + &time_value.duration_since(&self.0)
     }
 
     /// Calculates the [`FrameIndex`] most recently prior to a [`TimeValue`]
@@ -52,4 +76,9 @@ impl StartTime {
     pub fn get_time_value(&self) -> &TimeValue {
         &self.0
     }
+
+    /// Returns the first [`FrameIndex`] of the session
+    pub fn get_first_frame_index(&self) -> FrameIndex {
+        self.1
+    }
 }
@@ -2,55 +2,207 @@ use crate::client::{
     ClientCore,
     ClientCoreEvent,
 };
-use crate::interface::RenderReceiver;
+use crate::interface::{
+    ConnectConfig,
+    ConnectionState,
+    EngineError,
+    FrameObserver,
+    InputEventHandler,
+    RenderReceiver,
+    ServerDiscovery,
+};
+use crate::server::RoomId;
 use crate::GameTrait;
 use commons::real_time::{
     EventHandlerBuilder,
     EventSender,
     Factory,
+    Receiver,
 };
-use std::net::Ipv4Addr;
-use std::str::FromStr;
+use commons::time::TimeDuration;
 
 pub struct Client<Game: GameTrait> {
     core_sender: EventSender<ClientCoreEvent<Game>>,
+    message_receiver_option: Option<Receiver<Game::ServerMessage>>,
+    connection_state: ConnectionState,
+    connection_state_receiver: Receiver<ConnectionState>,
 }
 
 impl<Game: GameTrait> Client<Game> {
-    pub fn new(factory: Factory) -> (Self, RenderReceiver<Game>) {
-        let client_core_thread_builder = EventHandlerBuilder::<ClientCore<Game>>::new(&factory);
+    /// Connects to a server on the local host.  If the server is a
+    /// [RoomServer](crate::RoomServer), the client is assigned to a room.
+    ///
+    /// The connection is made in the background, retrying with the default
+    /// [ConnectConfig] until the server accepts it, so the client can be
+    /// started before the server.  Its progress is reported by
+    /// [get_connection_state](Self::get_connection_state).
+    pub fn new<InputHandler: InputEventHandler<Game = Game>>(
+        factory: Factory,
+        input_event_handler: InputHandler,
+    ) -> Result<(Self, RenderReceiver<Game>), EngineError> {
+        return Self::new_with_connect_config(
+            factory,
+            input_event_handler,
+            None,
+            ConnectConfig::default(),
+        );
+    }
+
+    /// Connects to the room with `room_id` on a [RoomServer](crate::RoomServer)
+    pub fn join_room<InputHandler: InputEventHandler<Game = Game>>(
+        factory: Factory,
+        input_event_handler: InputHandler,
+        room_id: RoomId,
+    ) -> Result<(Self, RenderReceiver<Game>), EngineError> {
+        return Self::new_with_connect_config(
+            factory,
+            input_event_handler,
+            Some(room_id),
+            ConnectConfig::default(),
+        );
+    }
+
+    /// Connects to the server at the address in `connect_config`, or to the
+    /// room with `room_id` on a [RoomServer](crate::RoomServer), retrying as
+    /// configured by `connect_config`
+    pub fn new_with_connect_config<InputHandler: InputEventHandler<Game = Game>>(
+        factory: Factory,
+        input_event_handler: InputHandler,
+        room_id: Option<RoomId>,
+        connect_config: ConnectConfig,
+    ) -> Result<(Self, RenderReceiver<Game>), EngineError> {
+        return Self::new_with_local_players(
+            factory,
+            vec![input_event_handler],
+            room_id,
+            connect_config,
+        );
+    }
+
+    /// Connects with several players on this client, such as for
+    /// split-screen.  Each input handler is one local player, and the server
+    /// gives each of them a player index.  The indices are available from
+    /// [RenderReceiver::get_local_player_indices] once the game starts.
+    ///
+    /// Fails if there are no input handlers or more than
+    /// [MAX_LOCAL_PLAYERS](GameTrait::MAX_LOCAL_PLAYERS).
+    pub fn new_with_local_players<InputHandler: InputEventHandler<Game = Game>>(
+        factory: Factory,
+        input_event_handlers: Vec<InputHandler>,
+        room_id: Option<RoomId>,
+        connect_config: ConnectConfig,
+    ) -> Result<(Self, RenderReceiver<Game>), EngineError> {
+        if input_event_handlers.is_empty() || input_event_handlers.len() > Game::MAX_LOCAL_PLAYERS {
+            return Err(EngineError::InvalidCall(
+                "A client needs between one and MAX_LOCAL_PLAYERS local players",
+            ));
+        }
+
+        let client_core_thread_builder =
+            EventHandlerBuilder::<ClientCore<Game, InputHandler>>::new(&factory);
 
         let (render_receiver_sender, render_receiver) = RenderReceiver::<Game>::new(&factory);
 
         let core_sender = client_core_thread_builder.get_sender().clone();
 
+        let (message_sender, message_receiver) = factory.new_channel();
+        let (connection_state_sender, connection_state_receiver) = factory.new_channel();
+
+        let client_core = ClientCore::<Game, InputHandler>::new(
+            factory,
+            connect_config.get_server_ip(),
+            core_sender.clone(),
+            render_receiver_sender,
+            input_event_handlers,
+            room_id,
+            message_sender,
+            connect_config,
+            connection_state_sender,
+        );
+
         client_core_thread_builder
-            .spawn_thread(
-                "ClientCore".to_string(),
-                ClientCore::<Game>::new(
-                    factory,
-                    Ipv4Addr::from_str("127.0.0.1").unwrap(),
-                    core_sender.clone(),
-                    render_receiver_sender,
-                ),
-            )
-            .unwrap();
+            .spawn_thread("ClientCore".to_string(), client_core)
+            .map_err(EngineError::Io)?;
+
+        let client = Self {
+            core_sender,
+            message_receiver_option: Some(message_receiver),
+            connection_state: ConnectionState::Connecting { attempt: 1 },
+            connection_state_receiver,
+        };
+
+        return Ok((client, render_receiver));
+    }
 
-        let client = Self { core_sender };
+    /// Broadcasts a probe for servers of this game on the LAN.  Servers that
+    /// have [discovery enabled](crate::Server::enable_discovery) answer, and
+    /// their answers are collected by the returned [ServerDiscovery] until
+    /// `timeout` elapses.  A discovered server is joined by setting its address
+    /// in the [ConnectConfig].
+    pub fn discover(
+        factory: &Factory,
+        timeout: TimeDuration,
+    ) -> Result<ServerDiscovery, EngineError> {
+        return ServerDiscovery::new::<Game>(factory, timeout);
+    }
+
+    /// Registers a [FrameObserver] that is notified of the frames computed by
+    /// this client.  Observers added after the game has started miss the
+    /// frames computed before they were added.
+    pub fn add_frame_observer(
+        &self,
+        frame_observer: impl FrameObserver<Game>,
+    ) -> Result<(), EngineError> {
+        self.core_sender
+            .send_event(ClientCoreEvent::AddFrameObserver(Box::new(frame_observer)))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Sends a [message](GameTrait::ClientMessage) to the server.  Messages
+    /// are delivered reliably and in order.
+    pub fn send_message(&self, message: Game::ClientMessage) -> Result<(), EngineError> {
+        self.core_sender
+            .send_event(ClientCoreEvent::SendMessage(message))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Takes the queue of [messages](GameTrait::ServerMessage) received from
+    /// the server.  Messages are queued until it is taken.
+    pub fn take_message_receiver(&mut self) -> Option<Receiver<Game::ServerMessage>> {
+        return self.message_receiver_option.take();
+    }
+
+    /// Returns the latest [ConnectionState] of this client
+    pub fn get_connection_state(&mut self) -> &ConnectionState {
+        while let Ok(connection_state) = self.connection_state_receiver.try_recv() {
+            self.connection_state = connection_state;
+        }
 
-        return (client, render_receiver);
+        return &self.connection_state;
     }
 
+    /// Sends an input event to the first local player
     pub fn send_client_input_event(
         &self,
         client_input_event: Game::ClientInputEvent,
     ) -> Result<(), Game::ClientInputEvent> {
-        return match self
-            .core_sender
-            .send_event(ClientCoreEvent::OnInputEvent(client_input_event))
-        {
+        return self.send_local_player_input_event(0, client_input_event);
+    }
+
+    /// Sends an input event to the local player at `local_player`, which is
+    /// the position of its input handler in
+    /// [new_with_local_players](Self::new_with_local_players)
+    pub fn send_local_player_input_event(
+        &self,
+        local_player: usize,
+        client_input_event: Game::ClientInputEvent,
+    ) -> Result<(), Game::ClientInputEvent> {
+        return match self.core_sender.send_event(ClientCoreEvent::OnInputEvent(
+            local_player,
+            client_input_event,
+        )) {
             Ok(()) => Ok(()),
-            Err(ClientCoreEvent::OnInputEvent(client_input_event)) => Err(client_input_event),
+            Err(ClientCoreEvent::OnInputEvent(_, client_input_event)) => Err(client_input_event),
             _ => panic!("This should never happen."),
         };
     }
@@ -0,0 +1,117 @@
+use crate::interface::SessionToken;
+use commons::time::TimeDuration;
+use std::net::Ipv4Addr;
+
+/// Which server a [Client](crate::Client) connects to, and how it retries
+/// connecting to a server that is not accepting connections yet.
+///
+/// The server is on the local host by default.  A server found by
+/// [Client::discover](crate::Client::discover) is joined by setting its
+/// [IP address](crate::DiscoveredServer::get_ip_addr).
+///
+/// The delay before each retry starts at the initial backoff and doubles after
+/// every failed attempt, up to the maximum backoff.  Once the maximum number of
+/// attempts have failed, the client gives up and reports
+/// [ConnectionState::Failed](crate::ConnectionState::Failed).
+///
+/// A client rejoining a [resumed](crate::Server::resume) match also sets the
+/// [SessionToken] it was given in the original match.  A client of a server
+/// with a [StandbyServer](crate::StandbyServer) sets the standby's address to
+/// follow the match to it.
+#[derive(Clone, Copy, Debug)]
+pub struct ConnectConfig {
+    server_ip: Ipv4Addr,
+    max_attempts: Option<usize>,
+    initial_backoff: TimeDuration,
+    max_backoff: TimeDuration,
+    session_token: Option<SessionToken>,
+    standby_server_ip: Option<Ipv4Addr>,
+}
+
+impl ConnectConfig {
+    /// The IP address of the server.  The client connects to its
+    /// [TCP_PORT](crate::GameTrait::TCP_PORT) and
+    /// [UDP_PORT](crate::GameTrait::UDP_PORT).
+    pub fn get_server_ip(&self) -> Ipv4Addr {
+        return self.server_ip;
+    }
+
+    pub fn set_server_ip(&mut self, server_ip: Ipv4Addr) {
+        self.server_ip = server_ip;
+    }
+
+    /// The maximum number of connection attempts, or [None] to retry forever
+    pub fn get_max_attempts(&self) -> Option<usize> {
+        return self.max_attempts;
+    }
+
+    pub fn set_max_attempts(&mut self, max_attempts: Option<usize>) {
+        self.max_attempts = max_attempts;
+    }
+
+    /// The delay before the first retry
+    pub fn get_initial_backoff(&self) -> TimeDuration {
+        return self.initial_backoff;
+    }
+
+    pub fn set_initial_backoff(&mut self, initial_backoff: TimeDuration) {
+        self.initial_backoff = initial_backoff;
+    }
+
+    /// The longest delay between two attempts
+    pub fn get_max_backoff(&self) -> TimeDuration {
+        return self.max_backoff;
+    }
+
+    pub fn set_max_backoff(&mut self, max_backoff: TimeDuration) {
+        self.max_backoff = max_backoff;
+    }
+
+    /// The [SessionToken] presented to a resumed server to rejoin a match.  A
+    /// server starting a new match ignores it and gives the client a new one.
+    pub fn get_session_token(&self) -> Option<SessionToken> {
+        return self.session_token;
+    }
+
+    pub fn set_session_token(&mut self, session_token: Option<SessionToken>) {
+        self.session_token = session_token;
+    }
+
+    /// The address of the [StandbyServer](crate::StandbyServer) the client
+    /// reconnects to, with its [SessionToken], if the connection to the server
+    /// closes during the game.  The client reconnects once, and its frame
+    /// observers do not follow it to the standby.
+    pub fn get_standby_server_ip(&self) -> Option<Ipv4Addr> {
+        return self.standby_server_ip;
+    }
+
+    pub fn set_standby_server_ip(&mut self, standby_server_ip: Option<Ipv4Addr>) {
+        self.standby_server_ip = standby_server_ip;
+    }
+
+    /// Returns the delay after the failed attempt number `attempt`, counting
+    /// from 1
+    pub(crate) fn get_backoff(&self, attempt: usize) -> TimeDuration {
+        let exponent = attempt.saturating_sub(1).min(30) as i32;
+        let backoff = self.initial_backoff.mul_f64(2f64.powi(exponent));
+
+        if backoff.as_secs_f64() > self.max_backoff.as_secs_f64() {
+            return self.max_backoff;
+        } else {
+            return backoff;
+        }
+    }
+}
+
+impl Default for ConnectConfig {
+    fn default() -> Self {
+        return Self {
+            server_ip: Ipv4Addr::LOCALHOST,
+            max_attempts: Some(10),
+            initial_backoff: TimeDuration::from_millis_f64(100.0),
+            max_backoff: TimeDuration::from_secs_f64(2.0),
+            session_token: None,
+            standby_server_ip: None,
+        };
+    }
+}
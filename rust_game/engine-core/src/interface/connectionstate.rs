@@ -0,0 +1,23 @@
+use crate::interface::EngineError;
+
+/// The progress of a [Client](crate::Client) connecting to a server and
+/// joining its game, read with
+/// [Client::get_connection_state](crate::Client::get_connection_state)
+#[derive(Debug)]
+pub enum ConnectionState {
+    /// The client is connecting to the server.  `attempt` counts from 1 and
+    /// grows each time the connection is retried.
+    Connecting { attempt: usize },
+
+    /// The client is connected and is asking to join a room
+    Connected,
+
+    /// The client has joined and is waiting for the server to start the game
+    WaitingForGameStart,
+
+    /// The game has started
+    InGame,
+
+    /// The client gave up, and will not connect
+    Failed(EngineError),
+}
@@ -0,0 +1,61 @@
+use crate::FrameIndex;
+
+/// A small, platform independent pseudo random number generator.
+///
+/// Given the same seed, a [DeterministicRng] produces the same sequence of
+/// values on every machine, which keeps the server and clients in sync when
+/// randomness is used to compute [States](crate::GameTrait::State).
+#[derive(Clone, Debug)]
+pub struct DeterministicRng {
+    state: u64,
+}
+
+impl DeterministicRng {
+    pub fn new(seed: u64) -> Self {
+        return Self { state: seed };
+    }
+
+    /// Creates a [DeterministicRng] for a single frame of a session.  Every
+    /// computation of the same frame in the same session gets the same sequence.
+    pub fn for_frame(session_seed: u64, frame_index: FrameIndex) -> Self {
+        let frame_seed = mix(session_seed ^ mix(frame_index.usize() as u64));
+        return Self::new(frame_seed);
+    }
+
+    /// Returns the next value in the sequence
+    pub fn next_u64(&mut self) -> u64 {
+        // SplitMix64
+        self.state = self.state.wrapping_add(0x9E3779B97F4A7C15);
+        return mix(self.state);
+    }
+
+    /// Returns a value uniformly distributed in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        return (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+    }
+
+    /// Returns a value uniformly distributed in `[min, max)`
+    pub fn range_f64(&mut self, min: f64, max: f64) -> f64 {
+        return min + (max - min) * self.next_f64();
+    }
+
+    /// Returns a value in `[min, max)`.  Panics if `max <= min`.
+    pub fn range_usize(&mut self, min: usize, max: usize) -> usize {
+        if max <= min {
+            panic!("Empty range: {:?}..{:?}", min, max);
+        }
+
+        return min + (self.next_u64() % (max - min) as u64) as usize;
+    }
+
+    pub fn next_bool(&mut self) -> bool {
+        return self.next_u64() & 1 == 1;
+    }
+}
+
+fn mix(value: u64) -> u64 {
+    let mut z = value;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    return z ^ (z >> 31);
+}
@@ -0,0 +1,67 @@
+use crate::messaging::DiscoveryResponse;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::net::{
+    Ipv4Addr,
+    SocketAddr,
+    SocketAddrV4,
+};
+
+/// Whether a discovered server is still accepting players
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ServerState {
+    /// The server is waiting for players and has not started its game
+    Listening,
+    /// The server's game has started and new players are turned away
+    Running,
+}
+
+/// A server found on the LAN by [Client::discover](crate::Client::discover)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiscoveredServer {
+    game_name: String,
+    player_count: usize,
+    state: ServerState,
+    ip_addr: Ipv4Addr,
+    tcp_port: u16,
+}
+
+impl DiscoveredServer {
+    pub(crate) fn new(ip_addr: Ipv4Addr, response: DiscoveryResponse) -> Self {
+        return Self {
+            game_name: response.get_game_name().to_string(),
+            player_count: response.get_player_count(),
+            state: response.get_state(),
+            ip_addr,
+            tcp_port: response.get_tcp_port(),
+        };
+    }
+
+    /// The [GAME_NAME](crate::GameTrait::GAME_NAME) of the server's game
+    pub fn get_game_name(&self) -> &str {
+        return &self.game_name;
+    }
+
+    /// The number of players connected when the server answered
+    pub fn get_player_count(&self) -> usize {
+        return self.player_count;
+    }
+
+    pub fn get_state(&self) -> ServerState {
+        return self.state;
+    }
+
+    /// The IP address of the server, which is set with
+    /// [ConnectConfig::set_server_ip](crate::ConnectConfig::set_server_ip) to
+    /// join it
+    pub fn get_ip_addr(&self) -> Ipv4Addr {
+        return self.ip_addr;
+    }
+
+    /// The address clients connect to over TCP to join the server
+    pub fn get_tcp_address(&self) -> SocketAddr {
+        return SocketAddr::V4(SocketAddrV4::new(self.ip_addr, self.tcp_port));
+    }
+}
@@ -0,0 +1,88 @@
+use std::fmt::{
+    Display,
+    Formatter,
+};
+use std::io;
+
+/// The error returned by the fallible APIs of the engine, so that embedding
+/// applications can react to failures instead of crashing
+#[derive(Debug)]
+pub enum EngineError {
+    /// A socket could not be bound to its address, usually because the port
+    /// is already in use
+    Bind(io::Error),
+
+    /// The connection to the server was refused or failed
+    Connect(io::Error),
+
+    /// Any other I/O failure, such as failing to spawn a thread
+    Io(io::Error),
+
+    /// The thread that handles the request has stopped, usually because its
+    /// connection was closed
+    ThreadStopped,
+
+    /// A message could not be serialized
+    Serialization(rmp_serde::encode::Error),
+
+    /// A message or file could not be deserialized
+    Deserialization(rmp_serde::decode::Error),
+
+    /// A request or message is not valid in the current state
+    ProtocolViolation(&'static str),
+
+    /// The game's constants do not fit the server's configuration
+    InvalidConfiguration(&'static str),
+
+    /// A method was called when it is not allowed, such as adding a local
+    /// player after the game has started
+    InvalidCall(&'static str),
+
+    /// A [ServerSnapshot](crate::ServerSnapshot) assigns player indices that
+    /// are out of range or assigned twice
+    InvalidSnapshot(&'static str),
+}
+
+impl EngineError {
+    /// Maps the error of sending an event to a stopped thread, discarding the
+    /// returned event
+    pub(crate) fn thread_stopped<T>(_: T) -> Self {
+        return EngineError::ThreadStopped;
+    }
+}
+
+impl Display for EngineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EngineError::Bind(error) => write!(f, "failed to bind a socket: {}", error),
+            EngineError::Connect(error) => write!(f, "failed to connect: {}", error),
+            EngineError::Io(error) => write!(f, "I/O error: {}", error),
+            EngineError::ThreadStopped => write!(f, "the handling thread has stopped"),
+            EngineError::Serialization(error) => write!(f, "failed to serialize: {}", error),
+            EngineError::Deserialization(error) => write!(f, "failed to deserialize: {}", error),
+            EngineError::ProtocolViolation(reason) => write!(f, "protocol violation: {}", reason),
+            EngineError::InvalidConfiguration(reason) => {
+                write!(f, "invalid configuration: {}", reason)
+            }
+            EngineError::InvalidCall(reason) => write!(f, "invalid call: {}", reason),
+            EngineError::InvalidSnapshot(reason) => write!(f, "invalid snapshot: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            EngineError::Bind(error) | EngineError::Connect(error) | EngineError::Io(error) => {
+                Some(error)
+            }
+            EngineError::Serialization(error) => Some(error),
+            EngineError::Deserialization(error) => Some(error),
+            EngineError::ThreadStopped
+            | EngineError::ProtocolViolation(_)
+            | EngineError::InvalidConfiguration(_)
+            | EngineError::InvalidCall(_)
+            | EngineError::InvalidSnapshot(_) => None,
+        }
+    }
+}
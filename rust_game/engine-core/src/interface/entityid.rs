@@ -0,0 +1,33 @@
+use crate::FrameIndex;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// An identifier for a game entity that is allocated deterministically by
+/// [UpdateArg::new_entity_id](crate::UpdateArg::new_entity_id).
+///
+/// IDs are made from the [FrameIndex] of the frame that allocated them and
+/// the order of allocation within that frame, so re-simulating a frame
+/// allocates the same IDs and IDs from different frames never collide.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct EntityId {
+    frame_index: FrameIndex,
+    index: u32,
+}
+
+impl EntityId {
+    pub fn new(frame_index: FrameIndex, index: u32) -> Self {
+        return Self { frame_index, index };
+    }
+
+    /// The [FrameIndex] of the frame in which this ID was allocated
+    pub fn get_frame_index(&self) -> FrameIndex {
+        return self.frame_index;
+    }
+
+    /// The order in which this ID was allocated within its frame
+    pub fn get_index(&self) -> u32 {
+        return self.index;
+    }
+}
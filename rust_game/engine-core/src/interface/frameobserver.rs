@@ -0,0 +1,74 @@
+use crate::frame_manager::{
+    InputRejectionCounts,
+    RejectionCounts,
+    RejectionReason,
+    SimulationMetrics,
+};
+use crate::server::DroppedTrafficCounts;
+use crate::{
+    FrameIndex,
+    GameTrait,
+};
+
+/// A hook to observe the frames computed by a [Server](crate::Server) or
+/// [Client](crate::Client), for uses such as analytics, replay writers or debug
+/// overlays.
+///
+/// Observers are registered with [Server::add_frame_observer](crate::Server::add_frame_observer)
+/// or [Client::add_frame_observer](crate::Client::add_frame_observer) and are
+/// called from the thread that computes frames, so they should return quickly.
+/// Every method has a default implementation that does nothing.
+pub trait FrameObserver<Game: GameTrait>: 'static + Send {
+    /// Called when a new [State](GameTrait::State) is computed or an
+    /// authoritative State is received
+    fn on_new_state(
+        &mut self,
+        _frame_index: FrameIndex,
+        _state: &Game::State,
+        _is_authoritative: bool,
+    ) {
+    }
+
+    /// Called when a player's [Input](crate::Input) is declared authoritatively
+    /// missing.  On the server this is called when the input times out, on a
+    /// client when the server's declaration is received.
+    fn on_input_missing(&mut self, _frame_index: FrameIndex, _player_index: usize) {}
+
+    /// Called when an input differs from the one predicted by
+    /// [GameTrait::predict_input]
+    fn on_input_mispredicted(&mut self, _frame_index: FrameIndex, _player_index: usize) {}
+
+    /// Called when already computed States are re-simulated, starting at
+    /// `first_frame_index`.  `rollback_depth` is the number of computed States
+    /// that are invalidated.
+    fn on_rollback(&mut self, _first_frame_index: FrameIndex, _rollback_depth: usize) {}
+
+    /// Called when a frame is dropped and can no longer be re-simulated
+    fn on_frame_dropped(&mut self, _frame_index: FrameIndex, _is_state_authoritative: bool) {}
+
+    /// Called when an [Input](crate::Input) or State is rejected
+    fn on_rejected(
+        &mut self,
+        _frame_index: FrameIndex,
+        _reason: RejectionReason,
+        _counts: &RejectionCounts,
+    ) {
+    }
+
+    /// Called on the server when an input from a remote player is rejected by
+    /// [GameTrait::validate_input]
+    fn on_input_rejected(
+        &mut self,
+        _frame_index: FrameIndex,
+        _player_index: usize,
+        _counts: &InputRejectionCounts,
+    ) {
+    }
+
+    /// Called on the server, at most once per frame, when datagrams or inputs
+    /// were dropped for going over the [RateLimits](crate::RateLimits)
+    fn on_traffic_dropped(&mut self, _counts: &DroppedTrafficCounts) {}
+
+    /// Called after each pass in which States were re-simulated
+    fn on_simulation_metrics(&mut self, _metrics: &SimulationMetrics) {}
+}
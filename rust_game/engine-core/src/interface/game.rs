@@ -1,6 +1,10 @@
 use crate::interface::InitialInformation;
+use crate::interface::InputValidation;
 use crate::interface::InterpolationArg;
-use crate::UpdateArg;
+use crate::{
+    FrameIndex,
+    UpdateArg,
+};
 use commons::time::TimeDuration;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
@@ -8,43 +12,140 @@ use std::fmt::Debug;
 
 //TODO: can Serialize + DeserializeOwned be removed
 pub trait GameTrait: 'static + Send + Sized + Clone {
-    type State: Serialize + DeserializeOwned + Clone + Debug + Send + Sync + 'static;
+    type State: Serialize + DeserializeOwned + Clone + Debug + PartialEq + Send + Sync + 'static;
 
-    type ClientInput: Serialize + DeserializeOwned + Clone + Debug + Send + 'static;
+    /// The input of one player for one frame.  Inputs are compared with the
+    /// ones predicted by [predict_input](Self::predict_input) to detect
+    /// mispredictions, so they must implement [PartialEq].  This bound is new,
+    /// so existing games need to derive PartialEq for their ClientInput.
+    type ClientInput: Serialize + DeserializeOwned + Clone + Debug + PartialEq + Send + 'static;
 
-    type InterpolationResult: Send + 'static;
+    /// Input originating from the server rather than a player, such as game
+    /// events or admin commands.  These are injected through the
+    /// [Server](crate::Server) and broadcast to clients like player inputs.
+    type ServerInput: Serialize + DeserializeOwned + Clone + Debug + Send + 'static;
+
+    type InterpolationResult: Clone + Send + 'static;
 
     type ClientInputEvent: Send + 'static;
 
-    //TODO: make input event handler its own trait
-    type ClientInputEventHandler: Send + 'static;
+    /// A message sent reliably from a client to the server over TCP, for
+    /// anything outside of the frame inputs, such as chat or votes
+    type ClientMessage: Serialize + DeserializeOwned + Clone + Debug + Send + 'static;
+
+    /// A message sent reliably from the server to one or all clients over TCP
+    type ServerMessage: Serialize + DeserializeOwned + Clone + Debug + Send + 'static;
+
+    /// Identifies the game to LAN discovery.  Servers only answer discovery
+    /// probes from clients of the same game.
+    const GAME_NAME: &'static str;
 
     const TCP_PORT: u16;
     const UDP_PORT: u16;
+
+    /// The UDP port servers listen on for LAN discovery probes
+    const DISCOVERY_PORT: u16;
+
     const STEP_PERIOD: TimeDuration;
     const GRACE_PERIOD: TimeDuration;
     //TODO: rename ping period
     const PING_PERIOD: TimeDuration;
     const CLOCK_AVERAGE_SIZE: usize;
 
+    /// The maximum number of States the [FrameManager](crate::frame_manager::FrameManager)
+    /// computes before checking for new events.  This bounds the time spent
+    /// re-simulating after a deep rollback.
+    const MAX_SIMULATED_FRAMES_PER_PASS: usize = 50;
+
+    /// How far ahead of the current [FrameIndex] Inputs and States are
+    /// accepted.  Anything further in the future is rejected.
+    const MAX_FUTURE_FRAMES: usize = 50;
+
+    /// How many frames behind the current [FrameIndex] are kept for rollback.
+    /// Older frames are dropped even if they are not yet authoritative.  This
+    /// must be more than the [GRACE_PERIOD](Self::GRACE_PERIOD) in frames, so
+    /// that frames are not dropped before their inputs time out.
+    const MAX_PAST_FRAMES: usize = 100;
+
+    /// Set to true by games that override
+    /// [filter_state_for_player](Self::filter_state_for_player).  Clients then
+    /// treat authoritative states as partial views, and states they compute
+    /// from them are never authoritative.
+    const IS_STATE_FILTERED: bool = false;
+
+    /// Set to true by games whose [get_next_state](Self::get_next_state)
+    /// depends on [UpdateArg::is_server], such as games that only spawn or
+    /// score on the server.  States computed by clients are then never
+    /// authoritative, and are replaced by the states sent by the server.
+    const HAS_SERVER_ONLY_LOGIC: bool = false;
+
+    /// The most players a single client can ask to join with, such as for
+    /// split-screen.  Servers drop connections that ask for more.
+    const MAX_LOCAL_PLAYERS: usize = 4;
+
+    /// How long a [resumed](crate::Server::resume) match waits for its players
+    /// to rejoin before it continues without the ones that have not
+    const REJOIN_TIMEOUT: TimeDuration = TimeDuration::new(30, 0);
+
     fn get_initial_state(player_count: usize) -> Self::State;
 
     fn get_next_state(arg: &UpdateArg<Self>) -> Self::State;
 
-    //TODO: this method needs to include the last interpolation result
+    /// Predicts the input of a player whose [Input](crate::Input) is
+    /// [Pending](crate::Input::Pending) or
+    /// [AuthoritativeMissing](crate::Input::AuthoritativeMissing).
+    /// `previous_inputs` holds the known or predicted input of each player in
+    /// the previous frame.  The prediction is available from
+    /// [UpdateArg::get_input_or_prediction].
+    ///
+    /// Predictions of authoritatively missing inputs become part of
+    /// authoritative states, so this must be deterministic.  By default, the
+    /// player's previous input is repeated.
+    fn predict_input(
+        previous_inputs: &[Option<Self::ClientInput>],
+        player_index: usize,
+        _frame_index: FrameIndex,
+    ) -> Option<Self::ClientInput> {
+        return previous_inputs[player_index].clone();
+    }
+
+    /// Checks an input received from a remote player before the server makes
+    /// it authoritative and sends it to the other clients.  `state` is the
+    /// server's latest state at or before the input's frame, which may still be
+    /// predicted.  Rejections are counted per player and reported to
+    /// [FrameObserver::on_input_rejected](crate::FrameObserver::on_input_rejected).
+    ///
+    /// Inputs of the listen-server local player are not validated.  By
+    /// default, every input is accepted.
+    fn validate_input(
+        _state: &Self::State,
+        _player_index: usize,
+        _input: &Self::ClientInput,
+    ) -> InputValidation<Self> {
+        return InputValidation::Accept;
+    }
+
+    /// Returns the part of `state` that the players with `player_indices` are
+    /// allowed to see, for interest management or fog of war.  The server
+    /// applies this to the initial state and to every authoritative state
+    /// before sending them to a client, with the player indices of all of the
+    /// client's local players.  It is only called when
+    /// [IS_STATE_FILTERED](Self::IS_STATE_FILTERED) is true.
+    ///
+    /// The server's own [RenderReceiver](crate::RenderReceiver) and
+    /// [FrameObservers](crate::FrameObserver) still see the whole state.
+    fn filter_state_for_player(state: &Self::State, _player_indices: &[usize]) -> Self::State {
+        return state.clone();
+    }
+
+    /// Interpolates between two states.  `previous_result` is the result of the
+    /// previous call, if there was one, which can be used to smooth out
+    /// corrections when [InterpolationArg::has_rolled_back] is true.
     fn interpolate(
         initial_information: &InitialInformation<Self>,
+        previous_result: Option<&Self::InterpolationResult>,
         first: &Self::State,
         second: &Self::State,
         arg: &InterpolationArg,
     ) -> Self::InterpolationResult;
-
-    fn new_input_event_handler() -> Self::ClientInputEventHandler;
-
-    fn handle_input_event(
-        input_event_handler: &mut Self::ClientInputEventHandler,
-        input_event: Self::ClientInputEvent,
-    );
-
-    fn get_input(input_event_handler: &mut Self::ClientInputEventHandler) -> Self::ClientInput;
 }
@@ -1,4 +1,7 @@
-use crate::interface::GameTrait;
+use crate::interface::{
+    GameTrait,
+    SessionToken,
+};
 use crate::server::ServerConfig;
 use serde::{
     Deserialize,
@@ -9,7 +12,8 @@ use serde::{
 pub struct InitialInformation<Game: GameTrait> {
     server_config: ServerConfig,
     player_count: usize,
-    player_index: usize,
+    local_player_indices: Vec<usize>,
+    session_token: Option<SessionToken>,
     state: Game::State,
 }
 
@@ -17,13 +21,14 @@ impl<Game: GameTrait> InitialInformation<Game> {
     pub fn new(
         server_config: ServerConfig,
         player_count: usize,
-        player_index: usize,
+        local_player_indices: Vec<usize>,
         state: Game::State,
     ) -> Self {
         return Self {
             server_config,
             player_count,
-            player_index,
+            local_player_indices,
+            session_token: None,
             state,
         };
     }
@@ -40,8 +45,31 @@ impl<Game: GameTrait> InitialInformation<Game> {
         self.player_count
     }
 
+    /// Returns the player index of the first local player, or `usize::MAX`
+    /// if there is no local player
     pub fn get_player_index(&self) -> usize {
-        self.player_index
+        match self.local_player_indices.first() {
+            Some(player_index) => *player_index,
+            None => usize::MAX,
+        }
+    }
+
+    /// Returns the player indices of the players in this process, in the
+    /// order of their input handlers
+    pub fn get_local_player_indices(&self) -> &[usize] {
+        &self.local_player_indices
+    }
+
+    /// Returns the [SessionToken] the server gave this client, which it can
+    /// present to rejoin the match after the server
+    /// [resumes](crate::Server::resume) it.  The server's own
+    /// [InitialInformation] has none.
+    pub fn get_session_token(&self) -> Option<SessionToken> {
+        self.session_token
+    }
+
+    pub(crate) fn set_session_token(&mut self, session_token: SessionToken) {
+        self.session_token = Some(session_token);
     }
 
     pub fn get_server_config(&self) -> &ServerConfig {
@@ -58,7 +86,8 @@ impl<Game: GameTrait> Clone for InitialInformation<Game> {
         Self {
             server_config: self.server_config.clone(),
             player_count: self.player_count,
-            player_index: self.player_index,
+            local_player_indices: self.local_player_indices.clone(),
+            session_token: self.session_token,
             state: self.state.clone(),
         }
     }
@@ -0,0 +1,19 @@
+use crate::interface::GameTrait;
+use crate::FrameIndex;
+
+/// A trait that turns [input events](GameTrait::ClientInputEvent) into
+/// [inputs](GameTrait::ClientInput) for a [Client](crate::Client).
+///
+/// Input handling is kept separate from [GameTrait] so that different kinds of
+/// clients, such as human players, bots or replays, can provide inputs for the
+/// same game in different ways.
+pub trait InputEventHandler: 'static + Send {
+    type Game: GameTrait;
+
+    /// Called for each input event sent to the [Client](crate::Client)
+    fn handle_input_event(&mut self, input_event: <Self::Game as GameTrait>::ClientInputEvent);
+
+    /// Samples the input for the frame at `frame_index`.  This is called exactly
+    /// once for each [FrameIndex] the client advances to.
+    fn get_input(&mut self, frame_index: FrameIndex) -> <Self::Game as GameTrait>::ClientInput;
+}
@@ -0,0 +1,16 @@
+use crate::interface::GameTrait;
+
+/// The result of [GameTrait::validate_input]
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputValidation<Game: GameTrait> {
+    /// The input is used as it was received
+    Accept,
+
+    /// The input is replaced by a sanitized one, such as a velocity clamped to
+    /// the maximum speed
+    Clamp(Game::ClientInput),
+
+    /// The input is dropped.  If no valid input arrives for the frame, the
+    /// player's input is declared authoritatively missing and predicted.
+    Reject,
+}
@@ -3,13 +3,25 @@ use commons::time::TimeDuration;
 pub struct InterpolationArg {
     weight: f64,
     duration_since_game_start: TimeDuration,
+    is_first_state_authoritative: bool,
+    is_second_state_authoritative: bool,
+    has_rolled_back: bool,
 }
 
 impl InterpolationArg {
-    pub fn new(weight: f64, duration_since_game_start: TimeDuration) -> Self {
+    pub fn new(
+        weight: f64,
+        duration_since_game_start: TimeDuration,
+        is_first_state_authoritative: bool,
+        is_second_state_authoritative: bool,
+        has_rolled_back: bool,
+    ) -> Self {
         return Self {
             weight,
             duration_since_game_start,
+            is_first_state_authoritative,
+            is_second_state_authoritative,
+            has_rolled_back,
         };
     }
 
@@ -20,4 +32,24 @@ impl InterpolationArg {
     pub fn get_duration_since_game_start(&self) -> TimeDuration {
         return self.duration_since_game_start;
     }
+
+    /// Returns true if the first state is authoritative, false if it was
+    /// predicted from non-authoritative inputs
+    pub fn is_first_state_authoritative(&self) -> bool {
+        return self.is_first_state_authoritative;
+    }
+
+    /// Returns true if the second state is authoritative, false if it was
+    /// predicted from non-authoritative inputs
+    pub fn is_second_state_authoritative(&self) -> bool {
+        return self.is_second_state_authoritative;
+    }
+
+    /// Returns true if any buffered state was replaced by a different state
+    /// since the last call to [interpolate](crate::GameTrait::interpolate).
+    /// When this is true, the previous interpolation result was based on a
+    /// misprediction.
+    pub fn has_rolled_back(&self) -> bool {
+        return self.has_rolled_back;
+    }
 }
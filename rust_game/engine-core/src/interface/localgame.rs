@@ -0,0 +1,97 @@
+use crate::interface::{
+    EngineError,
+    FrameObserver,
+    InputEventHandler,
+    RenderReceiver,
+};
+use crate::local::{
+    LocalGameCore,
+    LocalGameCoreEvent,
+};
+use crate::GameTrait;
+use commons::real_time::{
+    EventHandlerBuilder,
+    EventSender,
+    Factory,
+};
+
+/// Runs a single player game in-process, without a [Server](crate::Server)
+/// or any sockets.
+///
+/// The local player's inputs are authoritative as soon as they are read from
+/// the [InputEventHandler], so there is no prediction or rollback.
+pub struct LocalGame<Game: GameTrait> {
+    core_sender: EventSender<LocalGameCoreEvent<Game>>,
+}
+
+impl<Game: GameTrait> LocalGame<Game> {
+    /// Creates a [LocalGame].  The game runs once [start_game](Self::start_game)
+    /// is called.
+    pub fn new<InputHandler: InputEventHandler<Game = Game>>(
+        factory: Factory,
+        input_event_handler: InputHandler,
+    ) -> Result<(Self, RenderReceiver<Game>), EngineError> {
+        let core_thread_builder =
+            EventHandlerBuilder::<LocalGameCore<Game, InputHandler>>::new(&factory);
+
+        let (render_receiver_sender, render_receiver) = RenderReceiver::<Game>::new(&factory);
+
+        let core_sender = core_thread_builder.get_sender().clone();
+
+        core_thread_builder
+            .spawn_thread(
+                "LocalGameCore".to_string(),
+                LocalGameCore::<Game, InputHandler>::new(
+                    factory,
+                    core_sender.clone(),
+                    render_receiver_sender,
+                    input_event_handler,
+                ),
+            )
+            .map_err(EngineError::Io)?;
+
+        return Ok((Self { core_sender }, render_receiver));
+    }
+
+    pub fn start_game(&self) -> Result<(), EngineError> {
+        self.core_sender
+            .send_event(LocalGameCoreEvent::StartGame)
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Sends a [server input](GameTrait::ServerInput) to the game.  The input is
+    /// applied to the next frame.
+    pub fn send_server_input(&self, server_input: Game::ServerInput) -> Result<(), EngineError> {
+        self.core_sender
+            .send_event(LocalGameCoreEvent::ServerInput(server_input))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Registers a [FrameObserver] that is notified of the frames computed by
+    /// the game.  Observers added after the game has started miss the frames
+    /// computed before they were added.
+    pub fn add_frame_observer(
+        &self,
+        frame_observer: impl FrameObserver<Game>,
+    ) -> Result<(), EngineError> {
+        self.core_sender
+            .send_event(LocalGameCoreEvent::AddFrameObserver(Box::new(
+                frame_observer,
+            )))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn send_client_input_event(
+        &self,
+        client_input_event: Game::ClientInputEvent,
+    ) -> Result<(), Game::ClientInputEvent> {
+        return match self
+            .core_sender
+            .send_event(LocalGameCoreEvent::OnInputEvent(client_input_event))
+        {
+            Ok(()) => Ok(()),
+            Err(LocalGameCoreEvent::OnInputEvent(client_input_event)) => Err(client_input_event),
+            _ => panic!("This should never happen."),
+        };
+    }
+}
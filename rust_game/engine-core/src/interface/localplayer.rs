@@ -0,0 +1,23 @@
+use crate::server::ServerCore;
+use crate::GameTrait;
+
+/// A handle for the player hosting a [Server](crate::Server) in the same
+/// process.  Input events sent through it go straight to the server without
+/// passing through any sockets.
+pub struct LocalPlayer<Game: GameTrait> {
+    server_core: ServerCore<Game>,
+}
+
+impl<Game: GameTrait> LocalPlayer<Game> {
+    pub(crate) fn new(server_core: ServerCore<Game>) -> Self {
+        return Self { server_core };
+    }
+
+    pub fn send_client_input_event(
+        &self,
+        client_input_event: Game::ClientInputEvent,
+    ) -> Result<(), Game::ClientInputEvent> {
+        self.server_core
+            .handle_local_input_event(client_input_event)
+    }
+}
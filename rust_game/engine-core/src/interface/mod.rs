@@ -1,16 +1,61 @@
 mod client;
+mod connectconfig;
+mod connectionstate;
+mod deterministicrng;
+mod discoveredserver;
+mod engineerror;
+mod entityid;
+mod frameobserver;
 mod game;
 mod initialinformation;
+mod inputeventhandler;
+mod inputvalidation;
 mod interpolationarg;
+mod localgame;
+mod localplayer;
+mod ratelimits;
+mod renderconfig;
+mod renderdiagnostics;
 mod renderreceiver;
+mod roomserver;
 mod server;
+mod serverdiscovery;
+mod serversnapshot;
+mod sessiontoken;
+mod standbyserver;
 mod updatearg;
 
 pub use self::client::Client;
+pub use self::connectconfig::ConnectConfig;
+pub use self::connectionstate::ConnectionState;
+pub use self::deterministicrng::DeterministicRng;
+pub use self::discoveredserver::DiscoveredServer;
+pub use self::discoveredserver::ServerState;
+pub use self::engineerror::EngineError;
+pub use self::entityid::EntityId;
+pub use self::frameobserver::FrameObserver;
 pub use self::game::GameTrait;
 pub use self::initialinformation::InitialInformation;
+pub use self::inputeventhandler::InputEventHandler;
+pub use self::inputvalidation::InputValidation;
 pub use self::interpolationarg::InterpolationArg;
+pub use self::localgame::LocalGame;
+pub use self::localplayer::LocalPlayer;
+pub use self::ratelimits::RateLimits;
+pub use self::renderconfig::ExtrapolationPolicy;
+pub use self::renderconfig::RenderDelay;
+pub use self::renderdiagnostics::RenderDiagnostics;
+pub use self::renderdiagnostics::RenderStatus;
 pub use self::renderreceiver::RenderReceiver;
 pub use self::renderreceiver::RenderReceiverMessage;
+pub use self::roomserver::RoomServer;
 pub use self::server::Server;
+pub use self::serverdiscovery::ServerDiscovery;
+pub(crate) use self::serversnapshot::SavedClient;
+pub use self::serversnapshot::ServerSnapshot;
+pub use self::sessiontoken::SessionToken;
+pub use self::standbyserver::StandbyServer;
 pub use self::updatearg::UpdateArg;
+
+#[cfg(test)]
+mod tests;
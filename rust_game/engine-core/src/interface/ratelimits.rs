@@ -0,0 +1,72 @@
+use commons::real_time::net::MAX_UDP_DATAGRAM_SIZE;
+
+/// Budgets for the UDP traffic a [Server](crate::Server) accepts from its
+/// clients.  Each budget is measured over one frame of
+/// [GameTrait::STEP_PERIOD](crate::GameTrait::STEP_PERIOD).
+///
+/// Datagrams over a source address's packet or byte budget are dropped before
+/// they are deserialized.  Inputs over a player's input budget are dropped
+/// before they reach the game.  Dropped datagrams and inputs are counted and
+/// reported to [FrameObserver::on_traffic_dropped](crate::FrameObserver::on_traffic_dropped),
+/// and a player whose inputs are dropped more than the kick threshold is
+/// disconnected.
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimits {
+    max_packets_per_frame: usize,
+    max_bytes_per_frame: usize,
+    max_inputs_per_frame: usize,
+    kick_threshold: Option<usize>,
+}
+
+impl RateLimits {
+    /// The maximum number of datagrams accepted from one source address in a
+    /// frame
+    pub fn get_max_packets_per_frame(&self) -> usize {
+        return self.max_packets_per_frame;
+    }
+
+    pub fn set_max_packets_per_frame(&mut self, max_packets_per_frame: usize) {
+        self.max_packets_per_frame = max_packets_per_frame;
+    }
+
+    /// The maximum number of bytes accepted from one source address in a frame
+    pub fn get_max_bytes_per_frame(&self) -> usize {
+        return self.max_bytes_per_frame;
+    }
+
+    pub fn set_max_bytes_per_frame(&mut self, max_bytes_per_frame: usize) {
+        self.max_bytes_per_frame = max_bytes_per_frame;
+    }
+
+    /// The maximum number of inputs accepted from one player in a frame
+    pub fn get_max_inputs_per_frame(&self) -> usize {
+        return self.max_inputs_per_frame;
+    }
+
+    pub fn set_max_inputs_per_frame(&mut self, max_inputs_per_frame: usize) {
+        self.max_inputs_per_frame = max_inputs_per_frame;
+    }
+
+    /// The number of dropped inputs after which a player is disconnected.
+    /// Players are never disconnected if this is None.
+    pub fn get_kick_threshold(&self) -> Option<usize> {
+        return self.kick_threshold;
+    }
+
+    pub fn set_kick_threshold(&mut self, kick_threshold: Option<usize>) {
+        self.kick_threshold = kick_threshold;
+    }
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        // Clients send one input per frame and an occasional ping, so these
+        // leave plenty of room for fragmented inputs and network jitter
+        return Self {
+            max_packets_per_frame: 32,
+            max_bytes_per_frame: 32 * MAX_UDP_DATAGRAM_SIZE,
+            max_inputs_per_frame: 8,
+            kick_threshold: None,
+        };
+    }
+}
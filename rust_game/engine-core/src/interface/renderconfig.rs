@@ -0,0 +1,53 @@
+use crate::game_time::FrameDuration;
+use commons::time::TimeDuration;
+
+/// How far behind the current time the [RenderReceiver](super::RenderReceiver)
+/// renders.  Rendering slightly in the past gives the interpolation buffer time
+/// to receive the frame after the one being displayed.
+#[derive(Clone, Copy, Debug)]
+pub enum RenderDelay {
+    /// A delay expressed as a (possibly fractional) number of frames
+    Frames(f64),
+
+    /// A delay expressed as a fixed amount of time
+    Duration(TimeDuration),
+}
+
+impl RenderDelay {
+    /// Converts the delay into a [TimeDuration] using the game's [FrameDuration]
+    pub fn get_duration(&self, frame_duration: &FrameDuration) -> TimeDuration {
+        match self {
+            RenderDelay::Frames(frames) => frame_duration.get_frame_duration().mul_f64(*frames),
+            RenderDelay::Duration(duration) => *duration,
+        }
+    }
+
+    /// Converts the delay into a fractional number of frames using the game's
+    /// [FrameDuration]
+    pub fn get_frame_count(&self, frame_duration: &FrameDuration) -> f64 {
+        match self {
+            RenderDelay::Frames(frames) => *frames,
+            RenderDelay::Duration(duration) => frame_duration.to_frame_count(duration),
+        }
+    }
+}
+
+impl Default for RenderDelay {
+    fn default() -> Self {
+        RenderDelay::Frames(0.0)
+    }
+}
+
+/// What the [RenderReceiver](super::RenderReceiver) does when the render time
+/// has moved past the newest frame in its buffer.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum ExtrapolationPolicy {
+    /// Keep rendering the newest frame until the next one arrives
+    #[default]
+    Hold,
+
+    /// Continue past the newest frame using the trend of the last two frames,
+    /// but for no more than `max_frames` frames.  After that, the newest frame
+    /// is held.
+    Extrapolate { max_frames: f64 },
+}
@@ -0,0 +1,109 @@
+use crate::FrameIndex;
+
+/// Describes how the most recently rendered frame was produced
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderStatus {
+    /// The render time fell between two buffered frames
+    Interpolating,
+
+    /// The render time was past the newest buffered frame and the result was
+    /// extrapolated from the two newest frames
+    Extrapolating,
+
+    /// The render time was past the newest buffered frame and the newest frame
+    /// was held
+    Holding,
+
+    /// The render time was before the oldest buffered frame and the oldest
+    /// frame was held
+    Behind,
+}
+
+/// Per-frame diagnostics describing the state of the
+/// [RenderReceiver's](super::RenderReceiver) interpolation buffer
+#[derive(Clone, Debug)]
+pub struct RenderDiagnostics {
+    render_frame: f64,
+    from_frame_index: FrameIndex,
+    to_frame_index: FrameIndex,
+    weight: f64,
+    buffered_frames: usize,
+    latest_frame_index: Option<FrameIndex>,
+    status: RenderStatus,
+    underrun_count: usize,
+}
+
+impl RenderDiagnostics {
+    pub(super) fn new(
+        render_frame: f64,
+        from_frame_index: FrameIndex,
+        to_frame_index: FrameIndex,
+        weight: f64,
+        buffered_frames: usize,
+        latest_frame_index: Option<FrameIndex>,
+        status: RenderStatus,
+        underrun_count: usize,
+    ) -> Self {
+        return Self {
+            render_frame,
+            from_frame_index,
+            to_frame_index,
+            weight,
+            buffered_frames,
+            latest_frame_index,
+            status,
+            underrun_count,
+        };
+    }
+
+    /// The fractional [FrameIndex] that was rendered, after the render delay
+    /// was applied
+    pub fn get_render_frame(&self) -> f64 {
+        return self.render_frame;
+    }
+
+    /// The [FrameIndex] of the first state passed to
+    /// [interpolate](crate::GameTrait::interpolate)
+    pub fn get_from_frame_index(&self) -> FrameIndex {
+        return self.from_frame_index;
+    }
+
+    /// The [FrameIndex] of the second state passed to
+    /// [interpolate](crate::GameTrait::interpolate)
+    pub fn get_to_frame_index(&self) -> FrameIndex {
+        return self.to_frame_index;
+    }
+
+    pub fn get_weight(&self) -> f64 {
+        return self.weight;
+    }
+
+    /// The number of states held in the interpolation buffer
+    pub fn get_buffered_frames(&self) -> usize {
+        return self.buffered_frames;
+    }
+
+    /// The current [FrameIndex] of the game, if it is known
+    pub fn get_latest_frame_index(&self) -> Option<FrameIndex> {
+        return self.latest_frame_index;
+    }
+
+    pub fn get_status(&self) -> RenderStatus {
+        return self.status;
+    }
+
+    /// Returns true if the buffer did not hold a frame after the render time
+    pub fn is_underrun(&self) -> bool {
+        return match self.status {
+            RenderStatus::Extrapolating | RenderStatus::Holding => true,
+            RenderStatus::Interpolating | RenderStatus::Behind => false,
+        };
+    }
+
+    /// The total number of underruns since the [RenderReceiver](super::RenderReceiver)
+    /// was created.  An underrun starts on a frame rendered from an underrun
+    /// buffer after one that was not, and lasts until the buffer refills.
+    pub fn get_underrun_count(&self) -> usize {
+        return self.underrun_count;
+    }
+}
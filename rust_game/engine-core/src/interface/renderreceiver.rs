@@ -5,9 +5,13 @@ use crate::game_time::{
     StartTime,
 };
 use crate::interface::{
+    ExtrapolationPolicy,
     GameTrait,
     InitialInformation,
     InterpolationArg,
+    RenderDelay,
+    RenderDiagnostics,
+    RenderStatus,
 };
 use crate::messaging::FrameIndexAndState;
 use commons::real_time::{
@@ -21,11 +25,15 @@ use log::{
     info,
     warn,
 };
+use std::collections::VecDeque;
 
 pub enum RenderReceiverMessage<Game: GameTrait> {
     InitialInformation(InitialInformation<Game>),
     //TODO: rename
-    StepMessage(FrameIndexAndState<Game>),
+    StepMessage {
+        is_authoritative: bool,
+        state_message: FrameIndexAndState<Game>,
+    },
     //TODO: rename and document these
     StartTime(StartTime),
     FrameIndex(FrameIndex),
@@ -42,10 +50,27 @@ pub struct RenderReceiver<Game: GameTrait> {
 struct Data<Game: GameTrait> {
     time_source: TimeSource,
     start_time: Option<StartTime>,
-    //TODO: use vec deque so that this is more efficient
-    step_queue: Vec<FrameIndexAndState<Game>>,
+    //New states at the back, old at the front (index 0)
+    step_queue: VecDeque<BufferedStep<Game>>,
     latest_frame_index: Option<FrameIndex>,
     initial_information: Option<InitialInformation<Game>>,
+    render_delay: RenderDelay,
+    extrapolation_policy: ExtrapolationPolicy,
+    render_diagnostics: Option<RenderDiagnostics>,
+    underrun_count: usize,
+    previous_interpolation_result: Option<Game::InterpolationResult>,
+    has_rolled_back: bool,
+}
+
+struct BufferedStep<Game: GameTrait> {
+    is_authoritative: bool,
+    state_message: FrameIndexAndState<Game>,
+}
+
+impl<Game: GameTrait> BufferedStep<Game> {
+    fn get_frame_index(&self) -> FrameIndex {
+        return self.state_message.get_frame_index();
+    }
 }
 
 impl<Game: GameTrait> RenderReceiver<Game> {
@@ -55,9 +80,15 @@ impl<Game: GameTrait> RenderReceiver<Game> {
         let data = Data::<Game> {
             time_source: factory.get_time_source().clone(),
             start_time: None,
-            step_queue: Vec::new(),
+            step_queue: VecDeque::new(),
             latest_frame_index: None,
             initial_information: None,
+            render_delay: RenderDelay::default(),
+            extrapolation_policy: ExtrapolationPolicy::default(),
+            render_diagnostics: None,
+            underrun_count: 0,
+            previous_interpolation_result: None,
+            has_rolled_back: false,
         };
 
         let render_receiver = Self {
@@ -78,9 +109,10 @@ impl<Game: GameTrait> RenderReceiver<Game> {
                     self.data.on_initial_information(initial_information)
                 }
 
-                Ok(RenderReceiverMessage::StepMessage(step_message)) => {
-                    self.data.on_step_message(step_message)
-                }
+                Ok(RenderReceiverMessage::StepMessage {
+                    is_authoritative,
+                    state_message,
+                }) => self.data.on_step_message(is_authoritative, state_message),
 
                 Ok(RenderReceiverMessage::StartTime(start_time)) => {
                     self.data.on_start_time(start_time)
@@ -111,7 +143,7 @@ impl<Game: GameTrait> RenderReceiver<Game> {
         }
 
         let start_time = match &self.data.start_time {
-            Some(start_time) => start_time,
+            Some(start_time) => *start_time,
             None => return None,
         };
 
@@ -120,119 +152,175 @@ impl<Game: GameTrait> RenderReceiver<Game> {
             None => return None,
         };
 
-        let now = self.time_source.now();
-        //let latest_time_message = self.data.latest_time_message.as_ref().unwrap();
-        //let mut duration_since_start = latest_time_message.get_duration_since_start(now);
-        //used to be floor
-        //let now_as_fractional_step_index = latest_time_message.get_step_from_actual_time(now);
-
-        //TODO: should probably be interpolating between the current frame (nearest past), to next (nearest future)
-        let desired_first_step_index = start_time.get_frame_index(
-            initial_information.get_server_config().get_frame_duration(),
-            &now,
-        );
-
-        let first_step = &self.data.step_queue[self.data.step_queue.len() - 1];
-        let second_step = if self.data.step_queue.len() >= 2 {
-            &self.data.step_queue[self.data.step_queue.len() - 2]
-        } else {
-            first_step
-        };
-
-        if first_step.get_frame_index() + 1 != second_step.get_frame_index() {
-            warn!(
-                "Interpolating from non-sequential steps: {:?}, {:?}",
-                first_step.get_frame_index(),
-                second_step.get_frame_index()
-            );
-        }
-
-        if (desired_first_step_index.usize() as i64 - first_step.get_frame_index().usize() as i64)
-            .abs()
-            > 1
-        {
-            warn!(
-                "Needed step: {:?}, Gotten step: {:?}",
-                desired_first_step_index,
-                first_step.get_frame_index()
-            );
-        }
+        let frame_duration = *initial_information.get_server_config().get_frame_duration();
 
-        let mut weight = if second_step.get_frame_index() == first_step.get_frame_index() {
-            1 as f64
-        } else {
-            let fractional_frame_index = start_time.get_fractional_frame_index(
-                initial_information.get_server_config().get_frame_duration(),
-                &now,
-            );
-            (fractional_frame_index - first_step.get_frame_index().usize() as f64)
-                / ((second_step.get_frame_index().usize() - first_step.get_frame_index().usize())
-                    as f64)
-        };
+        // Render in the past by the render delay so that the buffer has a
+        // chance to hold the frame after the one being displayed
+        let now = self.time_source.now();
+        let render_time = now - &self.data.render_delay.get_duration(&frame_duration);
+        let render_frame = start_time.get_fractional_frame_index(&frame_duration, &render_time);
 
-        let interpolate = true;
-        if !interpolate {
-            weight = 1 as f64;
-        }
+        // Position of the nearest future frame, which is also one past the
+        // position of the nearest past frame
+        let future_position = self
+            .data
+            .step_queue
+            .partition_point(|step| step.get_frame_index().usize() as f64 <= render_frame);
+
+        let (from_position, to_position, weight, status) =
+            if future_position == self.data.step_queue.len() {
+                let past_position = future_position - 1;
+
+                match self.data.extrapolation_policy {
+                    ExtrapolationPolicy::Extrapolate { max_frames } if past_position > 0 => {
+                        let from_position = past_position - 1;
+                        let from = self.data.step_queue[from_position]
+                            .get_frame_index()
+                            .usize();
+                        let to = self.data.step_queue[past_position]
+                            .get_frame_index()
+                            .usize();
+                        let clamped_render_frame = render_frame.min(to as f64 + max_frames);
+                        let weight = (clamped_render_frame - from as f64) / ((to - from) as f64);
+                        (
+                            from_position,
+                            past_position,
+                            weight,
+                            RenderStatus::Extrapolating,
+                        )
+                    }
+                    _ => (past_position, past_position, 1.0, RenderStatus::Holding),
+                }
+            } else if future_position == 0 {
+                (0, 0, 1.0, RenderStatus::Behind)
+            } else {
+                let from_position = future_position - 1;
+                let from = self.data.step_queue[from_position]
+                    .get_frame_index()
+                    .usize();
+                let to = self.data.step_queue[future_position]
+                    .get_frame_index()
+                    .usize();
+                let weight = (render_frame - from as f64) / ((to - from) as f64);
+                (
+                    from_position,
+                    future_position,
+                    weight,
+                    RenderStatus::Interpolating,
+                )
+            };
+
+        self.data
+            .on_render(render_frame, from_position, to_position, weight, status);
 
         //TODO: this duration since start thing seems strange
         // The render receiver should probably just expose the start time
-        let duration_since_start = now.duration_since(start_time.get_time_value());
+        let duration_since_start =
+            start_time.get_duration_since_game_start(&frame_duration, &render_time);
+
+        let from_step = &self.data.step_queue[from_position];
+        let to_step = &self.data.step_queue[to_position];
+
+        let arg = InterpolationArg::new(
+            weight,
+            duration_since_start,
+            from_step.is_authoritative,
+            to_step.is_authoritative,
+            self.data.has_rolled_back,
+        );
 
-        let arg = InterpolationArg::new(weight, duration_since_start);
         let interpolation_result = Game::interpolate(
             self.data.initial_information.as_ref().unwrap(),
-            first_step.get_state(),
-            second_step.get_state(),
+            self.data.previous_interpolation_result.as_ref(),
+            from_step.state_message.get_state(),
+            to_step.state_message.get_state(),
             &arg,
         );
 
+        self.data.previous_interpolation_result = Some(interpolation_result.clone());
+        self.data.has_rolled_back = false;
+
+        // Keep the frame before the one rendered from so that there are always
+        // two frames available to extrapolate from
+        self.data
+            .drop_steps_before_position(from_position.saturating_sub(1));
+
         return Some((duration_since_start, interpolation_result));
     }
 
+    /// Sets how far in the past the [RenderReceiver] renders
+    pub fn set_render_delay(&mut self, render_delay: RenderDelay) {
+        self.data.render_delay = render_delay;
+    }
+
+    /// Sets what the [RenderReceiver] does when it runs out of future frames
+    pub fn set_extrapolation_policy(&mut self, extrapolation_policy: ExtrapolationPolicy) {
+        self.data.extrapolation_policy = extrapolation_policy;
+    }
+
+    /// Returns diagnostics for the most recently rendered frame
+    pub fn get_render_diagnostics(&self) -> &Option<RenderDiagnostics> {
+        return &self.data.render_diagnostics;
+    }
+
     pub fn get_initial_information(&self) -> &Option<InitialInformation<Game>> {
         return &self.data.initial_information;
     }
+
+    /// Returns the player index of each local player, such as for rendering
+    /// one viewport per player.  It is empty until the game starts.
+    pub fn get_local_player_indices(&self) -> &[usize] {
+        return match &self.data.initial_information {
+            Some(initial_information) => initial_information.get_local_player_indices(),
+            None => &[],
+        };
+    }
 }
 
 impl<Game: GameTrait> Data<Game> {
-    fn drop_steps_before(&mut self, drop_before: FrameIndex) {
-        while self.step_queue.len() > 2
-            && self.step_queue[self.step_queue.len() - 1].get_frame_index() < drop_before
-        {
-            let _dropped = self.step_queue.pop().unwrap();
-            //info!("Dropped step: {:?}", dropped.get_step_index());
-        }
+    fn drop_steps_before_position(&mut self, position: usize) {
+        self.step_queue.drain(..position);
     }
 
     fn on_initial_information(&mut self, initial_information: InitialInformation<Game>) {
         self.initial_information = Some(initial_information);
     }
 
-    fn on_step_message(&mut self, state_message: FrameIndexAndState<Game>) {
-        if !self.step_queue.is_empty()
-            && self.step_queue[0].get_frame_index() + 1 < state_message.get_frame_index()
-        {
-            warn!(
-                "Received steps out of order.  Waiting for {:?} but got {:?}.",
-                self.step_queue[0].get_frame_index() + 1,
-                state_message.get_frame_index()
-            );
+    fn on_step_message(&mut self, is_authoritative: bool, state_message: FrameIndexAndState<Game>) {
+        if let Some(newest) = self.step_queue.back() {
+            if newest.get_frame_index() + 1 < state_message.get_frame_index() {
+                warn!(
+                    "Received steps out of order.  Waiting for {:?} but got {:?}.",
+                    newest.get_frame_index() + 1,
+                    state_message.get_frame_index()
+                );
+            }
         }
 
-        //info!("StepMessage: {:?}", step_message.get_step_index());
-        //insert in reverse sorted order
+        let buffered_step = BufferedStep {
+            is_authoritative,
+            state_message,
+        };
+
+        //insert in sorted order
         match self
             .step_queue
-            .binary_search_by(|elem| state_message.cmp(elem))
-        {
-            Ok(pos) => self.step_queue[pos] = state_message,
-            Err(pos) => self.step_queue.insert(pos, state_message),
-        }
+            .binary_search_by_key(&buffered_step.get_frame_index(), |step| {
+                step.get_frame_index()
+            }) {
+            Ok(pos) => {
+                // A state that has already been buffered was recomputed or
+                // replaced by an authoritative one.  It only rolled back if
+                // the prediction it was based on was wrong.
+                if self.step_queue[pos].state_message.get_state()
+                    != buffered_step.state_message.get_state()
+                {
+                    self.has_rolled_back = true;
+                }
 
-        //TODO: this while block seems strange
-        if let Some(latest_frame_index) = self.latest_frame_index {
-            self.drop_steps_before(latest_frame_index);
+                self.step_queue[pos] = buffered_step;
+            }
+            Err(pos) => self.step_queue.insert(pos, buffered_step),
         }
     }
 
@@ -242,6 +330,70 @@ impl<Game: GameTrait> Data<Game> {
 
     fn on_frame_index(&mut self, frame_index: FrameIndex) {
         self.latest_frame_index = Some(frame_index);
-        self.drop_steps_before(frame_index);
+
+        // Bound the buffer even if nothing is rendering.  Frames older than the
+        // render delay, plus the two frames needed to extrapolate, will never
+        // be rendered again.
+        let frame_duration = match &self.initial_information {
+            Some(initial_information) => {
+                *initial_information.get_server_config().get_frame_duration()
+            }
+            None => return,
+        };
+
+        let frames_to_keep = self.render_delay.get_frame_count(&frame_duration).ceil() as usize + 2;
+
+        if frame_index.usize() > frames_to_keep {
+            let drop_before = frame_index - frames_to_keep;
+            let position = self
+                .step_queue
+                .partition_point(|step| step.get_frame_index() < drop_before);
+            self.drop_steps_before_position(position);
+        }
+    }
+
+    fn on_render(
+        &mut self,
+        render_frame: f64,
+        from_position: usize,
+        to_position: usize,
+        weight: f64,
+        status: RenderStatus,
+    ) {
+        let from_frame_index = self.step_queue[from_position].get_frame_index();
+        let to_frame_index = self.step_queue[to_position].get_frame_index();
+
+        let is_underrun = match status {
+            RenderStatus::Extrapolating | RenderStatus::Holding => true,
+            RenderStatus::Interpolating | RenderStatus::Behind => false,
+        };
+
+        if is_underrun {
+            let was_underrun = match &self.render_diagnostics {
+                Some(render_diagnostics) => render_diagnostics.is_underrun(),
+                None => false,
+            };
+
+            // Consecutive underrun frames are counted as one underrun
+            if !was_underrun {
+                warn!(
+                    "Render buffer underrun.  Render frame: {:?}, Newest frame: {:?}",
+                    render_frame, to_frame_index
+                );
+
+                self.underrun_count = self.underrun_count + 1;
+            }
+        }
+
+        self.render_diagnostics = Some(RenderDiagnostics::new(
+            render_frame,
+            from_frame_index,
+            to_frame_index,
+            weight,
+            self.step_queue.len(),
+            self.latest_frame_index,
+            status,
+            self.underrun_count,
+        ));
     }
 }
@@ -0,0 +1,107 @@
+use crate::server::{
+    RoomId,
+    RoomManager,
+    RoomUdpInput,
+};
+use crate::{
+    EngineError,
+    GameTrait,
+    RateLimits,
+    Server,
+};
+use commons::real_time::net::tcp::TcpListenerBuilder;
+use commons::real_time::net::udp::UdpSocket;
+use commons::real_time::{
+    EventHandlerStopper,
+    Factory,
+};
+use std::net::{
+    Ipv4Addr,
+    SocketAddr,
+    SocketAddrV4,
+};
+
+/// A [RoomServer] hosts many independent games, called rooms, behind a single
+/// TCP listener on [GameTrait::TCP_PORT] and a single UDP socket on
+/// [GameTrait::UDP_PORT].
+///
+/// Each room is controlled through its own [Server].  Clients pick a room with
+/// [Client::join_room](crate::Client::join_room), or are assigned to the open
+/// room with the lowest [RoomId] by [Client::new](crate::Client::new).  A room
+/// stops accepting clients once its game starts.
+pub struct RoomServer<Game: GameTrait> {
+    factory: Factory,
+    room_manager: RoomManager<Game>,
+    udp_socket: UdpSocket,
+    next_room_id: RoomId,
+    _tcp_listener_stopper: EventHandlerStopper,
+    _udp_input: RoomUdpInput,
+}
+
+impl<Game: GameTrait> RoomServer<Game> {
+    /// Creates a [RoomServer] that listens on the loopback address, so only
+    /// clients on the same machine can join
+    pub fn new(factory: Factory) -> Result<Self, EngineError> {
+        return Self::new_with_ip_addr(factory, Ipv4Addr::LOCALHOST);
+    }
+
+    /// Creates a [RoomServer] that listens on `ip_addr`.  Use
+    /// [Ipv4Addr::UNSPECIFIED] to accept clients from other machines.
+    pub fn new_with_ip_addr(factory: Factory, ip_addr: Ipv4Addr) -> Result<Self, EngineError> {
+        return Self::new_with_rate_limits(factory, ip_addr, RateLimits::default());
+    }
+
+    /// Creates a [RoomServer] listening on `ip_addr` that drops datagrams over
+    /// the packet and byte budgets of `rate_limits`.  The input budgets of each
+    /// room are set with [Server::set_rate_limits].
+    pub fn new_with_rate_limits(
+        factory: Factory,
+        ip_addr: Ipv4Addr,
+        rate_limits: RateLimits,
+    ) -> Result<Self, EngineError> {
+        let room_manager = RoomManager::<Game>::new(&factory).map_err(EngineError::Io)?;
+
+        let udp_socket = factory
+            .bind_udp_socket(SocketAddr::V4(SocketAddrV4::new(ip_addr, Game::UDP_PORT)))
+            .map_err(EngineError::Bind)?;
+
+        let udp_input = RoomUdpInput::new(&factory, &udp_socket, room_manager.clone(), rate_limits)
+            .map_err(EngineError::Io)?;
+
+        let tcp_listener_stopper = TcpListenerBuilder::new_thread(
+            &factory,
+            "RoomServerTcpListener".to_string(),
+            SocketAddr::V4(SocketAddrV4::new(ip_addr, Game::TCP_PORT)),
+            room_manager.clone(),
+        )
+        .map_err(EngineError::Bind)?;
+
+        return Ok(Self {
+            factory,
+            room_manager,
+            udp_socket,
+            next_room_id: RoomId::default(),
+            _tcp_listener_stopper: tcp_listener_stopper,
+            _udp_input: udp_input,
+        });
+    }
+
+    /// Creates a new room that clients can join.  The returned [Server]
+    /// controls the room's game.
+    pub fn create_room(&mut self) -> Result<(RoomId, Server<Game>), EngineError> {
+        let room_id = self.next_room_id;
+
+        let udp_socket = self.udp_socket.try_clone().map_err(EngineError::Io)?;
+
+        let server = Server::new_room(
+            self.factory.clone(),
+            room_id,
+            udp_socket,
+            self.room_manager.clone(),
+        )?;
+
+        self.next_room_id = room_id.next();
+
+        return Ok((room_id, server));
+    }
+}
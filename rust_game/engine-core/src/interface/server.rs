@@ -1,29 +1,297 @@
 use crate::{
-    interface::RenderReceiver,
-    server::ServerCore,
+    interface::{
+        EngineError,
+        FrameObserver,
+        InputEventHandler,
+        LocalPlayer,
+        RateLimits,
+        RenderReceiver,
+        ServerSnapshot,
+        SessionToken,
+    },
+    server::{
+        RoomId,
+        RoomManager,
+        ServerCore,
+    },
     GameTrait,
 };
-use commons::real_time::Factory;
+use commons::real_time::net::udp::UdpSocket;
+use commons::real_time::{
+    Factory,
+    Receiver,
+};
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
 
 pub struct Server<Game: GameTrait> {
     server_core: ServerCore<Game>,
     render_receiver_option: Option<RenderReceiver<Game>>,
+    message_receiver_option: Option<Receiver<(usize, Game::ClientMessage)>>,
+    start_error: Option<EngineError>,
+    start_error_receiver: Receiver<EngineError>,
+    is_game_started: bool,
+    has_local_player: bool,
 }
 
 impl<Game: GameTrait> Server<Game> {
-    pub fn new(factory: Factory) -> Result<Self, ()> {
+    /// Starts a server that listens on [GameTrait::TCP_PORT] and
+    /// [GameTrait::UDP_PORT] of the loopback address, so only clients on the
+    /// same machine can join.  Fails with [EngineError::Bind] if either port
+    /// is in use.
+    pub fn new(factory: Factory) -> Result<Self, EngineError> {
+        return Self::new_with_ip_addr(factory, Ipv4Addr::LOCALHOST);
+    }
+
+    /// Starts a server that listens on [GameTrait::TCP_PORT] and
+    /// [GameTrait::UDP_PORT] of `ip_addr`.  Use [Ipv4Addr::UNSPECIFIED] to
+    /// accept the clients that find the server by
+    /// [discovery](Self::enable_discovery).
+    pub fn new_with_ip_addr(factory: Factory, ip_addr: Ipv4Addr) -> Result<Self, EngineError> {
+        return Self::new_standalone(factory, ip_addr, None);
+    }
+
+    /// Starts a server that resumes the match saved in a [ServerSnapshot].
+    /// Only the clients in the snapshot can join, by connecting with their
+    /// [SessionToken](crate::SessionToken) set in the
+    /// [ConnectConfig](crate::ConnectConfig), and they get back their player
+    /// indices.
+    ///
+    /// The match continues from the saved [FrameIndex](crate::FrameIndex) once
+    /// all of its clients have rejoined, along with the local player if it had
+    /// one.  If they have not all rejoined within
+    /// [REJOIN_TIMEOUT](GameTrait::REJOIN_TIMEOUT), or when
+    /// [start_game](Self::start_game) is called, it continues without the
+    /// players that have not rejoined, whose inputs are then missing.
+    ///
+    /// Fails with [EngineError::InvalidSnapshot] if the snapshot's player
+    /// indices are out of range or assigned twice.
+    pub fn resume(factory: Factory, snapshot: ServerSnapshot<Game>) -> Result<Self, EngineError> {
+        return Self::resume_with_ip_addr(factory, Ipv4Addr::LOCALHOST, snapshot);
+    }
+
+    /// Resumes the match saved in a [ServerSnapshot] like
+    /// [resume](Self::resume), listening on `ip_addr`
+    pub fn resume_with_ip_addr(
+        factory: Factory,
+        ip_addr: Ipv4Addr,
+        snapshot: ServerSnapshot<Game>,
+    ) -> Result<Self, EngineError> {
+        snapshot.validate()?;
+        return Self::new_standalone(factory, ip_addr, Some(snapshot));
+    }
+
+    /// Starts a standalone server listening on `ip_addr`, which resumes
+    /// `resumed_snapshot` if there is one
+    pub(crate) fn new_standalone(
+        factory: Factory,
+        ip_addr: Ipv4Addr,
+        resumed_snapshot: Option<ServerSnapshot<Game>>,
+    ) -> Result<Self, EngineError> {
         let (render_receiver_sender, render_receiver) = RenderReceiver::new(&factory);
+        let (message_sender, message_receiver) = factory.new_channel();
+        let (start_error_sender, start_error_receiver) = factory.new_channel();
 
-        let server_core = ServerCore::new(factory.clone(), render_receiver_sender.clone()).unwrap();
+        let server_core = ServerCore::new(
+            factory.clone(),
+            ip_addr,
+            render_receiver_sender.clone(),
+            message_sender,
+            start_error_sender,
+            resumed_snapshot,
+        )?;
 
         return Ok(Self {
             server_core,
             render_receiver_option: Some(render_receiver),
+            message_receiver_option: Some(message_receiver),
+            start_error: None,
+            start_error_receiver,
+            is_game_started: false,
+            has_local_player: false,
         });
     }
 
-    pub fn start_game(&self) -> Result<(), ()> {
-        self.server_core.start_game()
+    /// Creates a [Server] for one room of a [RoomServer](crate::RoomServer)
+    pub(crate) fn new_room(
+        factory: Factory,
+        room_id: RoomId,
+        udp_socket: UdpSocket,
+        room_manager: RoomManager<Game>,
+    ) -> Result<Self, EngineError> {
+        let (render_receiver_sender, render_receiver) = RenderReceiver::new(&factory);
+        let (message_sender, message_receiver) = factory.new_channel();
+        let (start_error_sender, start_error_receiver) = factory.new_channel();
+
+        let server_core = ServerCore::new_room(
+            factory,
+            render_receiver_sender,
+            message_sender,
+            start_error_sender,
+            room_id,
+            udp_socket,
+            room_manager.clone(),
+        )?;
+
+        room_manager.add_room(room_id, server_core.clone())?;
+
+        return Ok(Self {
+            server_core,
+            render_receiver_option: Some(render_receiver),
+            message_receiver_option: Some(message_receiver),
+            start_error: None,
+            start_error_receiver,
+            is_game_started: false,
+            has_local_player: false,
+        });
+    }
+
+    /// Starts the game with the clients that have joined.  The game is set up
+    /// on the server's thread, so a failure to set it up is reported by
+    /// [get_start_error](Self::get_start_error) rather than returned here.
+    pub fn start_game(&mut self) -> Result<(), EngineError> {
+        self.server_core.start_game()?;
+        self.is_game_started = true;
+        return Ok(());
+    }
+
+    /// Returns the error that stopped the game from starting, such as failing
+    /// to spawn one of the game's threads.  The server stops after such an
+    /// error.
+    pub fn get_start_error(&mut self) -> Option<&EngineError> {
+        if let Ok(start_error) = self.start_error_receiver.try_recv() {
+            self.start_error = Some(start_error);
+        }
+
+        return self.start_error.as_ref();
+    }
+
+    /// Saves the latest authoritative state of the running match, with the
+    /// players and their [SessionTokens](crate::SessionToken), to a
+    /// [ServerSnapshot] file at `path`.  The file is written by the server's
+    /// thread, which logs a failure to write it.
+    pub fn save_snapshot(&self, path: PathBuf) -> Result<(), EngineError> {
+        self.server_core.save_snapshot(path)
+    }
+
+    /// Lets a [StandbyServer](crate::StandbyServer) that presents
+    /// `standby_token` follow this server.  The standby is sent the latest
+    /// authoritative state of every frame, and takes over the match if this
+    /// server stops.
+    ///
+    /// Only a standalone [Server] can have a standby, not the rooms of a
+    /// [RoomServer](crate::RoomServer).
+    pub fn enable_standby(&self, standby_token: SessionToken) -> Result<(), EngineError> {
+        self.server_core.enable_standby(standby_token)
+    }
+
+    /// Stops the server and closes the connections to its clients.  A
+    /// [StandbyServer](crate::StandbyServer) following it takes over, and the
+    /// room of a [RoomServer](crate::RoomServer) is removed.
+    pub fn stop(self) -> Result<(), EngineError> {
+        self.server_core.stop()
+    }
+
+    /// Sends a [server input](GameTrait::ServerInput) to the game.  The input is
+    /// applied to the next frame and broadcast to all clients.
+    pub fn send_server_input(&self, server_input: Game::ServerInput) -> Result<(), EngineError> {
+        self.server_core.handle_server_input(server_input)
+    }
+
+    /// Registers a [FrameObserver] that is notified of the frames computed by
+    /// the server.  Observers added after the game has started miss the
+    /// frames computed before they were added.
+    pub fn add_frame_observer(
+        &self,
+        frame_observer: impl FrameObserver<Game>,
+    ) -> Result<(), EngineError> {
+        self.server_core
+            .add_frame_observer(Box::new(frame_observer))
+    }
+
+    /// Adds a player that plays on the server itself.  The player's inputs are
+    /// inserted into the server's frames as authoritative without passing
+    /// through UDP, and it takes the player index after all remote clients.
+    ///
+    /// The returned [RenderReceiver] is the server's own, so only one local
+    /// player can be added, and only before the game starts.  Otherwise this
+    /// fails with [EngineError::InvalidCall].
+    pub fn add_local_player<InputHandler: InputEventHandler<Game = Game>>(
+        &mut self,
+        input_event_handler: InputHandler,
+    ) -> Result<(LocalPlayer<Game>, RenderReceiver<Game>), EngineError> {
+        if self.has_local_player {
+            return Err(EngineError::InvalidCall(
+                "The server already has a local player",
+            ));
+        }
+
+        if self.is_game_started {
+            return Err(EngineError::InvalidCall(
+                "A local player can only be added before the game starts",
+            ));
+        }
+
+        if self.render_receiver_option.is_none() {
+            return Err(EngineError::InvalidCall(
+                "The render receiver has already been taken",
+            ));
+        }
+
+        self.server_core
+            .set_local_player(Box::new(input_event_handler))?;
+
+        self.has_local_player = true;
+        let render_receiver = self.render_receiver_option.take().unwrap();
+
+        let local_player = LocalPlayer::new(self.server_core.clone());
+
+        return Ok((local_player, render_receiver));
+    }
+
+    /// Sets the budgets for the UDP traffic accepted from clients.  This must
+    /// be called before the game starts.
+    ///
+    /// The rooms of a [RoomServer](crate::RoomServer) share a UDP socket, so
+    /// their packet and byte budgets are set with
+    /// [RoomServer::new_with_rate_limits](crate::RoomServer::new_with_rate_limits)
+    /// and only the input budget and kick threshold are used from here.
+    pub fn set_rate_limits(&self, rate_limits: RateLimits) -> Result<(), EngineError> {
+        self.server_core.set_rate_limits(rate_limits)
+    }
+
+    /// Answers LAN discovery probes sent by
+    /// [Client::discover](crate::Client::discover) on
+    /// [GameTrait::DISCOVERY_PORT].  Once the game starts the server still
+    /// answers, reporting [ServerState::Running](crate::ServerState::Running).
+    ///
+    /// Only a standalone [Server] can be discovered, not the rooms of a
+    /// [RoomServer](crate::RoomServer).
+    pub fn enable_discovery(&self) -> Result<(), EngineError> {
+        self.server_core.enable_discovery()
+    }
+
+    /// Sends a [message](GameTrait::ServerMessage) to the client with
+    /// `player_index`.  Messages are delivered reliably and in order.  The
+    /// local player has no connection and cannot be sent messages.
+    pub fn send_message(
+        &self,
+        player_index: usize,
+        message: Game::ServerMessage,
+    ) -> Result<(), EngineError> {
+        self.server_core.send_message(player_index, message)
+    }
+
+    /// Sends a [message](GameTrait::ServerMessage) to every connected client
+    pub fn broadcast_message(&self, message: Game::ServerMessage) -> Result<(), EngineError> {
+        self.server_core.broadcast_message(message)
+    }
+
+    /// Takes the queue of [messages](GameTrait::ClientMessage) received from
+    /// the clients, each paired with the player index of its sender.  Messages
+    /// are queued until it is taken.
+    pub fn take_message_receiver(&mut self) -> Option<Receiver<(usize, Game::ClientMessage)>> {
+        return self.message_receiver_option.take();
     }
 
     pub fn take_render_receiver(&mut self) -> Option<RenderReceiver<Game>> {
@@ -0,0 +1,172 @@
+use crate::interface::{
+    DiscoveredServer,
+    EngineError,
+};
+use crate::messaging::{
+    DiscoveryProbe,
+    DiscoveryResponse,
+};
+use crate::GameTrait;
+use commons::real_time::net::udp::{
+    HandleUdpRead,
+    UdpReadHandlerBuilder,
+};
+use commons::real_time::{
+    EventHandlerStopper,
+    Factory,
+    Receiver,
+    Sender,
+    TimeSource,
+};
+use commons::time::{
+    TimeDuration,
+    TimeValue,
+};
+use log::{
+    debug,
+    warn,
+};
+use std::marker::PhantomData;
+use std::net::{
+    Ipv4Addr,
+    SocketAddr,
+    SocketAddrV4,
+};
+use std::ops::ControlFlow;
+
+/// A LAN discovery started by [Client::discover](crate::Client::discover).
+///
+/// Responses are collected in the background until the timeout elapses.  Poll
+/// [ServerDiscovery::get_discovered_servers] for the servers found so far and
+/// [ServerDiscovery::is_complete] to know when the list is final.
+pub struct ServerDiscovery {
+    time_source: TimeSource,
+    end_time: TimeValue,
+    receiver: Receiver<DiscoveredServer>,
+    discovered_servers: Vec<DiscoveredServer>,
+    stopper: Option<EventHandlerStopper>,
+}
+
+impl ServerDiscovery {
+    pub(crate) fn new<Game: GameTrait>(
+        factory: &Factory,
+        timeout: TimeDuration,
+    ) -> Result<Self, EngineError> {
+        let socket_addr = SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0));
+        let mut udp_socket = factory
+            .bind_udp_socket(socket_addr)
+            .map_err(EngineError::Bind)?;
+        udp_socket.set_broadcast(true).map_err(EngineError::Io)?;
+
+        let (sender, receiver) = factory.new_channel();
+
+        let stopper = UdpReadHandlerBuilder::new_thread(
+            factory,
+            "ClientDiscoveryInput".to_string(),
+            udp_socket.try_clone().map_err(EngineError::Io)?,
+            ReadHandler::<Game> {
+                sender,
+                phantom: PhantomData,
+            },
+        )
+        .map_err(EngineError::Io)?;
+
+        let probe = DiscoveryProbe::new(Game::GAME_NAME.to_string());
+        let buf = rmp_serde::to_vec(&probe).map_err(EngineError::Serialization)?;
+
+        let broadcast_addr =
+            SocketAddr::V4(SocketAddrV4::new(Ipv4Addr::BROADCAST, Game::DISCOVERY_PORT));
+
+        udp_socket
+            .send_to(&buf, &broadcast_addr)
+            .map_err(EngineError::Io)?;
+
+        let time_source = factory.get_time_source().clone();
+        let end_time = time_source.now() + timeout;
+
+        return Ok(Self {
+            time_source,
+            end_time,
+            receiver,
+            discovered_servers: Vec::new(),
+            stopper: Some(stopper),
+        });
+    }
+
+    /// Returns true once the timeout has elapsed and no more servers will be
+    /// added
+    pub fn is_complete(&self) -> bool {
+        return self.stopper.is_none();
+    }
+
+    /// Returns the servers that have answered so far, each listed once
+    pub fn get_discovered_servers(&mut self) -> &Vec<DiscoveredServer> {
+        if self.stopper.is_some() {
+            while let Ok(discovered_server) = self.receiver.try_recv() {
+                if !self.discovered_servers.contains(&discovered_server) {
+                    self.discovered_servers.push(discovered_server);
+                }
+            }
+
+            if !self.time_source.now().is_before(&self.end_time) {
+                self.stop();
+            }
+        }
+
+        return &self.discovered_servers;
+    }
+
+    fn stop(&mut self) {
+        if let Some(stopper) = self.stopper.take() {
+            if stopper.send_stop_thread().is_err() {
+                debug!("The discovery input thread has already stopped");
+            }
+        }
+    }
+}
+
+impl Drop for ServerDiscovery {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+struct ReadHandler<Game: GameTrait> {
+    sender: Sender<DiscoveredServer>,
+    phantom: PhantomData<Game>,
+}
+
+impl<Game: GameTrait> HandleUdpRead for ReadHandler<Game> {
+    fn on_read(&mut self, peer_addr: SocketAddr, buf: &[u8]) -> ControlFlow<()> {
+        let response = match rmp_serde::from_slice::<DiscoveryResponse>(buf) {
+            Ok(response) => response,
+            Err(error) => {
+                warn!(
+                    "Failed to deserialize a DiscoveryResponse from {:?}: {:?}",
+                    peer_addr, error
+                );
+                return ControlFlow::Continue(());
+            }
+        };
+
+        if response.get_game_name() != Game::GAME_NAME {
+            return ControlFlow::Continue(());
+        }
+
+        // Probes are only broadcast over IPv4
+        let ip_addr = match peer_addr {
+            SocketAddr::V4(peer_addr) => *peer_addr.ip(),
+            SocketAddr::V6(_) => {
+                warn!("Ignoring a DiscoveryResponse from {:?}", peer_addr);
+                return ControlFlow::Continue(());
+            }
+        };
+
+        let discovered_server = DiscoveredServer::new(ip_addr, response);
+
+        match self.sender.send(discovered_server) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(_) => ControlFlow::Break(()),
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use crate::interface::{
+    EngineError,
+    GameTrait,
+    SessionToken,
+};
+use crate::server::ServerConfig;
+use crate::FrameIndex;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+use std::fs::File;
+use std::io::{
+    BufReader,
+    BufWriter,
+    Write,
+};
+use std::path::Path;
+
+/// A running match saved by [Server::save_snapshot](crate::Server::save_snapshot).
+///
+/// It holds the latest authoritative [State](GameTrait::State), its
+/// [FrameIndex], the [ServerConfig] and which player indices belong to which
+/// client.  A [Server](crate::Server) started with [Server::resume](crate::Server::resume)
+/// continues the match from it once the clients rejoin with their
+/// [SessionTokens](SessionToken).
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(bound = "")]
+pub struct ServerSnapshot<Game: GameTrait> {
+    server_config: ServerConfig,
+    player_count: usize,
+    clients: Vec<SavedClient>,
+    local_player_index: Option<usize>,
+    frame_index: FrameIndex,
+    state: Game::State,
+}
+
+/// The player indices of a client in a [ServerSnapshot]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub(crate) struct SavedClient {
+    session_token: SessionToken,
+    player_indices: Vec<usize>,
+}
+
+impl<Game: GameTrait> ServerSnapshot<Game> {
+    pub(crate) fn new(
+        server_config: ServerConfig,
+        player_count: usize,
+        clients: Vec<SavedClient>,
+        local_player_index: Option<usize>,
+        frame_index: FrameIndex,
+        state: Game::State,
+    ) -> Self {
+        return Self {
+            server_config,
+            player_count,
+            clients,
+            local_player_index,
+            frame_index,
+            state,
+        };
+    }
+
+    /// Reads a snapshot written by [write_to_file](Self::write_to_file)
+    pub fn read_from_file(path: &Path) -> Result<Self, EngineError> {
+        let file = File::open(path).map_err(EngineError::Io)?;
+        return rmp_serde::from_read(BufReader::new(file)).map_err(EngineError::Deserialization);
+    }
+
+    pub fn write_to_file(&self, path: &Path) -> Result<(), EngineError> {
+        let file = File::create(path).map_err(EngineError::Io)?;
+        let mut writer = BufWriter::new(file);
+        rmp_serde::encode::write(&mut writer, self).map_err(EngineError::Serialization)?;
+        return writer.flush().map_err(EngineError::Io);
+    }
+
+    pub fn get_server_config(&self) -> &ServerConfig {
+        return &self.server_config;
+    }
+
+    pub fn get_player_count(&self) -> usize {
+        return self.player_count;
+    }
+
+    /// Returns the player index of the listen-server
+    /// [local player](crate::Server::add_local_player), if there was one
+    pub fn get_local_player_index(&self) -> Option<usize> {
+        return self.local_player_index;
+    }
+
+    /// Returns the [FrameIndex] of the saved [State](GameTrait::State)
+    pub fn get_frame_index(&self) -> FrameIndex {
+        return self.frame_index;
+    }
+
+    /// Returns the latest authoritative [State](GameTrait::State) when the
+    /// snapshot was taken
+    pub fn get_state(&self) -> &Game::State {
+        return &self.state;
+    }
+
+    /// Moves the snapshot forward to a later authoritative state
+    pub(crate) fn set_state(&mut self, frame_index: FrameIndex, state: Game::State) {
+        self.frame_index = frame_index;
+        self.state = state;
+    }
+
+    /// Checks that every player index of the clients and the local player is
+    /// below the player count and belongs to only one of them, since a
+    /// snapshot read from a file can't be trusted
+    pub(crate) fn validate(&self) -> Result<(), EngineError> {
+        let mut is_assigned = vec![false; self.player_count];
+
+        let player_indices = self
+            .clients
+            .iter()
+            .flat_map(|client| client.player_indices.iter())
+            .chain(self.local_player_index.iter());
+
+        for player_index in player_indices {
+            match is_assigned.get_mut(*player_index) {
+                Some(true) => {
+                    return Err(EngineError::InvalidSnapshot(
+                        "A player index is assigned more than once",
+                    ))
+                }
+                Some(is_assigned) => *is_assigned = true,
+                None => {
+                    return Err(EngineError::InvalidSnapshot(
+                        "A player index is not below the player count",
+                    ))
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    pub(crate) fn get_clients(&self) -> &[SavedClient] {
+        return &self.clients;
+    }
+
+    /// Forgets the local player, whose process is gone when a
+    /// [StandbyServer](crate::StandbyServer) takes over.  Its player index
+    /// stays in the match, and its inputs are missing.
+    pub(crate) fn remove_local_player(&mut self) {
+        self.local_player_index = None;
+    }
+}
+
+impl<Game: GameTrait> Clone for ServerSnapshot<Game> {
+    fn clone(&self) -> Self {
+        Self {
+            server_config: self.server_config.clone(),
+            player_count: self.player_count,
+            clients: self.clients.clone(),
+            local_player_index: self.local_player_index,
+            frame_index: self.frame_index,
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl SavedClient {
+    pub(crate) fn new(session_token: SessionToken, player_indices: Vec<usize>) -> Self {
+        return Self {
+            session_token,
+            player_indices,
+        };
+    }
+
+    pub(crate) fn get_session_token(&self) -> SessionToken {
+        return self.session_token;
+    }
+
+    pub(crate) fn get_player_indices(&self) -> &[usize] {
+        return &self.player_indices;
+    }
+}
@@ -0,0 +1,32 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Identifies a client across connections.  The server gives each client a
+/// token when it joins, which the client presents with
+/// [ConnectConfig::set_session_token](crate::ConnectConfig::set_session_token)
+/// to rejoin a match that was [resumed](crate::Server::resume).
+///
+/// A token also lets a [StandbyServer](crate::StandbyServer) follow a
+/// [Server](crate::Server), see
+/// [Server::enable_standby](crate::Server::enable_standby).
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SessionToken(u64);
+
+impl SessionToken {
+    /// Creates a token that is hard to guess, such as for
+    /// [Server::enable_standby](crate::Server::enable_standby)
+    pub fn new_random() -> Self {
+        return Self(rand::random());
+    }
+
+    /// Recreates a token from a value the game saved with [get_value](Self::get_value)
+    pub fn from_value(value: u64) -> Self {
+        return Self(value);
+    }
+
+    pub fn get_value(&self) -> u64 {
+        return self.0;
+    }
+}
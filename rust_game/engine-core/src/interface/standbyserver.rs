@@ -0,0 +1,60 @@
+use crate::interface::{
+    EngineError,
+    GameTrait,
+    Server,
+    SessionToken,
+};
+use crate::server::StandbyCore;
+use commons::real_time::{
+    Factory,
+    Receiver,
+};
+use std::net::Ipv4Addr;
+
+/// A [StandbyServer] follows a [Server] as a privileged spectator so that the
+/// match survives the [Server] failing.
+///
+/// The [Server] must [enable](Server::enable_standby) the standby token the
+/// [StandbyServer] presents.  It then sends the standby a snapshot of the
+/// running match, followed by the authoritative state of every frame.  Once the connection to the [Server]
+/// closes, the standby [resumes](Server::resume) the match from the last state
+/// it was sent.  Clients that set
+/// [ConnectConfig::set_standby_server_ip](crate::ConnectConfig::set_standby_server_ip)
+/// reconnect to it with their [SessionTokens](SessionToken).
+///
+/// The [local player](Server::add_local_player) of the [Server] does not
+/// follow the match to the standby, and its inputs are missing from then on.
+/// The taken over match continues once the clients have reconnected, or after
+/// [REJOIN_TIMEOUT](GameTrait::REJOIN_TIMEOUT) without the ones that have not.
+pub struct StandbyServer<Game: GameTrait> {
+    _standby_core: StandbyCore<Game>,
+    server_receiver: Receiver<Server<Game>>,
+}
+
+impl<Game: GameTrait> StandbyServer<Game> {
+    /// Starts following the [Server] at `primary_ip`.  If it takes over, the
+    /// standby listens on `ip_addr`.  Fails with [EngineError::Connect] if the
+    /// [Server] cannot be reached.
+    pub fn new(
+        factory: Factory,
+        primary_ip: Ipv4Addr,
+        ip_addr: Ipv4Addr,
+        standby_token: SessionToken,
+    ) -> Result<Self, EngineError> {
+        let (server_sender, server_receiver) = factory.new_channel();
+
+        let standby_core =
+            StandbyCore::new(factory, primary_ip, ip_addr, standby_token, server_sender)?;
+
+        return Ok(Self {
+            _standby_core: standby_core,
+            server_receiver,
+        });
+    }
+
+    /// Takes the [Server] that continues the match once the standby has taken
+    /// over.  It is [None] until then.
+    pub fn take_server(&mut self) -> Option<Server<Game>> {
+        return self.server_receiver.try_recv().ok();
+    }
+}
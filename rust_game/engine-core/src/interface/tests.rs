@@ -0,0 +1,339 @@
+use crate::interface::{
+    DeterministicRng,
+    ExtrapolationPolicy,
+    InitialInformation,
+    RenderDelay,
+    RenderDiagnostics,
+    RenderReceiver,
+    RenderReceiverMessage,
+    RenderStatus,
+};
+use crate::messaging::FrameIndexAndState;
+use crate::server::{
+    RoomId,
+    ServerConfig,
+};
+use crate::test_utils::SumGame;
+use crate::{
+    FrameIndex,
+    GameTrait,
+};
+use commons::logging;
+use commons::real_time::simulation::SingleThreadedFactory;
+use commons::real_time::{
+    Factory,
+    Sender,
+};
+use commons::time::TimeValue;
+
+/// Creates a [RenderReceiver] for a game that starts now
+fn new_render_receiver(
+    simulated_factory: &SingleThreadedFactory,
+) -> (
+    Sender<RenderReceiverMessage<SumGame>>,
+    RenderReceiver<SumGame>,
+    TimeValue,
+) {
+    logging::setup_test_logging();
+
+    let factory: Factory = simulated_factory.clone().into();
+    let (sender, render_receiver) = RenderReceiver::<SumGame>::new(&factory);
+
+    let initial_information = InitialInformation::<SumGame>::new(
+        ServerConfig::new::<SumGame>(&factory, RoomId::default()),
+        1,
+        vec![0],
+        SumGame::get_initial_state(1),
+    );
+
+    let start_time = *initial_information.get_server_config().get_start_time();
+
+    sender
+        .send(RenderReceiverMessage::InitialInformation(
+            initial_information,
+        ))
+        .ok()
+        .unwrap();
+    sender
+        .send(RenderReceiverMessage::StartTime(start_time))
+        .ok()
+        .unwrap();
+
+    return (sender, render_receiver, *start_time.get_time_value());
+}
+
+fn send_state(
+    sender: &Sender<RenderReceiverMessage<SumGame>>,
+    frame_index: usize,
+    state: i64,
+    is_authoritative: bool,
+) {
+    sender
+        .send(RenderReceiverMessage::StepMessage {
+            is_authoritative,
+            state_message: FrameIndexAndState::new(FrameIndex::from(frame_index), state),
+        })
+        .ok()
+        .unwrap();
+}
+
+/// Advances the time to `frame` frames after `start_time` and renders
+fn render_at(
+    simulated_factory: &SingleThreadedFactory,
+    render_receiver: &mut RenderReceiver<SumGame>,
+    start_time: TimeValue,
+    frame: f64,
+) -> (f64, RenderDiagnostics) {
+    simulated_factory
+        .get_time_queue()
+        .advance_time_until(start_time + SumGame::STEP_PERIOD.mul_f64(frame));
+
+    let (_, (sum, _)) = render_receiver.get_step_message().unwrap();
+    let render_diagnostics = render_receiver.get_render_diagnostics().clone().unwrap();
+
+    return (sum, render_diagnostics);
+}
+
+fn assert_near(expected: f64, actual: f64) {
+    assert!(
+        (expected - actual).abs() < 1e-6,
+        "expected {:?} but got {:?}",
+        expected,
+        actual
+    );
+}
+
+#[test]
+fn test_interpolating() {
+    let simulated_factory = SingleThreadedFactory::new();
+    let (sender, mut render_receiver, start_time) = new_render_receiver(&simulated_factory);
+
+    send_state(&sender, 0, 0, true);
+    send_state(&sender, 1, 10, true);
+    send_state(&sender, 2, 20, true);
+
+    let (sum, render_diagnostics) =
+        render_at(&simulated_factory, &mut render_receiver, start_time, 1.5);
+
+    assert_eq!(RenderStatus::Interpolating, render_diagnostics.get_status());
+    assert_eq!(
+        FrameIndex::from(1),
+        render_diagnostics.get_from_frame_index()
+    );
+    assert_eq!(FrameIndex::from(2), render_diagnostics.get_to_frame_index());
+    assert_near(0.5, render_diagnostics.get_weight());
+    assert_near(15.0, sum);
+    assert_eq!(0, render_diagnostics.get_underrun_count());
+}
+
+#[test]
+fn test_holding() {
+    let simulated_factory = SingleThreadedFactory::new();
+    let (sender, mut render_receiver, start_time) = new_render_receiver(&simulated_factory);
+
+    send_state(&sender, 0, 0, true);
+    send_state(&sender, 1, 10, true);
+
+    let (sum, render_diagnostics) =
+        render_at(&simulated_factory, &mut render_receiver, start_time, 2.5);
+
+    assert_eq!(RenderStatus::Holding, render_diagnostics.get_status());
+    assert_eq!(
+        FrameIndex::from(1),
+        render_diagnostics.get_from_frame_index()
+    );
+    assert_eq!(FrameIndex::from(1), render_diagnostics.get_to_frame_index());
+    assert_near(10.0, sum);
+    assert!(render_diagnostics.is_underrun());
+    assert_eq!(1, render_diagnostics.get_underrun_count());
+}
+
+#[test]
+fn test_extrapolating() {
+    let simulated_factory = SingleThreadedFactory::new();
+    let (sender, mut render_receiver, start_time) = new_render_receiver(&simulated_factory);
+    render_receiver.set_extrapolation_policy(ExtrapolationPolicy::Extrapolate { max_frames: 1.0 });
+
+    send_state(&sender, 0, 0, true);
+    send_state(&sender, 1, 10, true);
+
+    let (sum, render_diagnostics) =
+        render_at(&simulated_factory, &mut render_receiver, start_time, 1.5);
+
+    assert_eq!(RenderStatus::Extrapolating, render_diagnostics.get_status());
+    assert_eq!(
+        FrameIndex::from(0),
+        render_diagnostics.get_from_frame_index()
+    );
+    assert_eq!(FrameIndex::from(1), render_diagnostics.get_to_frame_index());
+    assert_near(1.5, render_diagnostics.get_weight());
+    assert_near(15.0, sum);
+
+    // Extrapolation stops max_frames past the newest frame
+    let (sum, render_diagnostics) =
+        render_at(&simulated_factory, &mut render_receiver, start_time, 4.0);
+
+    assert_eq!(RenderStatus::Extrapolating, render_diagnostics.get_status());
+    assert_near(4.0, render_diagnostics.get_render_frame());
+    assert_near(2.0, render_diagnostics.get_weight());
+    assert_near(20.0, sum);
+
+    // Consecutive underrun frames are one underrun
+    assert_eq!(1, render_diagnostics.get_underrun_count());
+
+    send_state(&sender, 2, 20, true);
+    send_state(&sender, 3, 30, true);
+    send_state(&sender, 4, 40, true);
+    send_state(&sender, 5, 50, true);
+
+    let (_, render_diagnostics) =
+        render_at(&simulated_factory, &mut render_receiver, start_time, 4.5);
+
+    assert_eq!(RenderStatus::Interpolating, render_diagnostics.get_status());
+    assert_eq!(1, render_diagnostics.get_underrun_count());
+
+    let (_, render_diagnostics) =
+        render_at(&simulated_factory, &mut render_receiver, start_time, 5.5);
+
+    assert_eq!(RenderStatus::Extrapolating, render_diagnostics.get_status());
+    assert_eq!(2, render_diagnostics.get_underrun_count());
+}
+
+#[test]
+fn test_extrapolating_needs_two_frames() {
+    let simulated_factory = SingleThreadedFactory::new();
+    let (sender, mut render_receiver, start_time) = new_render_receiver(&simulated_factory);
+    render_receiver.set_extrapolation_policy(ExtrapolationPolicy::Extrapolate { max_frames: 1.0 });
+
+    send_state(&sender, 0, 0, true);
+
+    let (sum, render_diagnostics) =
+        render_at(&simulated_factory, &mut render_receiver, start_time, 0.5);
+
+    assert_eq!(RenderStatus::Holding, render_diagnostics.get_status());
+    assert_near(0.0, sum);
+}
+
+#[test]
+fn test_behind() {
+    let simulated_factory = SingleThreadedFactory::new();
+    let (sender, mut render_receiver, start_time) = new_render_receiver(&simulated_factory);
+
+    send_state(&sender, 2, 20, true);
+    send_state(&sender, 3, 30, true);
+
+    let (sum, render_diagnostics) =
+        render_at(&simulated_factory, &mut render_receiver, start_time, 1.0);
+
+    assert_eq!(RenderStatus::Behind, render_diagnostics.get_status());
+    assert_eq!(
+        FrameIndex::from(2),
+        render_diagnostics.get_from_frame_index()
+    );
+    assert_eq!(FrameIndex::from(2), render_diagnostics.get_to_frame_index());
+    assert_near(20.0, sum);
+    assert!(!render_diagnostics.is_underrun());
+}
+
+#[test]
+fn test_render_delay_and_trimming() {
+    let simulated_factory = SingleThreadedFactory::new();
+    let (sender, mut render_receiver, start_time) = new_render_receiver(&simulated_factory);
+    render_receiver.set_render_delay(RenderDelay::Frames(1.0));
+
+    for frame_index in 0..10 {
+        send_state(&sender, frame_index, frame_index as i64 * 10, true);
+    }
+
+    // The frames older than the render delay and the two frames needed to
+    // extrapolate are dropped
+    sender
+        .send(RenderReceiverMessage::FrameIndex(FrameIndex::from(9)))
+        .ok()
+        .unwrap();
+
+    let (sum, render_diagnostics) =
+        render_at(&simulated_factory, &mut render_receiver, start_time, 9.5);
+
+    assert_eq!(RenderStatus::Interpolating, render_diagnostics.get_status());
+    assert_near(8.5, render_diagnostics.get_render_frame());
+    assert_eq!(
+        FrameIndex::from(8),
+        render_diagnostics.get_from_frame_index()
+    );
+    assert_eq!(FrameIndex::from(9), render_diagnostics.get_to_frame_index());
+    assert_near(85.0, sum);
+    assert_eq!(4, render_diagnostics.get_buffered_frames());
+    assert_eq!(
+        Some(FrameIndex::from(9)),
+        render_diagnostics.get_latest_frame_index()
+    );
+}
+
+#[test]
+fn test_rollback_only_when_state_changes() {
+    let simulated_factory = SingleThreadedFactory::new();
+    let (sender, mut render_receiver, _) = new_render_receiver(&simulated_factory);
+
+    send_state(&sender, 0, 0, true);
+    send_state(&sender, 1, 1, false);
+    send_state(&sender, 2, 2, false);
+
+    simulated_factory
+        .get_time_queue()
+        .advance_time_for_duration(SumGame::STEP_PERIOD.mul_f64(0.5));
+
+    let (_, (_, has_rolled_back)) = render_receiver.get_step_message().unwrap();
+    assert!(!has_rolled_back);
+
+    // The authoritative state matches the prediction
+    send_state(&sender, 1, 1, true);
+
+    let (_, (_, has_rolled_back)) = render_receiver.get_step_message().unwrap();
+    assert!(!has_rolled_back);
+
+    // The authoritative state corrects the prediction
+    send_state(&sender, 2, 5, true);
+
+    let (_, (_, has_rolled_back)) = render_receiver.get_step_message().unwrap();
+    assert!(has_rolled_back);
+
+    // The rollback is only reported once
+    let (_, (_, has_rolled_back)) = render_receiver.get_step_message().unwrap();
+    assert!(!has_rolled_back);
+}
+
+#[test]
+fn test_deterministic_rng_for_frame() {
+    let mut rng = DeterministicRng::for_frame(7, FrameIndex::from(3));
+    let mut same_rng = DeterministicRng::for_frame(7, FrameIndex::from(3));
+    let mut next_frame_rng = DeterministicRng::for_frame(7, FrameIndex::from(4));
+    let mut other_session_rng = DeterministicRng::for_frame(8, FrameIndex::from(3));
+
+    for _ in 0..10 {
+        let value = rng.next_u64();
+        assert_eq!(value, same_rng.next_u64());
+        assert_ne!(value, next_frame_rng.next_u64());
+        assert_ne!(value, other_session_rng.next_u64());
+    }
+}
+
+#[test]
+fn test_session_seed_is_reproducible() {
+    let factory: Factory = SingleThreadedFactory::new().into();
+    let other_factory: Factory = SingleThreadedFactory::new().into();
+
+    let server_config = ServerConfig::new::<SumGame>(&factory, RoomId::default());
+
+    // Sessions started at the same simulated time have the same seed
+    assert_eq!(
+        server_config.get_session_seed(),
+        ServerConfig::new::<SumGame>(&other_factory, RoomId::default()).get_session_seed()
+    );
+
+    // Rooms started together have different seeds
+    assert_ne!(
+        server_config.get_session_seed(),
+        ServerConfig::new::<SumGame>(&factory, RoomId::default().next()).get_session_seed()
+    );
+}
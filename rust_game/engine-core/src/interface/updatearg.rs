@@ -1,8 +1,16 @@
 use crate::frame_manager::Input;
 use crate::game_time::FrameIndex;
 use crate::interface::game::GameTrait;
-use crate::interface::InitialInformation;
+use crate::interface::{
+    DeterministicRng,
+    EntityId,
+    InitialInformation,
+};
 use commons::time::TimeDuration;
+use std::cell::{
+    Cell,
+    RefCell,
+};
 
 #[derive(Debug)]
 pub struct UpdateArg<'a, Game: GameTrait> {
@@ -11,6 +19,12 @@ pub struct UpdateArg<'a, Game: GameTrait> {
     step: FrameIndex,
     state: &'a Game::State,
     inputs: &'a Vec<Input<Game::ClientInput>>,
+    predicted_inputs: &'a Vec<Option<Game::ClientInput>>,
+    server_input: &'a Input<Vec<Game::ServerInput>>,
+    is_server: bool,
+    is_state_authoritative: bool,
+    rng: RefCell<DeterministicRng>,
+    next_entity_index: Cell<u32>,
 }
 
 impl<'a, 'b, Game: GameTrait> UpdateArg<'a, Game> {
@@ -19,19 +33,54 @@ impl<'a, 'b, Game: GameTrait> UpdateArg<'a, Game> {
         step: FrameIndex,
         state: &'a Game::State,
         inputs: &'a Vec<Input<Game::ClientInput>>,
+        predicted_inputs: &'a Vec<Option<Game::ClientInput>>,
+        server_input: &'a Input<Vec<Game::ServerInput>>,
+        is_server: bool,
+        is_state_authoritative: bool,
     ) -> Self {
+        let rng = DeterministicRng::for_frame(
+            initial_information.get_server_config().get_session_seed(),
+            step,
+        );
+
         return Self {
             initial_information,
             step,
             state,
             inputs,
+            predicted_inputs,
+            server_input,
+            is_server,
+            is_state_authoritative,
+            rng: RefCell::new(rng),
+            next_entity_index: Cell::new(0),
         };
     }
 
+    pub fn get_player_count(&self) -> usize {
+        return self.inputs.len();
+    }
+
     pub fn get_input(&self, player_index: usize) -> &Input<Game::ClientInput> {
         return &self.inputs[player_index];
     }
 
+    /// Returns the player's input if it is known, otherwise the input
+    /// returned by [predict_input](GameTrait::predict_input).  Use
+    /// [get_input](Self::get_input) to tell known and predicted inputs apart.
+    pub fn get_input_or_prediction(&self, player_index: usize) -> Option<&Game::ClientInput> {
+        return self.inputs[player_index]
+            .get_value()
+            .or(self.predicted_inputs[player_index].as_ref());
+    }
+
+    /// Returns the [server inputs](GameTrait::ServerInput) for the current
+    /// frame.  These are [Input::Pending] on clients until they are received
+    /// from the server.
+    pub fn get_server_input(&self) -> &Input<Vec<Game::ServerInput>> {
+        return self.server_input;
+    }
+
     pub fn get_current_step(&self) -> FrameIndex {
         return self.step;
     }
@@ -40,6 +89,50 @@ impl<'a, 'b, Game: GameTrait> UpdateArg<'a, Game> {
         return self.state;
     }
 
+    /// Returns true if this update is being computed by the server, false if it
+    /// is being computed by a client.
+    ///
+    /// Clients treat a state computed from an authoritative state and a complete
+    /// set of authoritative inputs as authoritative.  Games with logic that
+    /// only runs on the server must set [GameTrait::HAS_SERVER_ONLY_LOGIC], so
+    /// that clients wait for the server's states instead.
+    pub fn is_server(&self) -> bool {
+        return self.is_server;
+    }
+
+    /// Returns true if the [State](GameTrait::State) being updated is
+    /// authoritative, false if it was predicted.
+    pub fn is_state_authoritative(&self) -> bool {
+        return self.is_state_authoritative;
+    }
+
+    /// Returns the next value from this frame's [DeterministicRng].  The
+    /// sequence is the same every time this frame is computed, on the server
+    /// and on every client.
+    pub fn random_u64(&self) -> u64 {
+        return self.rng.borrow_mut().next_u64();
+    }
+
+    /// Returns a value uniformly distributed in `[0, 1)` from this frame's
+    /// [DeterministicRng]
+    pub fn random_f64(&self) -> f64 {
+        return self.rng.borrow_mut().next_f64();
+    }
+
+    /// Returns a value uniformly distributed in `[min, max)` from this frame's
+    /// [DeterministicRng]
+    pub fn random_range_f64(&self, min: f64, max: f64) -> f64 {
+        return self.rng.borrow_mut().range_f64(min, max);
+    }
+
+    /// Allocates a new [EntityId].  The same IDs are allocated, in the same
+    /// order, every time this frame is computed.
+    pub fn new_entity_id(&self) -> EntityId {
+        let index = self.next_entity_index.get();
+        self.next_entity_index.set(index + 1);
+        return EntityId::new(self.step, index);
+    }
+
     pub fn get_next_step(&self) -> FrameIndex {
         return self.get_current_step() + 1;
     }
@@ -2,17 +2,54 @@ mod client;
 mod frame_manager;
 mod game_time;
 mod interface;
+mod local;
 mod messaging;
 mod server;
+#[cfg(test)]
+mod test_utils;
 
+pub use self::frame_manager::FrameManagerCore;
 pub use self::frame_manager::Input;
+pub use self::frame_manager::InputRejectionCounts;
+pub use self::frame_manager::ObserveFrames;
+pub use self::frame_manager::RejectionCounts;
+pub use self::frame_manager::RejectionReason;
+pub use self::frame_manager::SimulationMetrics;
 
 pub use self::game_time::FrameIndex;
 
+pub use self::messaging::FrameIndexAndState;
+
+pub use self::server::DroppedTrafficCounts;
+pub use self::server::RoomId;
+pub use self::server::ServerConfig;
+
 pub use interface::Client;
+pub use interface::ConnectConfig;
+pub use interface::ConnectionState;
+pub use interface::DeterministicRng;
+pub use interface::DiscoveredServer;
+pub use interface::EngineError;
+pub use interface::EntityId;
+pub use interface::ExtrapolationPolicy;
+pub use interface::FrameObserver;
 pub use interface::GameTrait;
 pub use interface::InitialInformation;
+pub use interface::InputEventHandler;
+pub use interface::InputValidation;
 pub use interface::InterpolationArg;
+pub use interface::LocalGame;
+pub use interface::LocalPlayer;
+pub use interface::RateLimits;
+pub use interface::RenderDelay;
+pub use interface::RenderDiagnostics;
 pub use interface::RenderReceiver;
+pub use interface::RenderStatus;
+pub use interface::RoomServer;
 pub use interface::Server;
+pub use interface::ServerDiscovery;
+pub use interface::ServerSnapshot;
+pub use interface::ServerState;
+pub use interface::SessionToken;
+pub use interface::StandbyServer;
 pub use interface::UpdateArg;
@@ -0,0 +1,309 @@
+use crate::frame_manager::FrameManager;
+use crate::game_time::{
+    FrameIndex,
+    GameTimerScheduler,
+};
+use crate::interface::{
+    FrameObserver,
+    GameTrait,
+    InitialInformation,
+    InputEventHandler,
+    RenderReceiverMessage,
+};
+use crate::local::localgametimerobserver::LocalGameTimerObserver;
+use crate::local::localmanagerobserver::LocalManagerObserver;
+use crate::server::{
+    RoomId,
+    ServerConfig,
+};
+use commons::real_time::timer_service::{
+    IdleTimerService,
+    TimerService,
+};
+use commons::real_time::{
+    EventHandleResult,
+    EventSender,
+    Factory,
+    HandleEvent,
+    ReceiveMetaData,
+    Sender,
+};
+use log::{
+    trace,
+    warn,
+};
+use std::mem::take;
+
+/// The index of the only player in a [LocalGame](crate::LocalGame)
+const LOCAL_PLAYER_INDEX: usize = 0;
+
+pub enum LocalGameCoreEvent<Game: GameTrait> {
+    StartGame,
+    OnInputEvent(Game::ClientInputEvent),
+    ServerInput(Game::ServerInput),
+    AddFrameObserver(Box<dyn FrameObserver<Game>>),
+    GameTimerTick,
+}
+
+pub struct LocalGameCore<Game: GameTrait, InputHandler: InputEventHandler<Game = Game>> {
+    factory: Factory,
+    sender: EventSender<LocalGameCoreEvent<Game>>,
+    render_receiver_sender: Sender<RenderReceiverMessage<Game>>,
+    input_event_handler: InputHandler,
+    pending_server_inputs: Vec<Game::ServerInput>,
+    // Frame observers added before the game starts
+    pending_frame_observers: Vec<Box<dyn FrameObserver<Game>>>,
+    running_state: Option<RunningState<Game>>,
+}
+
+struct RunningState<Game: GameTrait> {
+    frame_manager: FrameManager<Game>,
+    _timer_service: TimerService<(), LocalGameTimerObserver<Game>>,
+    game_timer: GameTimerScheduler,
+
+    /// The next FrameIndex that server inputs have not been inserted for
+    next_server_input_frame_index: FrameIndex,
+}
+
+impl<Game: GameTrait, InputHandler: InputEventHandler<Game = Game>>
+    LocalGameCore<Game, InputHandler>
+{
+    pub fn new(
+        factory: Factory,
+        sender: EventSender<LocalGameCoreEvent<Game>>,
+        render_receiver_sender: Sender<RenderReceiverMessage<Game>>,
+        input_event_handler: InputHandler,
+    ) -> Self {
+        return Self {
+            factory,
+            sender,
+            render_receiver_sender,
+            input_event_handler,
+            pending_server_inputs: Vec::new(),
+            pending_frame_observers: Vec::new(),
+            running_state: None,
+        };
+    }
+
+    fn start_game(&mut self) -> EventHandleResult {
+        if self.running_state.is_some() {
+            warn!("The LocalGame has already been started");
+            return EventHandleResult::TryForNextEvent;
+        }
+
+        let server_config = ServerConfig::new::<Game>(&self.factory, RoomId::default());
+
+        let initial_information = InitialInformation::<Game>::new(
+            server_config,
+            1,
+            vec![LOCAL_PLAYER_INDEX],
+            Game::get_initial_state(1),
+        );
+
+        let mut idle_timer_service = IdleTimerService::new();
+
+        let mut game_timer = GameTimerScheduler::server_new(
+            self.factory.get_time_source().clone(),
+            &mut idle_timer_service,
+            &server_config,
+            LocalGameTimerObserver::new(self.sender.clone()),
+        );
+
+        let timer_service = match idle_timer_service.start(&self.factory) {
+            Ok(timer_service) => timer_service,
+            Err(err) => {
+                warn!("Failed to Start the TimerService: {:?}", err);
+                return EventHandleResult::StopThread;
+            }
+        };
+
+        let (start_time, frame_index) = match game_timer.start_server_timer(&timer_service) {
+            Ok(result) => result,
+            Err(err) => {
+                warn!("Failed to Start the GameTimer: {:?}", err);
+                return EventHandleResult::StopThread;
+            }
+        };
+
+        if self
+            .render_receiver_sender
+            .send(RenderReceiverMessage::InitialInformation(
+                initial_information.clone(),
+            ))
+            .is_err()
+        {
+            warn!("Failed to send InitialInformation to Render Receiver");
+            return EventHandleResult::StopThread;
+        }
+
+        if self
+            .render_receiver_sender
+            .send(RenderReceiverMessage::StartTime(start_time))
+            .is_err()
+        {
+            warn!("Failed to send StartTime to Render Receiver");
+            return EventHandleResult::StopThread;
+        }
+
+        let frame_manager = match FrameManager::new(
+            &self.factory,
+            LocalManagerObserver::new(self.render_receiver_sender.clone()),
+            take(&mut self.pending_frame_observers),
+            initial_information,
+        ) {
+            Ok(frame_manager) => frame_manager,
+            Err(error) => {
+                warn!("Failed to start the FrameManager: {:?}", error);
+                return EventHandleResult::StopThread;
+            }
+        };
+
+        self.running_state = Some(RunningState {
+            frame_manager,
+            _timer_service: timer_service,
+            game_timer,
+            next_server_input_frame_index: FrameIndex::zero(),
+        });
+
+        return self.send_new_frame_index(frame_index);
+    }
+
+    fn on_game_timer_tick(&mut self) -> EventHandleResult {
+        let frame_index = match self.running_state {
+            Some(ref mut running_state) => match running_state.game_timer.try_advance_frame_index()
+            {
+                Some(frame_index) => frame_index,
+                None => return EventHandleResult::TryForNextEvent,
+            },
+            None => {
+                warn!("Received a game timer tick before the LocalGame started");
+                return EventHandleResult::TryForNextEvent;
+            }
+        };
+
+        return self.send_new_frame_index(frame_index);
+    }
+
+    fn send_new_frame_index(&mut self, frame_index: FrameIndex) -> EventHandleResult {
+        let running_state = match self.running_state {
+            Some(ref mut running_state) => running_state,
+            None => {
+                warn!("Tried to send next frame when the core wasn't running");
+                return EventHandleResult::TryForNextEvent;
+            }
+        };
+
+        trace!("TimeMessage step_index: {:?}", frame_index);
+
+        // There is no server to wait for, so the local input is authoritative
+        let send_result = running_state.frame_manager.insert_input(
+            frame_index,
+            LOCAL_PLAYER_INDEX,
+            self.input_event_handler.get_input(frame_index),
+            true,
+        );
+
+        if send_result.is_err() {
+            warn!("Failed to send InputMessage to Game Manager");
+            return EventHandleResult::StopThread;
+        }
+
+        // Every frame gets an authoritative set of server inputs, even if it is
+        // empty, including frames that were skipped by the game timer.
+        while running_state.next_server_input_frame_index <= frame_index {
+            let server_input_frame_index = running_state.next_server_input_frame_index;
+
+            let server_inputs = if server_input_frame_index == frame_index {
+                take(&mut self.pending_server_inputs)
+            } else {
+                Vec::new()
+            };
+
+            let send_result = running_state
+                .frame_manager
+                .insert_server_input(server_input_frame_index, server_inputs);
+
+            if send_result.is_err() {
+                warn!("Failed to send ServerInput to Game Manager");
+                return EventHandleResult::StopThread;
+            }
+
+            running_state.next_server_input_frame_index = server_input_frame_index.next();
+        }
+
+        if running_state
+            .frame_manager
+            .advance_frame_index(frame_index)
+            .is_err()
+        {
+            warn!("Failed to send FrameIndex to Game Manager");
+            return EventHandleResult::StopThread;
+        }
+
+        if self
+            .render_receiver_sender
+            .send(RenderReceiverMessage::FrameIndex(frame_index))
+            .is_err()
+        {
+            warn!("Failed to send FrameIndex to Render Receiver");
+            return EventHandleResult::StopThread;
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_input_event(&mut self, input_event: Game::ClientInputEvent) -> EventHandleResult {
+        self.input_event_handler.handle_input_event(input_event);
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_server_input(&mut self, server_input: Game::ServerInput) -> EventHandleResult {
+        // Server inputs are applied to the next frame
+        self.pending_server_inputs.push(server_input);
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_add_frame_observer(
+        &mut self,
+        frame_observer: Box<dyn FrameObserver<Game>>,
+    ) -> EventHandleResult {
+        match &self.running_state {
+            Some(running_state) => {
+                if running_state
+                    .frame_manager
+                    .add_frame_observer(frame_observer)
+                    .is_err()
+                {
+                    warn!("Failed to send FrameObserver to the FrameManager");
+                    return EventHandleResult::StopThread;
+                }
+            }
+            None => self.pending_frame_observers.push(frame_observer),
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+}
+
+impl<Game: GameTrait, InputHandler: InputEventHandler<Game = Game>> HandleEvent
+    for LocalGameCore<Game, InputHandler>
+{
+    type Event = LocalGameCoreEvent<Game>;
+    type ThreadReturn = ();
+
+    fn on_event(&mut self, _: ReceiveMetaData, event: Self::Event) -> EventHandleResult {
+        return match event {
+            LocalGameCoreEvent::StartGame => self.start_game(),
+            LocalGameCoreEvent::OnInputEvent(input_event) => self.on_input_event(input_event),
+            LocalGameCoreEvent::ServerInput(server_input) => self.on_server_input(server_input),
+            LocalGameCoreEvent::AddFrameObserver(frame_observer) => {
+                self.on_add_frame_observer(frame_observer)
+            }
+            LocalGameCoreEvent::GameTimerTick => self.on_game_timer_tick(),
+        };
+    }
+
+    fn on_stop_self(self) -> Self::ThreadReturn {
+        return ();
+    }
+}
@@ -0,0 +1,25 @@
+use crate::local::LocalGameCoreEvent;
+use crate::local::LocalGameCoreEvent::GameTimerTick;
+use crate::GameTrait;
+use commons::real_time::timer_service::TimerCallBack;
+use commons::real_time::EventSender;
+
+pub struct LocalGameTimerObserver<Game: GameTrait> {
+    core_sender: EventSender<LocalGameCoreEvent<Game>>,
+}
+
+impl<Game: GameTrait> LocalGameTimerObserver<Game> {
+    pub fn new(core_sender: EventSender<LocalGameCoreEvent<Game>>) -> Self {
+        return Self { core_sender };
+    }
+}
+
+impl<Game: GameTrait> TimerCallBack for LocalGameTimerObserver<Game> {
+    fn tick(&mut self) {
+        let send_result = self.core_sender.send_event(GameTimerTick);
+        if send_result.is_err() {
+            //TODO: handle this without panicing
+            panic!("Failed to send GameTimerTick to the Core")
+        }
+    }
+}
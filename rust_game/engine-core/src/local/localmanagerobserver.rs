@@ -0,0 +1,56 @@
+use crate::frame_manager::ObserveFrames;
+use crate::interface::RenderReceiverMessage;
+use crate::messaging::FrameIndexAndState;
+use crate::{
+    FrameIndex,
+    GameTrait,
+};
+use commons::real_time::Sender;
+use log::warn;
+use std::ops::ControlFlow;
+
+/// Observes the [FrameManager](crate::frame_manager::FrameManager) of a
+/// [LocalGame](crate::LocalGame).  The local game is its own server, so all
+/// of its states are computed with server logic.
+pub struct LocalManagerObserver<Game: GameTrait> {
+    render_receiver_sender: Sender<RenderReceiverMessage<Game>>,
+}
+
+impl<Game: GameTrait> LocalManagerObserver<Game> {
+    pub fn new(render_receiver_sender: Sender<RenderReceiverMessage<Game>>) -> Self {
+        return Self {
+            render_receiver_sender,
+        };
+    }
+}
+
+impl<Game: GameTrait> ObserveFrames for LocalManagerObserver<Game> {
+    type Game = Game;
+
+    const IS_SERVER: bool = true;
+
+    fn new_state(
+        &self,
+        is_state_authoritative: bool,
+        state_message: FrameIndexAndState<Game>,
+    ) -> ControlFlow<()> {
+        let result = self
+            .render_receiver_sender
+            .send(RenderReceiverMessage::StepMessage {
+                is_authoritative: is_state_authoritative,
+                state_message,
+            });
+
+        if result.is_err() {
+            warn!("Failed to send new state to render receiver");
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
+
+    fn input_authoritatively_missing(&self, _: FrameIndex, _: usize) -> ControlFlow<()> {
+        // There are no remote clients to tell
+        ControlFlow::Continue(())
+    }
+}
@@ -0,0 +1,11 @@
+mod localgamecore;
+mod localgametimerobserver;
+mod localmanagerobserver;
+
+pub use self::localgamecore::{
+    LocalGameCore,
+    LocalGameCoreEvent,
+};
+
+#[cfg(test)]
+mod tests;
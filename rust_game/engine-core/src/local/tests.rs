@@ -0,0 +1,102 @@
+use crate::interface::InputEventHandler;
+use crate::test_utils::SumGame;
+use crate::{
+    FrameIndex,
+    FrameObserver,
+    LocalGame,
+};
+use commons::logging;
+use commons::real_time::simulation::SingleThreadedFactory;
+use commons::time::TimeDuration;
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+/// Each input event adds one to the input of every following frame
+struct CountingInputEventHandler {
+    input: i64,
+}
+
+impl InputEventHandler for CountingInputEventHandler {
+    type Game = SumGame;
+
+    fn handle_input_event(&mut self, _input_event: ()) {
+        self.input = self.input + 1;
+    }
+
+    fn get_input(&mut self, _frame_index: FrameIndex) -> i64 {
+        self.input
+    }
+}
+
+struct StateObserver(Arc<Mutex<Vec<(FrameIndex, i64, bool)>>>);
+
+impl FrameObserver<SumGame> for StateObserver {
+    fn on_new_state(&mut self, frame_index: FrameIndex, state: &i64, is_authoritative: bool) {
+        self.0
+            .lock()
+            .unwrap()
+            .push((frame_index, *state, is_authoritative));
+    }
+}
+
+#[test]
+fn test_local_game() {
+    logging::setup_test_logging();
+
+    let factory = SingleThreadedFactory::new();
+    let (local_game, mut render_receiver) = LocalGame::<SumGame>::new(
+        factory.clone().into(),
+        CountingInputEventHandler { input: 0 },
+    )
+    .unwrap();
+    let states = Arc::new(Mutex::new(Vec::new()));
+    local_game
+        .add_frame_observer(StateObserver(states.clone()))
+        .unwrap();
+
+    local_game.start_game().unwrap();
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+
+    assert!(render_receiver.get_step_message().is_some());
+    assert_eq!(&[0], render_receiver.get_local_player_indices());
+
+    // Without input events, every frame adds nothing
+    assert!(1 < states.lock().unwrap().len());
+    assert!(states
+        .lock()
+        .unwrap()
+        .iter()
+        .all(|(_, state, _)| *state == 0));
+
+    local_game.send_client_input_event(()).ok().unwrap();
+    local_game.send_client_input_event(()).ok().unwrap();
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+
+    // The local inputs are authoritative right away, so after the first frame,
+    // which may be computed before its inputs arrive, each frame is computed
+    // once.  The frames after the input events add two.
+    let states: Vec<(FrameIndex, i64, bool)> = states
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|(frame_index, _, _)| FrameIndex::from(1) < *frame_index)
+        .cloned()
+        .collect();
+    assert!(states
+        .iter()
+        .all(|(_, _, is_authoritative)| *is_authoritative));
+
+    for pair in states.windows(2) {
+        assert_eq!(pair[0].0.next(), pair[1].0);
+    }
+
+    let (_, last_state, _) = *states.last().unwrap();
+    let (_, previous_state, _) = states[states.len() - 2];
+    assert_eq!(2, last_state - previous_state);
+}
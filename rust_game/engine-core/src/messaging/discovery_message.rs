@@ -0,0 +1,57 @@
+use crate::interface::ServerState;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Broadcast by clients looking for servers on the LAN
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DiscoveryProbe {
+    game_name: String,
+}
+
+impl DiscoveryProbe {
+    pub fn new(game_name: String) -> Self {
+        return Self { game_name };
+    }
+
+    pub fn get_game_name(&self) -> &str {
+        return &self.game_name;
+    }
+}
+
+/// Sent by a server in reply to a [DiscoveryProbe]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DiscoveryResponse {
+    game_name: String,
+    player_count: usize,
+    state: ServerState,
+    tcp_port: u16,
+}
+
+impl DiscoveryResponse {
+    pub fn new(game_name: String, player_count: usize, state: ServerState, tcp_port: u16) -> Self {
+        return Self {
+            game_name,
+            player_count,
+            state,
+            tcp_port,
+        };
+    }
+
+    pub fn get_game_name(&self) -> &str {
+        return &self.game_name;
+    }
+
+    pub fn get_player_count(&self) -> usize {
+        return self.player_count;
+    }
+
+    pub fn get_state(&self) -> ServerState {
+        return self.state;
+    }
+
+    pub fn get_tcp_port(&self) -> u16 {
+        return self.tcp_port;
+    }
+}
@@ -1,4 +1,7 @@
-use crate::messaging::MessageFragment;
+use crate::messaging::{
+    FragmentError,
+    MessageFragment,
+};
 use commons::real_time::TimeSource;
 use commons::time::TimeValue;
 use std::collections::HashMap;
@@ -18,9 +21,15 @@ impl FragmentAssembler {
         };
     }
 
-    pub fn add_fragment(&mut self, fragment: MessageFragment) -> Option<Vec<u8>> {
+    /// Adds a received fragment, returning the full message once all of its
+    /// fragments have arrived.  A fragment whose count disagrees with earlier
+    /// fragments of the same message is rejected.
+    pub fn add_fragment(
+        &mut self,
+        fragment: MessageFragment,
+    ) -> Result<Option<Vec<u8>>, FragmentError> {
         if fragment.get_count() == 1 {
-            return Some(fragment.move_buf());
+            return Ok(Some(fragment.move_buf()));
         }
 
         let id = fragment.get_id();
@@ -60,15 +69,15 @@ impl FragmentAssembler {
                 self.messages.get_mut(&id).unwrap()
             }
             Some(partial) => {
-                partial.add_fragment(fragment);
+                partial.add_fragment(fragment)?;
                 partial
             }
         };
 
         if partial.has_all_fragments() {
-            return Some(self.messages.remove(&id).unwrap().get_full_message());
+            return Ok(Some(self.messages.remove(&id).unwrap().get_full_message()));
         } else {
-            return None;
+            return Ok(None);
         }
     }
 }
@@ -97,18 +106,39 @@ impl PartiallyAssembledFragment {
             time_of_first_fragment: time_source.now(),
         };
 
-        new.add_fragment(fragment);
+        // The count of the first fragment is always consistent
+        new.add_fragment(fragment).unwrap();
 
         return new;
     }
 
-    fn add_fragment(&mut self, fragment: MessageFragment) {
-        if self.fragments[fragment.get_index() as usize].is_none() {
-            self.outstanding_fragments = self.outstanding_fragments - 1;
+    fn add_fragment(&mut self, fragment: MessageFragment) -> Result<(), FragmentError> {
+        if fragment.get_count() != self.count {
+            return Err(FragmentError::CountMismatch {
+                id: self.id,
+                expected: self.count,
+                actual: fragment.get_count(),
+            });
         }
 
         let index = fragment.get_index() as usize;
-        self.fragments[index] = Some(fragment);
+        let slot = match self.fragments.get_mut(index) {
+            Some(slot) => slot,
+            None => {
+                return Err(FragmentError::IndexOutOfRange {
+                    id: self.id,
+                    index: fragment.get_index(),
+                    count: self.count,
+                })
+            }
+        };
+
+        if slot.is_none() {
+            self.outstanding_fragments = self.outstanding_fragments - 1;
+        }
+
+        *slot = Some(fragment);
+        return Ok(());
     }
 
     fn has_all_fragments(&self) -> bool {
@@ -0,0 +1,16 @@
+/// The reason a datagram was rejected as a [MessageFragment](crate::messaging::MessageFragment)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FragmentError {
+    /// The datagram is shorter than the fragment header
+    TooShort { length: usize },
+
+    /// The header declares a message with no fragments
+    ZeroCount { id: u32 },
+
+    /// The index of the fragment is not less than the fragment count
+    IndexOutOfRange { id: u32, index: u16, count: u16 },
+
+    /// The fragment count differs from the count of earlier fragments of the
+    /// same message
+    CountMismatch { id: u32, expected: u16, actual: u16 },
+}
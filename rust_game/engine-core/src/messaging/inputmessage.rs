@@ -38,10 +38,6 @@ impl<Game: GameTrait> ToServerInputMessage<Game> {
     pub fn take_input(self) -> Game::ClientInput {
         self.input
     }
-
-    pub fn to_client_message(self) -> ToClientInputMessage<Game> {
-        ToClientInputMessage::new(self.frame_index, self.player_index, Some(self.input))
-    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -83,3 +79,31 @@ impl<Game: GameTrait> ToClientInputMessage<Game> {
         self.input
     }
 }
+
+/// The authoritative sets of [server inputs](GameTrait::ServerInput) of
+/// consecutive frames, starting at `first_frame_index`.  A set is sent for
+/// every frame, even when it is empty, so that clients know when a frame's
+/// server inputs are complete.  The server resends the sets of every frame
+/// still open for inputs, so a lost datagram is made up by the next one.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ServerInputMessage<Game: GameTrait> {
+    first_frame_index: FrameIndex,
+    server_inputs: Vec<Vec<Game::ServerInput>>,
+}
+
+impl<Game: GameTrait> ServerInputMessage<Game> {
+    pub fn new(first_frame_index: FrameIndex, server_inputs: Vec<Vec<Game::ServerInput>>) -> Self {
+        Self {
+            first_frame_index,
+            server_inputs,
+        }
+    }
+
+    pub fn get_first_frame_index(&self) -> FrameIndex {
+        self.first_frame_index
+    }
+
+    pub fn take_server_inputs(self) -> Vec<Vec<Game::ServerInput>> {
+        self.server_inputs
+    }
+}
@@ -1,3 +1,5 @@
+use crate::messaging::FragmentError;
+
 pub const FRAGMENT_HEADER_SIZE: usize = 8;
 const ID_INDEX: usize = 0;
 const INDEX_INDEX: usize = 4;
@@ -19,8 +21,27 @@ impl MessageFragment {
         return Self { buf: fragment };
     }
 
-    pub fn from_vec(buf: Vec<u8>) -> Self {
-        return Self { buf };
+    /// Parses a received datagram, validating its header so that the getters
+    /// cannot read out of bounds
+    pub fn from_vec(buf: Vec<u8>) -> Result<Self, FragmentError> {
+        if buf.len() < FRAGMENT_HEADER_SIZE {
+            return Err(FragmentError::TooShort { length: buf.len() });
+        }
+
+        let fragment = Self { buf };
+        let id = fragment.get_id();
+        let index = fragment.get_index();
+        let count = fragment.get_count();
+
+        if count == 0 {
+            return Err(FragmentError::ZeroCount { id });
+        }
+
+        if index >= count {
+            return Err(FragmentError::IndexOutOfRange { id, index, count });
+        }
+
+        return Ok(fragment);
     }
 
     pub fn get_id(&self) -> u32 {
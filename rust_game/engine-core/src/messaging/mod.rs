@@ -1,6 +1,10 @@
+pub use self::discovery_message::DiscoveryProbe;
+pub use self::discovery_message::DiscoveryResponse;
 pub use self::fragmentassembler::FragmentAssembler;
 pub use self::fragmenter::Fragmenter;
+pub use self::fragmenterror::FragmentError;
 pub use self::frame_index_and_state::FrameIndexAndState;
+pub use self::inputmessage::ServerInputMessage;
 pub use self::inputmessage::ToClientInputMessage;
 pub use self::inputmessage::ToServerInputMessage;
 pub use self::messagefragment::MessageFragment;
@@ -8,9 +12,12 @@ pub use self::toclientmessagetcp::ToClientMessageTCP;
 pub use self::toservermessagetcp::ToServerMessageTCP;
 pub use self::udp_to_client_message::UdpToClientMessage;
 pub use self::udp_to_server_message::UdpToServerMessage;
+pub use self::udp_to_server_message::UdpToServerPacket;
 
+mod discovery_message;
 mod fragmentassembler;
 mod fragmenter;
+mod fragmenterror;
 mod frame_index_and_state;
 mod inputmessage;
 mod messagefragment;
@@ -18,3 +25,6 @@ mod toclientmessagetcp;
 mod toservermessagetcp;
 mod udp_to_client_message;
 mod udp_to_server_message;
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,84 @@
+use crate::messaging::{
+    FragmentAssembler,
+    FragmentError,
+    MessageFragment,
+};
+use crate::test_utils::FuzzedDatagrams;
+use commons::logging;
+use commons::real_time::simulation::SingleThreadedFactory;
+use commons::real_time::Factory;
+
+fn new_assembler() -> FragmentAssembler {
+    logging::setup_test_logging();
+
+    let factory: Factory = SingleThreadedFactory::new().into();
+    FragmentAssembler::new(factory.get_time_source().clone(), 5)
+}
+
+fn datagram(id: u32, index: u16, count: u16, body: &[u8]) -> Vec<u8> {
+    MessageFragment::new(id, index, count, body.to_vec())
+        .get_whole_buf()
+        .clone()
+}
+
+#[test]
+fn test_fragment_header_validation() {
+    assert_eq!(
+        Some(FragmentError::TooShort { length: 7 }),
+        MessageFragment::from_vec(vec![0; 7]).err()
+    );
+
+    assert_eq!(
+        Some(FragmentError::ZeroCount { id: 3 }),
+        MessageFragment::from_vec(datagram(3, 0, 0, &[1])).err()
+    );
+
+    assert_eq!(
+        Some(FragmentError::IndexOutOfRange {
+            id: 3,
+            index: 2,
+            count: 2
+        }),
+        MessageFragment::from_vec(datagram(3, 2, 2, &[1])).err()
+    );
+
+    let fragment = MessageFragment::from_vec(datagram(3, 1, 2, &[])).unwrap();
+    assert_eq!(3, fragment.get_id());
+    assert_eq!(1, fragment.get_index());
+    assert_eq!(2, fragment.get_count());
+    assert_eq!(0, fragment.get_fragment_length());
+}
+
+#[test]
+fn test_fragment_count_mismatch() {
+    let mut assembler = new_assembler();
+
+    let first = MessageFragment::from_vec(datagram(1, 0, 2, &[1, 2])).unwrap();
+    assert_eq!(Ok(None), assembler.add_fragment(first));
+
+    let mismatched = MessageFragment::from_vec(datagram(1, 1, 3, &[9])).unwrap();
+    assert_eq!(
+        Err(FragmentError::CountMismatch {
+            id: 1,
+            expected: 2,
+            actual: 3
+        }),
+        assembler.add_fragment(mismatched)
+    );
+
+    // The partial message is unaffected by the rejected fragment
+    let second = MessageFragment::from_vec(datagram(1, 1, 2, &[3])).unwrap();
+    assert_eq!(Ok(Some(vec![1, 2, 3])), assembler.add_fragment(second));
+}
+
+#[test]
+fn test_fuzzed_fragments() {
+    let mut assembler = new_assembler();
+    let mut fuzzed_datagrams = FuzzedDatagrams::new(45, Vec::new());
+
+    for _ in 0..10_000 {
+        if let Ok(fragment) = MessageFragment::from_vec(fuzzed_datagrams.next_datagram()) {
+            let _ = assembler.add_fragment(fragment);
+        }
+    }
+}
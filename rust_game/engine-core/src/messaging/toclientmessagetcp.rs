@@ -1,5 +1,7 @@
 use crate::interface::GameTrait;
 use crate::interface::InitialInformation;
+use crate::interface::ServerSnapshot;
+use crate::messaging::FrameIndexAndState;
 use serde::{
     Deserialize,
     Serialize,
@@ -10,4 +12,11 @@ use serde::{
 pub enum ToClientMessageTCP<Game: GameTrait> {
     //TODO: see if these can be borrowed
     InitialInformation(InitialInformation<Game>),
+    Message(Game::ServerMessage),
+    /// The latest authoritative state of the match, sent to a
+    /// [StandbyServer](crate::StandbyServer) once it follows a running match
+    Snapshot(ServerSnapshot<Game>),
+    /// The authoritative state of each following frame, which updates the
+    /// [Snapshot](Self::Snapshot) a [StandbyServer](crate::StandbyServer) holds
+    StandbyState(FrameIndexAndState<Game>),
 }
@@ -1,7 +1,29 @@
+use crate::interface::{
+    GameTrait,
+    SessionToken,
+};
+use crate::server::RoomId;
 use serde::{
     Deserialize,
     Serialize,
 };
 
 #[derive(Serialize, Deserialize, Debug)]
-pub enum ToServerMessageTCP {}
+#[serde(bound = "")]
+pub enum ToServerMessageTCP<Game: GameTrait> {
+    /// Sent by a client right after connecting to pick the room it wants to
+    /// join and how many local players it has.  If no room is given, the
+    /// server assigns one.  A client rejoining a resumed match presents its
+    /// [SessionToken].
+    JoinRoom {
+        room_id: Option<RoomId>,
+        local_player_count: usize,
+        session_token: Option<SessionToken>,
+    },
+    /// Sent by a [StandbyServer](crate::StandbyServer) right after connecting,
+    /// instead of [JoinRoom](Self::JoinRoom), to follow the server's match
+    FollowAsStandby {
+        standby_token: SessionToken,
+    },
+    Message(Game::ClientMessage),
+}
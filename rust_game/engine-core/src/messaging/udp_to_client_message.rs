@@ -7,6 +7,7 @@ use crate::game_time::PingResponse;
 use crate::interface::GameTrait;
 use crate::messaging::{
     FrameIndexAndState,
+    ServerInputMessage,
     ToClientInputMessage,
 };
 
@@ -16,5 +17,6 @@ pub enum UdpToClientMessage<Game: GameTrait> {
     //TODO: see if these can be borrowed
     PingResponse(PingResponse),
     InputMessage(ToClientInputMessage<Game>),
+    ServerInputMessage(ServerInputMessage<Game>),
     StateMessage(FrameIndexAndState<Game>),
 }
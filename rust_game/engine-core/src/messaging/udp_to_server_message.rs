@@ -1,6 +1,7 @@
 use crate::{
     game_time::PingRequest,
     interface::GameTrait,
+    server::RoomId,
 };
 use serde::{
     Deserialize,
@@ -25,3 +26,26 @@ impl<Game: GameTrait> UdpToServerMessage<Game> {
         };
     }
 }
+
+/// A [UdpToServerMessage] addressed to the room it is for, so that a single
+/// UDP socket can serve many rooms
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(bound = "")]
+pub struct UdpToServerPacket<Game: GameTrait> {
+    room_id: RoomId,
+    message: UdpToServerMessage<Game>,
+}
+
+impl<Game: GameTrait> UdpToServerPacket<Game> {
+    pub fn new(room_id: RoomId, message: UdpToServerMessage<Game>) -> Self {
+        return Self { room_id, message };
+    }
+
+    pub fn get_room_id(&self) -> RoomId {
+        return self.room_id;
+    }
+
+    pub fn take_message(self) -> UdpToServerMessage<Game> {
+        return self.message;
+    }
+}
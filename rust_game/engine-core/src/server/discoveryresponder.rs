@@ -0,0 +1,110 @@
+use crate::messaging::{
+    DiscoveryProbe,
+    DiscoveryResponse,
+};
+use crate::server::ServerCore;
+use crate::GameTrait;
+use commons::real_time::net::udp::{
+    HandleUdpRead,
+    UdpReadHandlerBuilder,
+    UdpSocket,
+};
+use commons::real_time::{
+    EventHandlerStopper,
+    Factory,
+};
+use log::{
+    debug,
+    warn,
+};
+use std::io::Error;
+use std::net::{
+    Ipv4Addr,
+    SocketAddr,
+    SocketAddrV4,
+};
+use std::ops::ControlFlow;
+
+/// Listens for [DiscoveryProbes](DiscoveryProbe) broadcast on
+/// [GameTrait::DISCOVERY_PORT] and passes them to the [ServerCore], which
+/// answers through [DiscoveryResponder::send_response].
+pub struct DiscoveryResponder {
+    udp_socket: UdpSocket,
+    _stopper: EventHandlerStopper,
+}
+
+impl DiscoveryResponder {
+    pub fn new<Game: GameTrait>(
+        factory: &Factory,
+        server_core: ServerCore<Game>,
+    ) -> Result<Self, Error> {
+        // Bound to the unspecified address so that broadcasts are received
+        let socket_addr = SocketAddr::V4(SocketAddrV4::new(
+            Ipv4Addr::UNSPECIFIED,
+            Game::DISCOVERY_PORT,
+        ));
+
+        let udp_socket = factory.bind_udp_socket(socket_addr)?;
+
+        let stopper = UdpReadHandlerBuilder::new_thread(
+            factory,
+            "ServerDiscoveryInput".to_string(),
+            udp_socket.try_clone()?,
+            ReadHandler { server_core },
+        )?;
+
+        Ok(Self {
+            udp_socket,
+            _stopper: stopper,
+        })
+    }
+
+    pub fn send_response(&mut self, peer_addr: SocketAddr, response: &DiscoveryResponse) {
+        let buf = match rmp_serde::to_vec(response) {
+            Ok(buf) => buf,
+            Err(error) => {
+                warn!("Failed to serialize a DiscoveryResponse: {:?}", error);
+                return;
+            }
+        };
+
+        if let Err(error) = self.udp_socket.send_to(&buf, &peer_addr) {
+            warn!("Failed to send a DiscoveryResponse: {:?}", error);
+        }
+    }
+}
+
+struct ReadHandler<Game: GameTrait> {
+    server_core: ServerCore<Game>,
+}
+
+impl<Game: GameTrait> HandleUdpRead for ReadHandler<Game> {
+    fn on_read(&mut self, peer_addr: SocketAddr, buf: &[u8]) -> ControlFlow<()> {
+        let probe = match rmp_serde::from_slice::<DiscoveryProbe>(buf) {
+            Ok(probe) => probe,
+            Err(error) => {
+                debug!(
+                    "Ignoring a datagram that is not a DiscoveryProbe from {:?}: {:?}",
+                    peer_addr, error
+                );
+                return ControlFlow::Continue(());
+            }
+        };
+
+        if probe.get_game_name() != Game::GAME_NAME {
+            debug!(
+                "Ignoring a DiscoveryProbe for another game: {:?}",
+                probe.get_game_name()
+            );
+            return ControlFlow::Continue(());
+        }
+
+        match self.server_core.handle_discovery_probe(peer_addr) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(_) => {
+                warn!("Error sending DiscoveryProbe to the Core");
+                ControlFlow::Break(())
+            }
+        }
+    }
+}
@@ -0,0 +1,33 @@
+/// The traffic dropped by a [Server](crate::Server) for going over its
+/// [RateLimits](crate::RateLimits), counted since the game started
+#[derive(Clone, Debug, Default)]
+pub struct DroppedTrafficCounts {
+    datagram_count: usize,
+    input_counts: Vec<usize>,
+}
+
+impl DroppedTrafficCounts {
+    pub(super) fn new(datagram_count: usize, input_counts: Vec<usize>) -> Self {
+        return Self {
+            datagram_count,
+            input_counts,
+        };
+    }
+
+    /// The datagrams dropped for going over the packet or byte budget of their
+    /// source.  On a [RoomServer](crate::RoomServer) the budgets are enforced
+    /// before the room of a datagram is known, so these are only logged and
+    /// this is always zero.
+    pub fn get_datagram_count(&self) -> usize {
+        return self.datagram_count;
+    }
+
+    /// The inputs dropped for going over the input budget of the player
+    pub fn get_input_count(&self, player_index: usize) -> usize {
+        return self.input_counts.get(player_index).copied().unwrap_or(0);
+    }
+
+    pub fn get_total_input_count(&self) -> usize {
+        return self.input_counts.iter().sum();
+    }
+}
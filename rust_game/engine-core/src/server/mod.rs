@@ -1,15 +1,31 @@
+pub use self::droppedtrafficcounts::DroppedTrafficCounts;
+pub use self::roomid::RoomId;
+pub use self::roommanager::RoomManager;
+pub use self::roomudpinput::RoomUdpInput;
 pub use self::serverconfig::ServerConfig;
 pub use self::servercore::ServerCore;
+pub use self::standbycore::StandbyCore;
 pub use crate::server::tcpconnectionhandler::TcpConnectionHandler;
 
 mod clientaddress;
+mod discoveryresponder;
+mod droppedtrafficcounts;
+mod pendingconnections;
+mod ratelimiter;
 mod remoteudppeer;
+mod roomid;
+mod roommanager;
+mod roomudpinput;
 mod serverconfig;
 mod servercore;
 mod servermanagerobserver;
+mod standbycore;
 mod tcpconnectionhandler;
 mod tcpinput;
 mod tcpoutput;
 mod udphandler;
 mod udpinput;
 mod udpoutput;
+
+#[cfg(test)]
+mod tests;
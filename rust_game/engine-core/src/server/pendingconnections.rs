@@ -0,0 +1,90 @@
+use crate::server::tcpinput::TcpInput;
+use commons::real_time::net::tcp::TcpStream;
+use commons::real_time::TimeSource;
+use commons::time::{
+    TimeDuration,
+    TimeValue,
+};
+use log::warn;
+use std::collections::HashMap;
+
+/// How long a new connection has to finish its handshake before it is closed
+pub const HANDSHAKE_TIMEOUT: TimeDuration = TimeDuration::new(5, 0);
+
+/// The maximum number of connections that can be waiting to finish their
+/// handshake.  Connections over it are closed as soon as they are accepted.
+pub const MAX_PENDING_CONNECTIONS: usize = 64;
+
+/// The accepted TCP connections that have not said how many players they have,
+/// which room they want, or whether they are a standby server.
+///
+/// Dropping a connection closes it and stops its [TcpInput] thread, so
+/// connections that stay silent past the [HANDSHAKE_TIMEOUT] are dropped.
+pub struct PendingConnections {
+    time_source: TimeSource,
+    connections: HashMap<usize, PendingConnection>,
+}
+
+struct PendingConnection {
+    tcp_stream: TcpStream,
+    tcp_input: TcpInput,
+    deadline: TimeValue,
+}
+
+impl PendingConnections {
+    pub fn new(time_source: TimeSource) -> Self {
+        return Self {
+            time_source,
+            connections: HashMap::new(),
+        };
+    }
+
+    /// Whether new connections have to be refused
+    pub fn is_full(&self) -> bool {
+        return self.connections.len() >= MAX_PENDING_CONNECTIONS;
+    }
+
+    pub fn insert(&mut self, connection_id: usize, tcp_stream: TcpStream, tcp_input: TcpInput) {
+        let deadline = self.time_source.now() + HANDSHAKE_TIMEOUT;
+
+        self.connections.insert(
+            connection_id,
+            PendingConnection {
+                tcp_stream,
+                tcp_input,
+                deadline,
+            },
+        );
+    }
+
+    pub fn remove(&mut self, connection_id: usize) -> Option<(TcpStream, TcpInput)> {
+        return self
+            .connections
+            .remove(&connection_id)
+            .map(|connection| (connection.tcp_stream, connection.tcp_input));
+    }
+
+    /// Drops the connections whose handshake has timed out, and returns when
+    /// the next one times out
+    pub fn drop_timed_out(&mut self) -> Option<TimeValue> {
+        let now = self.time_source.now();
+
+        self.connections.retain(|_, connection| {
+            if connection.deadline.is_after(&now) {
+                return true;
+            }
+
+            warn!(
+                "Dropping {:?}, which did not finish its handshake in time",
+                connection.tcp_stream.get_peer_addr()
+            );
+            return false;
+        });
+
+        return self
+            .connections
+            .values()
+            .map(|connection| connection.deadline)
+            .min_by(|a, b| a.as_secs_f64().total_cmp(&b.as_secs_f64()));
+    }
+}
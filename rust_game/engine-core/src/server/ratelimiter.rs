@@ -0,0 +1,155 @@
+use crate::interface::RateLimits;
+use crate::GameTrait;
+use commons::real_time::TimeSource;
+use commons::time::TimeValue;
+use log::warn;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::net::SocketAddr;
+
+/// Enforces the per-source packet and byte budgets of [RateLimits] on raw
+/// datagrams, before they are assembled or deserialized.
+///
+/// All sources share the same one frame window, and the counts are cleared
+/// when it ends, so the memory used is bounded by the number of sources seen
+/// in a single frame.
+pub struct SourceRateLimiter<Game: GameTrait> {
+    time_source: TimeSource,
+    rate_limits: RateLimits,
+    window_end: Option<TimeValue>,
+    // The packets and bytes received from each source in the current window
+    counts: HashMap<SocketAddr, (usize, usize)>,
+    // The datagrams dropped in the current window
+    dropped_count: usize,
+    // The datagrams dropped since the limiter was created
+    total_dropped_count: usize,
+    phantom: PhantomData<Game>,
+}
+
+impl<Game: GameTrait> SourceRateLimiter<Game> {
+    pub fn new(time_source: TimeSource, rate_limits: RateLimits) -> Self {
+        return Self {
+            time_source,
+            rate_limits,
+            window_end: None,
+            counts: HashMap::new(),
+            dropped_count: 0,
+            total_dropped_count: 0,
+            phantom: PhantomData,
+        };
+    }
+
+    /// Returns true if the datagram is within the source's budget
+    pub fn allow_datagram(&mut self, source: SocketAddr, len: usize) -> bool {
+        let now = self.time_source.now();
+
+        match self.window_end {
+            Some(window_end) if now.is_before(&window_end) => {}
+            _ => {
+                if self.dropped_count > 0 {
+                    warn!(
+                        "Dropped {:?} datagrams over the rate limit",
+                        self.dropped_count
+                    );
+                }
+
+                self.window_end = Some(now + Game::STEP_PERIOD);
+                self.counts.clear();
+                self.dropped_count = 0;
+            }
+        }
+
+        let (packets, bytes) = self.counts.entry(source).or_insert((0, 0));
+
+        if *packets >= self.rate_limits.get_max_packets_per_frame()
+            || *bytes + len > self.rate_limits.get_max_bytes_per_frame()
+        {
+            self.dropped_count += 1;
+            self.total_dropped_count += 1;
+            return false;
+        }
+
+        *packets += 1;
+        *bytes += len;
+
+        return true;
+    }
+
+    pub fn get_total_dropped_count(&self) -> usize {
+        return self.total_dropped_count;
+    }
+}
+
+/// The result of checking an input against a player's budget
+#[derive(Debug, PartialEq, Eq)]
+pub enum InputBudget {
+    Allow,
+    Drop,
+    /// The input is dropped and the player has passed the kick threshold
+    Kick,
+}
+
+/// Enforces the per-player input budget of [RateLimits] and counts the inputs
+/// dropped from each player against the kick threshold
+pub struct PlayerRateLimiter<Game: GameTrait> {
+    time_source: TimeSource,
+    rate_limits: RateLimits,
+    window_end: Option<TimeValue>,
+    // The inputs received from each player in the current window
+    input_counts: Vec<usize>,
+    // The inputs dropped from each player since the game started
+    dropped_counts: Vec<usize>,
+    phantom: PhantomData<Game>,
+}
+
+impl<Game: GameTrait> PlayerRateLimiter<Game> {
+    pub fn new(time_source: TimeSource, rate_limits: RateLimits) -> Self {
+        return Self {
+            time_source,
+            rate_limits,
+            window_end: None,
+            input_counts: Vec::new(),
+            dropped_counts: Vec::new(),
+            phantom: PhantomData,
+        };
+    }
+
+    pub fn check_input(&mut self, player_index: usize) -> InputBudget {
+        let now = self.time_source.now();
+
+        match self.window_end {
+            Some(window_end) if now.is_before(&window_end) => {}
+            _ => {
+                self.window_end = Some(now + Game::STEP_PERIOD);
+                self.input_counts.iter_mut().for_each(|count| *count = 0);
+            }
+        }
+
+        if self.input_counts.len() <= player_index {
+            self.input_counts.resize(player_index + 1, 0);
+            self.dropped_counts.resize(player_index + 1, 0);
+        }
+
+        if self.input_counts[player_index] < self.rate_limits.get_max_inputs_per_frame() {
+            self.input_counts[player_index] += 1;
+            return InputBudget::Allow;
+        }
+
+        self.dropped_counts[player_index] += 1;
+
+        return match self.rate_limits.get_kick_threshold() {
+            Some(kick_threshold) if self.dropped_counts[player_index] > kick_threshold => {
+                InputBudget::Kick
+            }
+            _ => InputBudget::Drop,
+        };
+    }
+
+    pub fn get_dropped_count(&self, player_index: usize) -> usize {
+        return self.dropped_counts.get(player_index).copied().unwrap_or(0);
+    }
+
+    pub fn get_dropped_counts(&self) -> &[usize] {
+        return &self.dropped_counts;
+    }
+}
@@ -0,0 +1,26 @@
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+/// Identifies one game room hosted by a [RoomServer](crate::RoomServer).
+/// A standalone [Server](crate::Server) hosts a single room with the default
+/// [RoomId].
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash,
+)]
+pub struct RoomId(u32);
+
+impl RoomId {
+    pub fn new(id: u32) -> Self {
+        return Self(id);
+    }
+
+    pub fn get_id(&self) -> u32 {
+        return self.0;
+    }
+
+    pub fn next(&self) -> Self {
+        return Self(self.0 + 1);
+    }
+}
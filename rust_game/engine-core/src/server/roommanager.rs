@@ -0,0 +1,407 @@
+use crate::messaging::UdpToServerPacket;
+use crate::server::pendingconnections::PendingConnections;
+use crate::server::tcpinput::TcpInput;
+use crate::server::udpinput::UdpInputHandler;
+use crate::server::{
+    RoomId,
+    ServerCore,
+};
+use crate::{
+    EngineError,
+    GameTrait,
+    SessionToken,
+};
+use commons::real_time::net::tcp::{
+    HandleTcpConnection,
+    TcpReader,
+    TcpStream,
+};
+use commons::real_time::{
+    EventHandleResult,
+    EventHandlerBuilder,
+    EventSender,
+    Factory,
+    HandleEvent,
+    ReceiveMetaData,
+};
+use log::{
+    error,
+    info,
+    warn,
+};
+use std::collections::{
+    BTreeMap,
+    HashMap,
+};
+use std::io::Error;
+use std::net::SocketAddr;
+use std::ops::ControlFlow;
+
+/// The [RoomManager] routes the TCP connections and UDP messages received by a
+/// [RoomServer](crate::RoomServer) to the [ServerCore] of the room they are
+/// for.
+///
+/// New connections are held until the client sends the room it wants to join.
+/// Clients that do not ask for a room are assigned to the room with the lowest
+/// [RoomId] that has not started its game yet.
+#[derive(Clone)]
+pub struct RoomManager<Game: GameTrait> {
+    sender: EventSender<RoomManagerEvent<Game>>,
+}
+
+impl<Game: GameTrait> RoomManager<Game> {
+    pub fn new(factory: &Factory) -> Result<Self, Error> {
+        let builder = EventHandlerBuilder::new(factory);
+
+        let room_manager = Self {
+            sender: builder.get_sender().clone(),
+        };
+
+        let event_handler = RoomManagerEventHandler {
+            factory: factory.clone(),
+            room_manager: room_manager.clone(),
+            next_connection_id: 0,
+            pending_connections: PendingConnections::new(factory.get_time_source().clone()),
+            joined_connections: HashMap::new(),
+            rooms: BTreeMap::new(),
+        };
+
+        builder.spawn_thread("RoomManager".to_string(), event_handler)?;
+
+        Ok(room_manager)
+    }
+
+    /// Adds a room that clients can join until its game starts
+    pub fn add_room(
+        &self,
+        room_id: RoomId,
+        server_core: ServerCore<Game>,
+    ) -> Result<(), EngineError> {
+        self.sender
+            .send_event(RoomManagerEvent::AddRoom(room_id, server_core))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Closes a room to new clients and routes its UDP messages to
+    /// `udp_input_handler`
+    pub fn start_room(
+        &self,
+        room_id: RoomId,
+        udp_input_handler: UdpInputHandler<Game>,
+    ) -> Result<(), EngineError> {
+        self.sender
+            .send_event(RoomManagerEvent::StartRoom(room_id, udp_input_handler))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Removes a room whose game has stopped, so that no more connections or
+    /// UDP messages are routed to it
+    pub fn remove_room(&self, room_id: RoomId) -> Result<(), EngineError> {
+        self.sender
+            .send_event(RoomManagerEvent::RemoveRoom(room_id))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn join_room(
+        &self,
+        connection_id: usize,
+        room_id: Option<RoomId>,
+        local_player_count: usize,
+        session_token: Option<SessionToken>,
+    ) -> Result<(), EngineError> {
+        self.sender
+            .send_event(RoomManagerEvent::JoinRoom {
+                connection_id,
+                room_id,
+                local_player_count,
+                session_token,
+            })
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn handle_client_message(
+        &self,
+        connection_id: usize,
+        message: Game::ClientMessage,
+    ) -> Result<(), EngineError> {
+        self.sender
+            .send_event(RoomManagerEvent::ClientMessage(connection_id, message))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn handle_udp_packet(
+        &self,
+        peer_addr: SocketAddr,
+        packet: UdpToServerPacket<Game>,
+    ) -> Result<(), EngineError> {
+        self.sender
+            .send_event(RoomManagerEvent::UdpPacket(peer_addr, packet))
+            .map_err(EngineError::thread_stopped)
+    }
+}
+
+impl<Game: GameTrait> HandleTcpConnection for RoomManager<Game> {
+    fn on_connection(&mut self, tcp_stream: TcpStream, tcp_reader: TcpReader) -> ControlFlow<()> {
+        info!("New TCP connection from {:?}", tcp_stream.get_peer_addr());
+
+        match self
+            .sender
+            .send_event(RoomManagerEvent::TcpConnection(tcp_stream, tcp_reader))
+        {
+            Ok(_) => ControlFlow::Continue(()),
+            Err(_) => {
+                warn!("Error sending TcpConnection to the RoomManager");
+                ControlFlow::Break(())
+            }
+        }
+    }
+}
+
+enum RoomManagerEvent<Game: GameTrait> {
+    TcpConnection(TcpStream, TcpReader),
+    JoinRoom {
+        connection_id: usize,
+        room_id: Option<RoomId>,
+        local_player_count: usize,
+        session_token: Option<SessionToken>,
+    },
+    AddRoom(RoomId, ServerCore<Game>),
+    StartRoom(RoomId, UdpInputHandler<Game>),
+    RemoveRoom(RoomId),
+    UdpPacket(SocketAddr, UdpToServerPacket<Game>),
+    ClientMessage(usize, Game::ClientMessage),
+}
+
+enum Room<Game: GameTrait> {
+    Listening(ServerCore<Game>),
+    Running(UdpInputHandler<Game>),
+}
+
+struct RoomManagerEventHandler<Game: GameTrait> {
+    factory: Factory,
+    room_manager: RoomManager<Game>,
+    next_connection_id: usize,
+    // Connections that have not joined a room yet
+    pending_connections: PendingConnections,
+    // The room each connection joined
+    joined_connections: HashMap<usize, (RoomId, ServerCore<Game>)>,
+    rooms: BTreeMap<RoomId, Room<Game>>,
+}
+
+impl<Game: GameTrait> HandleEvent for RoomManagerEventHandler<Game> {
+    type Event = RoomManagerEvent<Game>;
+    type ThreadReturn = ();
+
+    fn on_event(&mut self, _: ReceiveMetaData, event: Self::Event) -> EventHandleResult {
+        match event {
+            RoomManagerEvent::TcpConnection(tcp_stream, tcp_reader) => {
+                self.on_tcp_connection(tcp_stream, tcp_reader)
+            }
+            RoomManagerEvent::JoinRoom {
+                connection_id,
+                room_id,
+                local_player_count,
+                session_token,
+            } => self.on_join_room(connection_id, room_id, local_player_count, session_token),
+            RoomManagerEvent::AddRoom(room_id, server_core) => {
+                self.rooms.insert(room_id, Room::Listening(server_core));
+                EventHandleResult::TryForNextEvent
+            }
+            RoomManagerEvent::StartRoom(room_id, udp_input_handler) => {
+                self.rooms.insert(room_id, Room::Running(udp_input_handler));
+                EventHandleResult::TryForNextEvent
+            }
+            RoomManagerEvent::RemoveRoom(room_id) => self.on_remove_room(room_id),
+            RoomManagerEvent::UdpPacket(peer_addr, packet) => self.on_udp_packet(peer_addr, packet),
+            RoomManagerEvent::ClientMessage(connection_id, message) => {
+                self.on_client_message(connection_id, message)
+            }
+        }
+    }
+
+    fn on_timeout(&mut self) -> EventHandleResult {
+        return self.drop_timed_out_connections();
+    }
+
+    fn on_channel_empty(&mut self) -> EventHandleResult {
+        return self.drop_timed_out_connections();
+    }
+
+    fn on_stop_self(self) -> Self::ThreadReturn {
+        ()
+    }
+}
+
+impl<Game: GameTrait> RoomManagerEventHandler<Game> {
+    fn drop_timed_out_connections(&mut self) -> EventHandleResult {
+        return match self.pending_connections.drop_timed_out() {
+            Some(deadline) => EventHandleResult::WaitForNextEventOrTimeout(
+                deadline.duration_since(&self.factory.get_time_source().now()),
+            ),
+            None => EventHandleResult::WaitForNextEvent,
+        };
+    }
+
+    fn on_remove_room(&mut self, room_id: RoomId) -> EventHandleResult {
+        if self.rooms.remove(&room_id).is_some() {
+            info!("Removed room {:?}", room_id);
+        }
+
+        self.joined_connections
+            .retain(|_, (joined_room_id, _)| *joined_room_id != room_id);
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_tcp_connection(
+        &mut self,
+        tcp_stream: TcpStream,
+        tcp_reader: TcpReader,
+    ) -> EventHandleResult {
+        if self.pending_connections.is_full() {
+            // Dropping the connection closes it
+            warn!(
+                "Dropping {:?} because too many connections have not joined a room",
+                tcp_stream.get_peer_addr()
+            );
+            return EventHandleResult::TryForNextEvent;
+        }
+
+        let connection_id = self.next_connection_id;
+        self.next_connection_id += 1;
+
+        let tcp_input = match TcpInput::new_room_connection(
+            &self.factory,
+            connection_id,
+            tcp_reader,
+            self.room_manager.clone(),
+        ) {
+            Ok(tcp_input) => tcp_input,
+            Err(err) => {
+                error!("Failed to start TCP input thread: {:?}", err);
+                return EventHandleResult::StopThread;
+            }
+        };
+
+        self.pending_connections
+            .insert(connection_id, tcp_stream, tcp_input);
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_join_room(
+        &mut self,
+        connection_id: usize,
+        room_id: Option<RoomId>,
+        local_player_count: usize,
+        session_token: Option<SessionToken>,
+    ) -> EventHandleResult {
+        let (tcp_stream, tcp_input) = match self.pending_connections.remove(connection_id) {
+            Some(connection) => connection,
+            None => {
+                warn!(
+                    "Connection {:?} asked to join a room more than once",
+                    connection_id
+                );
+                return EventHandleResult::TryForNextEvent;
+            }
+        };
+
+        let room = match room_id {
+            Some(room_id) => match self.rooms.get(&room_id) {
+                Some(Room::Listening(server_core)) => Some((room_id, server_core)),
+                _ => None,
+            },
+            None => self.rooms.iter().find_map(|(room_id, room)| match room {
+                Room::Listening(server_core) => Some((*room_id, server_core)),
+                Room::Running(_) => None,
+            }),
+        };
+
+        let (joined_room_id, server_core) = match room {
+            Some(room) => room,
+            None => {
+                // Dropping the connection closes it
+                warn!(
+                    "No open room for {:?}, requested room: {:?}",
+                    tcp_stream.get_peer_addr(),
+                    room_id
+                );
+                return EventHandleResult::TryForNextEvent;
+            }
+        };
+
+        if server_core
+            .handle_room_connection(
+                tcp_stream,
+                tcp_input,
+                connection_id,
+                local_player_count,
+                session_token,
+            )
+            .is_err()
+        {
+            warn!("Failed to send the connection to the room's ServerCore");
+            return EventHandleResult::TryForNextEvent;
+        }
+
+        self.joined_connections
+            .insert(connection_id, (joined_room_id, server_core.clone()));
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_client_message(
+        &mut self,
+        connection_id: usize,
+        message: Game::ClientMessage,
+    ) -> EventHandleResult {
+        match self.joined_connections.get(&connection_id) {
+            Some((_, server_core)) => {
+                if server_core
+                    .handle_client_message(connection_id, message)
+                    .is_err()
+                {
+                    warn!("Failed to send ClientMessage to the room's ServerCore");
+                    self.joined_connections.remove(&connection_id);
+                }
+            }
+            None => warn!(
+                "Dropped a ClientMessage from connection {:?}, which has not joined a room",
+                connection_id
+            ),
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_udp_packet(
+        &mut self,
+        peer_addr: SocketAddr,
+        packet: UdpToServerPacket<Game>,
+    ) -> EventHandleResult {
+        let room_id = packet.get_room_id();
+
+        let udp_input_handler = match self.rooms.get_mut(&room_id) {
+            Some(Room::Running(udp_input_handler)) => udp_input_handler,
+            _ => {
+                warn!(
+                    "Received a UDP message for a room that is not running: {:?}",
+                    room_id
+                );
+                return EventHandleResult::TryForNextEvent;
+            }
+        };
+
+        if udp_input_handler
+            .on_message(peer_addr, packet.take_message())
+            .is_break()
+        {
+            // The room has stopped, but the other rooms carry on
+            warn!("Removing room {:?}", room_id);
+            self.rooms.remove(&room_id);
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+}
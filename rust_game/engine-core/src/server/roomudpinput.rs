@@ -0,0 +1,118 @@
+use crate::interface::RateLimits;
+use crate::messaging::{
+    FragmentAssembler,
+    MessageFragment,
+    UdpToServerPacket,
+};
+use crate::server::ratelimiter::SourceRateLimiter;
+use crate::server::roommanager::RoomManager;
+use crate::GameTrait;
+use commons::real_time::net::udp::{
+    HandleUdpRead,
+    UdpReadHandlerBuilder,
+    UdpSocket,
+};
+use commons::real_time::{
+    EventHandlerStopper,
+    Factory,
+    TimeSource,
+};
+use log::warn;
+use std::collections::HashMap;
+use std::io::Error;
+use std::net::SocketAddr;
+use std::ops::ControlFlow;
+
+/// Reads the UDP socket shared by all the rooms of a
+/// [RoomServer](crate::RoomServer).  Messages are assembled and deserialized
+/// here and then sent to the [RoomManager], which passes them on to the room
+/// they are addressed to.  Datagrams over the packet and byte budgets of the
+/// [RateLimits] are dropped before they are assembled.
+pub struct RoomUdpInput {
+    _stopper: EventHandlerStopper,
+}
+
+impl RoomUdpInput {
+    pub fn new<Game: GameTrait>(
+        factory: &Factory,
+        udp_socket: &UdpSocket,
+        room_manager: RoomManager<Game>,
+        rate_limits: RateLimits,
+    ) -> Result<Self, Error> {
+        let read_handler = ReadHandler {
+            time_source: factory.get_time_source().clone(),
+            room_manager,
+            source_rate_limiter: SourceRateLimiter::new(
+                factory.get_time_source().clone(),
+                rate_limits,
+            ),
+            fragment_assemblers: HashMap::new(),
+        };
+
+        let stopper = UdpReadHandlerBuilder::new_thread(
+            factory,
+            "RoomServerUdpInput".to_string(),
+            udp_socket.try_clone()?,
+            read_handler,
+        )?;
+
+        Ok(Self { _stopper: stopper })
+    }
+}
+
+struct ReadHandler<Game: GameTrait> {
+    time_source: TimeSource,
+    room_manager: RoomManager<Game>,
+    source_rate_limiter: SourceRateLimiter<Game>,
+
+    //TODO: timeout fragments or fragment assemblers
+    fragment_assemblers: HashMap<SocketAddr, FragmentAssembler>,
+}
+
+impl<Game: GameTrait> HandleUdpRead for ReadHandler<Game> {
+    fn on_read(&mut self, peer_addr: SocketAddr, buf: &[u8]) -> ControlFlow<()> {
+        if !self
+            .source_rate_limiter
+            .allow_datagram(peer_addr, buf.len())
+        {
+            return ControlFlow::Continue(());
+        }
+
+        let assembler = self
+            .fragment_assemblers
+            .entry(peer_addr)
+            .or_insert_with(|| FragmentAssembler::new(self.time_source.clone(), 5));
+
+        let result = MessageFragment::from_vec(buf.to_vec())
+            .and_then(|fragment| assembler.add_fragment(fragment));
+
+        let assembled = match result {
+            Ok(Some(assembled)) => assembled,
+            Ok(None) => return ControlFlow::Continue(()),
+            Err(error) => {
+                warn!(
+                    "Dropping a malformed fragment from {:?}: {:?}",
+                    peer_addr, error
+                );
+                return ControlFlow::Continue(());
+            }
+        };
+
+        let packet = match rmp_serde::from_slice::<UdpToServerPacket<Game>>(assembled.as_slice()) {
+            Ok(packet) => packet,
+            Err(error) => {
+                self.fragment_assemblers.remove(&peer_addr);
+                warn!("Failed to deserialize a UdpToServerPacket: {:?}", error);
+                return ControlFlow::Continue(());
+            }
+        };
+
+        match self.room_manager.handle_udp_packet(peer_addr, packet) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(_) => {
+                warn!("Error sending UdpToServerPacket to the RoomManager");
+                ControlFlow::Break(())
+            }
+        }
+    }
+}
@@ -9,6 +9,8 @@ use crate::{
         FrameDuration,
         StartTime,
     },
+    server::RoomId,
+    DeterministicRng,
     FrameIndex,
     GameTrait,
 };
@@ -18,18 +20,43 @@ pub struct ServerConfig {
     start_time: StartTime,
     frame_duration: FrameDuration,
     input_grace_period_frames: usize,
+    session_seed: u64,
+    room_id: RoomId,
 }
 
 impl ServerConfig {
-    pub fn new<Game: GameTrait>(factory: &Factory) -> Self {
+    /// Creates the [ServerConfig] of a session that starts now.  The session
+    /// seed is derived from the start time and the [RoomId], so a session run
+    /// with a simulated time source is reproducible.
+    pub fn new<Game: GameTrait>(factory: &Factory, room_id: RoomId) -> Self {
         let now = factory.get_time_source().now();
         let frame_duration = FrameDuration::new(Game::STEP_PERIOD);
         let input_grace_period_frames = frame_duration.to_frame_count(&Game::GRACE_PERIOD) as usize;
+        let session_seed =
+            DeterministicRng::new(now.as_secs_f64().to_bits() ^ room_id.get_id() as u64).next_u64();
 
         return Self {
             start_time: StartTime::new(now),
             frame_duration,
             input_grace_period_frames,
+            session_seed,
+            room_id,
+        };
+    }
+
+    /// Creates the [ServerConfig] of a session that resumes a saved one at
+    /// `frame_index`.  The session keeps the saved seed so the
+    /// [DeterministicRng](crate::DeterministicRng) continues where it left off.
+    pub(crate) fn new_resumed(
+        factory: &Factory,
+        saved_server_config: &ServerConfig,
+        frame_index: FrameIndex,
+    ) -> Self {
+        let now = factory.get_time_source().now();
+
+        return Self {
+            start_time: StartTime::new_at_frame(now, frame_index),
+            ..*saved_server_config
         };
     }
 
@@ -41,10 +68,33 @@ impl ServerConfig {
         &self.start_time
     }
 
+    /// Returns the [FrameIndex] of the initial state of this session, which is
+    /// 0 unless it resumed a [ServerSnapshot](crate::ServerSnapshot)
+    pub fn get_first_frame_index(&self) -> FrameIndex {
+        self.start_time.get_first_frame_index()
+    }
+
     pub fn get_input_grace_period_frames(&self) -> usize {
         self.input_grace_period_frames
     }
 
+    #[cfg(test)]
+    pub(crate) fn set_input_grace_period_frames(&mut self, input_grace_period_frames: usize) {
+        self.input_grace_period_frames = input_grace_period_frames;
+    }
+
+    /// Returns the seed shared by the server and all clients for this session.
+    /// It is used to seed the [DeterministicRng](crate::DeterministicRng) for
+    /// each frame.
+    pub fn get_session_seed(&self) -> u64 {
+        self.session_seed
+    }
+
+    /// Returns the [RoomId] of the room this game is hosted in
+    pub fn get_room_id(&self) -> RoomId {
+        self.room_id
+    }
+
     /// Returns the last frame index which is still open for clients to submit inputs
     pub fn get_last_open_frame_index(&self, current_frame_index: FrameIndex) -> FrameIndex {
         if current_frame_index.usize() > self.input_grace_period_frames {
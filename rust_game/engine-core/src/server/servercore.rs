@@ -1,19 +1,41 @@
 use crate::frame_manager::FrameManager;
 use crate::game_time::GameTimerScheduler;
 use crate::interface::{
+    EngineError,
+    FrameObserver,
     GameTrait,
     InitialInformation,
+    InputEventHandler,
+    RateLimits,
     RenderReceiverMessage,
+    SavedClient,
+    ServerSnapshot,
+    ServerState,
+    SessionToken,
+};
+use crate::messaging::{
+    DiscoveryResponse,
+    FrameIndexAndState,
+    ServerInputMessage,
+    ToClientInputMessage,
+    ToServerInputMessage,
 };
-use crate::messaging::ToServerInputMessage;
 use crate::server::clientaddress::ClientAddress;
+use crate::server::discoveryresponder::DiscoveryResponder;
+use crate::server::pendingconnections::PendingConnections;
+use crate::server::roommanager::RoomManager;
 use crate::server::servermanagerobserver::ServerManagerObserver;
 use crate::server::tcpinput::TcpInput;
 use crate::server::tcpoutput::TcpOutput;
 use crate::server::udphandler::UdpHandler;
-use crate::server::udpinput::UdpInput;
+use crate::server::udpinput::{
+    UdpInput,
+    UdpInputHandler,
+};
 use crate::server::udpoutput::UdpOutput;
 use crate::server::{
+    DroppedTrafficCounts,
+    RoomId,
     ServerConfig,
     TcpConnectionHandler,
 };
@@ -23,6 +45,7 @@ use commons::real_time::net::tcp::{
     TcpReader,
     TcpStream,
 };
+use commons::real_time::net::udp::UdpSocket;
 use commons::real_time::timer_service::{
     IdleTimerService,
     TimerCallBack,
@@ -38,20 +61,24 @@ use commons::real_time::{
     ReceiveMetaData,
     Sender,
 };
-use commons::utils::unit_error;
+use commons::time::TimeValue;
 use log::{
+    debug,
     error,
     info,
     warn,
 };
-use std::io::Error;
+use std::collections::{
+    HashMap,
+    VecDeque,
+};
 use std::mem::take;
 use std::net::{
     Ipv4Addr,
     SocketAddr,
     SocketAddrV4,
 };
-use std::str::FromStr;
+use std::path::PathBuf;
 
 #[derive(Clone)]
 pub struct ServerCore<Game: GameTrait> {
@@ -59,10 +86,77 @@ pub struct ServerCore<Game: GameTrait> {
 }
 
 impl<Game: GameTrait> ServerCore<Game> {
+    /// Starts a standalone [ServerCore] listening on `ip_addr`.  With a
+    /// `resumed_snapshot`, the core only takes back the clients saved in it
+    /// and continues its match.
     pub fn new(
+        factory: Factory,
+        ip_addr: Ipv4Addr,
+        render_receiver_sender: Sender<RenderReceiverMessage<Game>>,
+        client_message_sender: Sender<(usize, Game::ClientMessage)>,
+        start_error_sender: Sender<EngineError>,
+        resumed_snapshot: Option<ServerSnapshot<Game>>,
+    ) -> Result<Self, EngineError> {
+        let builder = EventHandlerBuilder::new(&factory);
+
+        let server_core = Self {
+            sender: builder.get_sender().clone(),
+        };
+
+        // The UDP socket is bound now so that a port in use is reported to
+        // the caller instead of failing when the game starts
+        let udp_socket = factory
+            .bind_udp_socket(SocketAddr::V4(SocketAddrV4::new(ip_addr, Game::UDP_PORT)))
+            .map_err(EngineError::Bind)?;
+
+        //Bind to TcpListener Socket
+        let socket_addr_v4 = SocketAddrV4::new(ip_addr, Game::TCP_PORT);
+        let socket_addr = SocketAddr::from(socket_addr_v4);
+
+        //TODO: maybe use a builder and spawn all the threads together
+        // This spawns the tcp listener thread before the core thread is spawned
+        let tcp_listener_stopper = TcpListenerBuilder::new_thread(
+            &factory,
+            "ServerTcpListener".to_string(),
+            socket_addr,
+            TcpConnectionHandler::<Game>::new(server_core.clone()),
+        )
+        .map_err(EngineError::Bind)?;
+
+        let event_handler = ServerCoreEventHandler::new(
+            factory,
+            server_core.clone(),
+            render_receiver_sender.clone(),
+            client_message_sender,
+            start_error_sender,
+            RoomId::default(),
+            Transport::Standalone {
+                udp_socket,
+                _tcp_listener_stopper: tcp_listener_stopper,
+            },
+            resumed_snapshot,
+        );
+
+        builder
+            .spawn_thread("ServerCore".to_string(), event_handler)
+            .map_err(EngineError::Io)?;
+
+        Ok(server_core)
+    }
+
+    /// Starts a [ServerCore] for one room of a [RoomServer](crate::RoomServer).
+    /// The room shares the TCP listener and UDP socket of the
+    /// [RoomServer](crate::RoomServer), which routes its clients through the
+    /// [RoomManager].
+    pub fn new_room(
         factory: Factory,
         render_receiver_sender: Sender<RenderReceiverMessage<Game>>,
-    ) -> Result<Self, Error> {
+        client_message_sender: Sender<(usize, Game::ClientMessage)>,
+        start_error_sender: Sender<EngineError>,
+        room_id: RoomId,
+        udp_socket: UdpSocket,
+        room_manager: RoomManager<Game>,
+    ) -> Result<Self, EngineError> {
         let builder = EventHandlerBuilder::new(&factory);
 
         let server_core = Self {
@@ -73,36 +167,227 @@ impl<Game: GameTrait> ServerCore<Game> {
             factory,
             server_core.clone(),
             render_receiver_sender.clone(),
-        )?;
+            client_message_sender,
+            start_error_sender,
+            room_id,
+            Transport::Room {
+                udp_socket,
+                room_manager,
+            },
+            None,
+        );
 
-        builder.spawn_thread("ServerCore".to_string(), event_handler)?;
+        builder
+            .spawn_thread(
+                format!("ServerCore-Room-{}", room_id.get_id()),
+                event_handler,
+            )
+            .map_err(EngineError::Io)?;
 
         Ok(server_core)
     }
 
-    pub fn start_game(&self) -> Result<(), ()> {
+    pub fn start_game(&self) -> Result<(), EngineError> {
         self.sender
             .send_event(ServerCoreEvent::StartGameEvent)
-            .map_err(unit_error)
+            .map_err(EngineError::thread_stopped)
     }
 
     pub fn handle_tcp_connection(
         &self,
         tcp_stream: TcpStream,
         tcp_reader: TcpReader,
-    ) -> Result<(), ()> {
+    ) -> Result<(), EngineError> {
         self.sender
             .send_event(ServerCoreEvent::TcpConnectionEvent(tcp_stream, tcp_reader))
-            .map_err(unit_error)
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Adds the local players of a client that connected to this standalone
+    /// server
+    pub fn handle_join(
+        &self,
+        connection_id: usize,
+        local_player_count: usize,
+        session_token: Option<SessionToken>,
+    ) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::JoinEvent(
+                connection_id,
+                local_player_count,
+                session_token,
+            ))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Adds a client that joined this room through the [RoomManager]
+    pub fn handle_room_connection(
+        &self,
+        tcp_stream: TcpStream,
+        tcp_input: TcpInput,
+        connection_id: usize,
+        local_player_count: usize,
+        session_token: Option<SessionToken>,
+    ) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::RoomConnectionEvent(
+                tcp_stream,
+                tcp_input,
+                connection_id,
+                local_player_count,
+                session_token,
+            ))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Lets the [StandbyServer](crate::StandbyServer) on `connection_id`
+    /// follow the match if it presented the standby token
+    pub fn handle_standby(
+        &self,
+        connection_id: usize,
+        standby_token: SessionToken,
+    ) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::StandbyEvent(connection_id, standby_token))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn enable_standby(&self, standby_token: SessionToken) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::EnableStandby(standby_token))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Stops the core, which closes the connections to its clients
+    pub fn stop(&self) -> Result<(), EngineError> {
+        self.sender
+            .send_stop_thread()
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Passes a message received from the client on `connection_id` to the game
+    pub fn handle_client_message(
+        &self,
+        connection_id: usize,
+        message: Game::ClientMessage,
+    ) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::ClientMessage(connection_id, message))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn send_message(
+        &self,
+        player_index: usize,
+        message: Game::ServerMessage,
+    ) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::SendMessage(player_index, message))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn broadcast_message(&self, message: Game::ServerMessage) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::BroadcastMessage(message))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Starts answering LAN discovery probes
+    pub fn enable_discovery(&self) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::EnableDiscovery)
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn handle_discovery_probe(&self, peer_addr: SocketAddr) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::DiscoveryProbe(peer_addr))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn set_rate_limits(&self, rate_limits: RateLimits) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::SetRateLimits(rate_limits))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Disconnects a player that went over the [RateLimits]
+    pub fn kick_player(&self, player_index: usize) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::KickPlayer(player_index))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Passes the traffic dropped by the [RateLimits] on to the
+    /// [FrameObservers](FrameObserver)
+    pub fn handle_dropped_traffic(&self, counts: DroppedTrafficCounts) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::DroppedTraffic(counts))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn handle_server_input(&self, server_input: Game::ServerInput) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::ServerInput(server_input))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn add_frame_observer(
+        &self,
+        frame_observer: Box<dyn FrameObserver<Game>>,
+    ) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::AddFrameObserver(frame_observer))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn set_local_player(
+        &self,
+        input_event_handler: Box<dyn InputEventHandler<Game = Game>>,
+    ) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::SetLocalPlayer(input_event_handler))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn handle_local_input_event(
+        &self,
+        client_input_event: Game::ClientInputEvent,
+    ) -> Result<(), Game::ClientInputEvent> {
+        return match self
+            .sender
+            .send_event(ServerCoreEvent::LocalInputEvent(client_input_event))
+        {
+            Ok(()) => Ok(()),
+            Err(ServerCoreEvent::LocalInputEvent(client_input_event)) => Err(client_input_event),
+            _ => panic!("This should never happen."),
+        };
     }
 
     pub fn handle_input_message(
         &self,
         input_message: ToServerInputMessage<Game>,
-    ) -> Result<(), ()> {
+    ) -> Result<(), EngineError> {
         self.sender
             .send_event(ServerCoreEvent::InputMessage(input_message))
-            .map_err(unit_error)
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Keeps the latest authoritative state so it can be saved in a
+    /// [ServerSnapshot]
+    pub fn handle_authoritative_state(
+        &self,
+        state_message: FrameIndexAndState<Game>,
+    ) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::AuthoritativeState(state_message))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn save_snapshot(&self, path: PathBuf) -> Result<(), EngineError> {
+        self.sender
+            .send_event(ServerCoreEvent::SaveSnapshot(path))
+            .map_err(EngineError::thread_stopped)
     }
 }
 
@@ -122,20 +407,92 @@ impl<Game: GameTrait> TimerCallBack for ServerCore<Game> {
 enum ServerCoreEvent<Game: GameTrait> {
     StartGameEvent,
     TcpConnectionEvent(TcpStream, TcpReader),
+    JoinEvent(usize, usize, Option<SessionToken>),
+    RoomConnectionEvent(TcpStream, TcpInput, usize, usize, Option<SessionToken>),
+    StandbyEvent(usize, SessionToken),
+    ClientMessage(usize, Game::ClientMessage),
+    SendMessage(usize, Game::ServerMessage),
+    BroadcastMessage(Game::ServerMessage),
     GameTimerTick,
     InputMessage(ToServerInputMessage<Game>),
+    ServerInput(Game::ServerInput),
+    AddFrameObserver(Box<dyn FrameObserver<Game>>),
+    SetLocalPlayer(Box<dyn InputEventHandler<Game = Game>>),
+    LocalInputEvent(Game::ClientInputEvent),
+    EnableDiscovery,
+    DiscoveryProbe(SocketAddr),
+    SetRateLimits(RateLimits),
+    KickPlayer(usize),
+    DroppedTraffic(DroppedTrafficCounts),
+    AuthoritativeState(FrameIndexAndState<Game>),
+    SaveSnapshot(PathBuf),
+    EnableStandby(SessionToken),
 }
 
 struct ServerCoreEventHandler<Game: GameTrait> {
     factory: Factory,
     server_core: ServerCore<Game>,
     render_receiver_sender: Sender<RenderReceiverMessage<Game>>,
-    tcp_listener_sender: EventHandlerStopper,
-    tcp_inputs: Vec<TcpInput>,
-    tcp_outputs: Vec<TcpOutput<Game>>,
+    client_message_sender: Sender<(usize, Game::ClientMessage)>,
+    // Reports a failure to start the game to the Server
+    start_error_sender: Sender<EngineError>,
+    room_id: RoomId,
+    transport: Transport<Game>,
+    next_connection_id: usize,
+    // Standalone connections that have not said how many players they have
+    pending_connections: PendingConnections,
+    // The clients that joined, in the order of their player indices
+    connections: Vec<RemoteConnection<Game>>,
+    pending_server_inputs: Vec<Game::ServerInput>,
+    // Frame observers added before the game starts
+    pending_frame_observers: Vec<Box<dyn FrameObserver<Game>>>,
+    // The input handler of the player hosting the game in this process
+    local_player: Option<Box<dyn InputEventHandler<Game = Game>>>,
+    discovery_responder: Option<DiscoveryResponder>,
+    rate_limits: RateLimits,
+    // The match this core resumes, until the game starts
+    resumed_snapshot: Option<ServerSnapshot<Game>>,
+    // When a resumed match stops waiting for its players to rejoin
+    rejoin_deadline: Option<TimeValue>,
+    // The token a StandbyServer presents to follow the match
+    standby_token: Option<SessionToken>,
+    standbys: Vec<StandbyConnection<Game>>,
     state: State<Game>,
 }
 
+/// A client with one or more local players
+struct RemoteConnection<Game: GameTrait> {
+    connection_id: usize,
+    player_indices: Vec<usize>,
+    // Identifies the client if it reconnects to a resumed match
+    session_token: SessionToken,
+    _tcp_input: TcpInput,
+    tcp_output: TcpOutput<Game>,
+}
+
+/// A [StandbyServer](crate::StandbyServer) that is sent a snapshot of the
+/// match, and then the authoritative state of every frame
+struct StandbyConnection<Game: GameTrait> {
+    _tcp_input: TcpInput,
+    tcp_output: TcpOutput<Game>,
+    has_snapshot: bool,
+}
+
+/// How a [ServerCore] is connected to its clients
+enum Transport<Game: GameTrait> {
+    /// The core has its own TCP listener and UDP socket
+    Standalone {
+        udp_socket: UdpSocket,
+        _tcp_listener_stopper: EventHandlerStopper,
+    },
+    /// The core is one room of a [RoomServer](crate::RoomServer) and shares
+    /// its TCP listener and UDP socket with the other rooms
+    Room {
+        udp_socket: UdpSocket,
+        room_manager: RoomManager<Game>,
+    },
+}
+
 #[derive(Default)]
 enum State<Game: GameTrait> {
     Listening(ListeningCore<Game>),
@@ -152,9 +509,26 @@ struct RunningCore<Game: GameTrait> {
     server_config: ServerConfig,
     _timer_service: TimerService<(), ServerCore<Game>>,
     game_timer: GameTimerScheduler,
-    _udp_input: UdpInput,
+    // Rooms of a RoomServer receive their UDP messages from the RoomManager
+    _udp_input: Option<UdpInput>,
+    // One per connection
     udp_output_senders: Vec<UdpOutput<Game>>,
     frame_manager: FrameManager<Game>,
+
+    /// The next FrameIndex that server inputs have not been sent for
+    next_server_input_frame_index: FrameIndex,
+
+    /// The server inputs of the frames still open for inputs, up to
+    /// `next_server_input_frame_index`.  They are resent on every frame.
+    open_server_inputs: VecDeque<Vec<Game::ServerInput>>,
+
+    /// The player index of the local player, if there is one
+    local_player_index: Option<usize>,
+
+    player_count: usize,
+
+    /// The latest authoritative state, which is saved in snapshots
+    latest_authoritative_state: Option<FrameIndexAndState<Game>>,
 }
 
 impl<Game: GameTrait> HandleEvent for ServerCoreEventHandler<Game> {
@@ -167,13 +541,71 @@ impl<Game: GameTrait> HandleEvent for ServerCoreEventHandler<Game> {
             ServerCoreEvent::TcpConnectionEvent(tcp_stream, tcp_reader) => {
                 self.on_tcp_connection(tcp_stream, tcp_reader)
             }
+            ServerCoreEvent::JoinEvent(connection_id, local_player_count, session_token) => {
+                self.on_join(connection_id, local_player_count, session_token)
+            }
+            ServerCoreEvent::RoomConnectionEvent(
+                tcp_stream,
+                tcp_input,
+                connection_id,
+                local_player_count,
+                session_token,
+            ) => self.add_players(
+                tcp_stream,
+                tcp_input,
+                connection_id,
+                local_player_count,
+                session_token,
+            ),
+            ServerCoreEvent::StandbyEvent(connection_id, standby_token) => {
+                self.on_standby(connection_id, standby_token)
+            }
+            ServerCoreEvent::ClientMessage(connection_id, message) => {
+                self.on_client_message(connection_id, message)
+            }
+            ServerCoreEvent::SendMessage(player_index, message) => {
+                self.on_send_message(player_index, message)
+            }
+            ServerCoreEvent::BroadcastMessage(message) => self.on_broadcast_message(message),
             ServerCoreEvent::GameTimerTick => self.on_game_timer_tick(),
             ServerCoreEvent::InputMessage(input_message) => self.on_input_message(input_message),
+            ServerCoreEvent::ServerInput(server_input) => self.on_server_input(server_input),
+            ServerCoreEvent::AddFrameObserver(frame_observer) => {
+                self.on_add_frame_observer(frame_observer)
+            }
+            ServerCoreEvent::SetLocalPlayer(input_event_handler) => {
+                self.on_set_local_player(input_event_handler)
+            }
+            ServerCoreEvent::LocalInputEvent(client_input_event) => {
+                self.on_local_input_event(client_input_event)
+            }
+            ServerCoreEvent::EnableDiscovery => self.on_enable_discovery(),
+            ServerCoreEvent::DiscoveryProbe(peer_addr) => self.on_discovery_probe(peer_addr),
+            ServerCoreEvent::SetRateLimits(rate_limits) => self.on_set_rate_limits(rate_limits),
+            ServerCoreEvent::KickPlayer(player_index) => self.on_kick_player(player_index),
+            ServerCoreEvent::DroppedTraffic(counts) => self.on_dropped_traffic(counts),
+            ServerCoreEvent::AuthoritativeState(state_message) => {
+                self.on_authoritative_state(state_message)
+            }
+            ServerCoreEvent::SaveSnapshot(path) => self.on_save_snapshot(path),
+            ServerCoreEvent::EnableStandby(standby_token) => self.on_enable_standby(standby_token),
         }
     }
 
+    fn on_timeout(&mut self) -> EventHandleResult {
+        return self.on_none_pending();
+    }
+
+    fn on_channel_empty(&mut self) -> EventHandleResult {
+        return self.on_none_pending();
+    }
+
     fn on_stop_self(self) -> Self::ThreadReturn {
-        ()
+        if let Transport::Room { room_manager, .. } = &self.transport {
+            if room_manager.remove_room(self.room_id).is_err() {
+                warn!("Failed to remove the room from the RoomManager");
+            }
+        }
     }
 }
 
@@ -182,33 +614,72 @@ impl<Game: GameTrait> ServerCoreEventHandler<Game> {
         factory: Factory,
         server_core: ServerCore<Game>,
         render_receiver_sender: Sender<RenderReceiverMessage<Game>>,
-    ) -> Result<Self, Error> {
+        client_message_sender: Sender<(usize, Game::ClientMessage)>,
+        start_error_sender: Sender<EngineError>,
+        room_id: RoomId,
+        transport: Transport<Game>,
+        resumed_snapshot: Option<ServerSnapshot<Game>>,
+    ) -> Self {
         let udp_handler = UdpHandler::<Game>::new(factory.get_time_source().clone());
 
         let listening_core = ListeningCore { udp_handler };
 
-        //Bind to TcpListener Socket
-        let socket_addr_v4 = SocketAddrV4::new(Ipv4Addr::new(127, 0, 0, 1), Game::TCP_PORT);
-        let socket_addr = SocketAddr::from(socket_addr_v4);
+        let pending_connections = PendingConnections::new(factory.get_time_source().clone());
 
-        //TODO: maybe use a builder and spawn all the threads together
-        // This spawns the tcp listener thread before the core thread is spawned
-        let tcp_listener_sender = TcpListenerBuilder::new_thread(
-            &factory,
-            "ServerTcpListener".to_string(),
-            socket_addr,
-            TcpConnectionHandler::<Game>::new(server_core.clone()),
-        )?;
+        let rejoin_deadline = resumed_snapshot
+            .as_ref()
+            .map(|_| factory.get_time_source().now() + Game::REJOIN_TIMEOUT);
 
-        Ok(Self {
+        Self {
             factory,
             server_core,
             render_receiver_sender,
-            tcp_listener_sender,
-            tcp_inputs: Vec::new(),
-            tcp_outputs: Vec::new(),
+            client_message_sender,
+            start_error_sender,
+            room_id,
+            transport,
+            next_connection_id: 0,
+            pending_connections,
+            connections: Vec::new(),
+            pending_server_inputs: Vec::new(),
+            pending_frame_observers: Vec::new(),
+            local_player: None,
+            discovery_responder: None,
+            rate_limits: RateLimits::default(),
+            resumed_snapshot,
+            rejoin_deadline,
+            standby_token: None,
+            standbys: Vec::new(),
             state: State::Listening(listening_core),
-        })
+        }
+    }
+
+    /// Drops the connections that have not finished their handshake in time,
+    /// and continues a resumed match whose players have not rejoined in time
+    fn on_none_pending(&mut self) -> EventHandleResult {
+        let now = self.factory.get_time_source().now();
+
+        if let Some(rejoin_deadline) = self.rejoin_deadline {
+            if !rejoin_deadline.is_after(&now) {
+                warn!("Not all the players of the resumed match rejoined in time, continuing without them");
+                return self.start_game();
+            }
+        }
+
+        let next_deadline = [
+            self.pending_connections.drop_timed_out(),
+            self.rejoin_deadline,
+        ]
+        .into_iter()
+        .flatten()
+        .min_by(|a, b| a.as_secs_f64().total_cmp(&b.as_secs_f64()));
+
+        return match next_deadline {
+            Some(next_deadline) => {
+                EventHandleResult::WaitForNextEventOrTimeout(next_deadline.duration_since(&now))
+            }
+            None => EventHandleResult::WaitForNextEvent,
+        };
     }
 
     fn start_game(&mut self) -> EventHandleResult {
@@ -220,57 +691,111 @@ impl<Game: GameTrait> ServerCoreEventHandler<Game> {
             }
         };
 
+        self.rejoin_deadline = None;
+
         //Start UDP
-        let ip_addr_v4 = match Ipv4Addr::from_str("127.0.0.1") {
-            Ok(ip_addr_v4) => ip_addr_v4,
-            Err(error) => {
-                error!("{:?}", error);
-                return EventHandleResult::StopThread;
+        let udp_socket = match &self.transport {
+            Transport::Standalone { udp_socket, .. } | Transport::Room { udp_socket, .. } => {
+                udp_socket.try_clone()
             }
         };
 
-        let socket_addr = SocketAddr::V4(SocketAddrV4::new(ip_addr_v4, Game::UDP_PORT));
-
-        let udp_socket = match self.factory.bind_udp_socket(socket_addr) {
+        let udp_socket = match udp_socket {
             Ok(udp_socket) => udp_socket,
-            Err(error) => {
-                error!("{:?}", error);
-                return EventHandleResult::StopThread;
-            }
+            Err(error) => return self.fail_to_start(EngineError::Io(error)),
         };
 
+        // Each connection has one UdpOutput, which is shared by the player
+        // indices of its local players
         let mut udp_outputs = Vec::new();
-        for player_index in 0..self.tcp_inputs.len() {
-            let result = UdpOutput::new(self.factory.clone(), player_index, &udp_socket);
+        let mut player_udp_outputs = HashMap::new();
+        for connection in self.connections.iter() {
+            let result = UdpOutput::new(
+                self.factory.clone(),
+                connection.player_indices.clone(),
+                &udp_socket,
+            );
 
-            match result {
-                Ok(udp_output) => udp_outputs.push(udp_output),
-                Err(err) => {
-                    error!("Failed to create UdpOutput: {:?}", err);
-                    return EventHandleResult::StopThread;
-                }
+            let udp_output = match result {
+                Ok(udp_output) => udp_output,
+                Err(err) => return self.fail_to_start(EngineError::Io(err)),
+            };
+
+            for player_index in connection.player_indices.iter() {
+                player_udp_outputs.insert(*player_index, udp_output.clone());
             }
+
+            udp_outputs.push(udp_output);
         }
 
-        let result = UdpInput::new(
-            &self.factory,
+        let udp_input_handler = UdpInputHandler::new(
+            self.factory.get_time_source().clone(),
             self.server_core.clone(),
-            &udp_socket,
             listening_core.udp_handler,
-            udp_outputs.clone(),
+            player_udp_outputs,
+            self.rate_limits,
         );
 
-        let udp_input = match result {
-            Ok(udp_input) => udp_input,
-            Err(error) => {
-                error!("Failed to create UdpInput: {:?}", error);
-                return EventHandleResult::StopThread;
+        let udp_input = match &self.transport {
+            Transport::Standalone { .. } => {
+                match UdpInput::new(&self.factory, &udp_socket, udp_input_handler) {
+                    Ok(udp_input) => Some(udp_input),
+                    Err(error) => return self.fail_to_start(EngineError::Io(error)),
+                }
             }
-        };
+            Transport::Room { room_manager, .. } => {
+                if let Err(error) = room_manager.start_room(self.room_id, udp_input_handler) {
+                    return self.fail_to_start(error);
+                }
 
-        let server_config = ServerConfig::new::<Game>(&self.factory);
+                None
+            }
+        };
 
-        let initial_state = Game::get_initial_state(self.tcp_outputs.len());
+        let (server_config, player_count, local_player_index, initial_state) =
+            match self.resumed_snapshot.take() {
+                // A resumed match continues from the saved state with the saved
+                // players, even those that have not rejoined
+                Some(snapshot) => {
+                    let local_player_index = match self.local_player {
+                        Some(_) => snapshot.get_local_player_index(),
+                        None => None,
+                    };
+
+                    if self.local_player.is_some() && local_player_index.is_none() {
+                        warn!("The resumed match has no local player");
+                    }
+
+                    (
+                        ServerConfig::new_resumed(
+                            &self.factory,
+                            snapshot.get_server_config(),
+                            snapshot.get_frame_index(),
+                        ),
+                        snapshot.get_player_count(),
+                        local_player_index,
+                        snapshot.get_state().clone(),
+                    )
+                }
+                None => {
+                    // The local player, if there is one, takes the player index
+                    // after all the remote players.
+                    let remote_player_count = self.get_remote_player_count();
+                    let local_player_index =
+                        self.local_player.as_ref().map(|_| remote_player_count);
+                    let player_count = match local_player_index {
+                        Some(_) => remote_player_count + 1,
+                        None => remote_player_count,
+                    };
+
+                    (
+                        ServerConfig::new::<Game>(&self.factory, self.room_id),
+                        player_count,
+                        local_player_index,
+                        Game::get_initial_state(player_count),
+                    )
+                }
+            };
 
         let mut idle_timer_service = IdleTimerService::new();
 
@@ -283,24 +808,18 @@ impl<Game: GameTrait> ServerCoreEventHandler<Game> {
 
         let timer_service = match idle_timer_service.start(&self.factory) {
             Ok(timer_service) => timer_service,
-            Err(err) => {
-                warn!("Failed to Start the TimerService: {:?}", err);
-                return EventHandleResult::StopThread;
-            }
+            Err(err) => return self.fail_to_start(EngineError::Io(err)),
         };
 
         let (start_time, frame_index) = match game_timer.start_server_timer(&timer_service) {
             Ok(result) => result,
-            Err(err) => {
-                warn!("Failed to Start the GameTimer: {:?}", err);
-                return EventHandleResult::StopThread;
-            }
+            Err(()) => return self.fail_to_start(EngineError::ThreadStopped),
         };
 
         let server_initial_information = InitialInformation::<Game>::new(
             server_config.clone(),
-            self.tcp_outputs.len(),
-            usize::MAX,
+            player_count,
+            local_player_index.into_iter().collect(),
             initial_state.clone(),
         );
 
@@ -324,11 +843,17 @@ impl<Game: GameTrait> ServerCoreEventHandler<Game> {
             return EventHandleResult::StopThread;
         }
 
-        for tcp_output in self.tcp_outputs.iter() {
-            let send_result = tcp_output.send_initial_information(
+        for connection in self.connections.iter() {
+            let initial_state = if Game::IS_STATE_FILTERED {
+                Game::filter_state_for_player(&initial_state, &connection.player_indices)
+            } else {
+                initial_state.clone()
+            };
+
+            let send_result = connection.tcp_output.send_initial_information(
                 server_config.clone(),
-                self.tcp_outputs.len(),
-                initial_state.clone(),
+                player_count,
+                initial_state,
             );
 
             if send_result.is_err() {
@@ -338,29 +863,46 @@ impl<Game: GameTrait> ServerCoreEventHandler<Game> {
         }
 
         let server_manager_observer = ServerManagerObserver::<Game>::new(
+            self.server_core.clone(),
             udp_outputs.clone(),
             self.render_receiver_sender.clone(),
         );
 
-        let frame_manager = FrameManager::new(
+        let frame_manager = match FrameManager::new(
             &self.factory,
             server_manager_observer,
+            take(&mut self.pending_frame_observers),
             server_initial_information,
-        )
-        .unwrap();
+        ) {
+            Ok(frame_manager) => frame_manager,
+            Err(error) => return self.fail_to_start(error),
+        };
 
         self.state = State::Running(RunningCore {
+            next_server_input_frame_index: server_config.get_first_frame_index(),
+            open_server_inputs: VecDeque::new(),
             server_config,
             _timer_service: timer_service,
             game_timer,
             _udp_input: udp_input,
             udp_output_senders: udp_outputs,
             frame_manager,
+            local_player_index,
+            player_count,
+            latest_authoritative_state: None,
         });
 
         return self.send_new_frame_index(frame_index);
     }
 
+    /// Reports a failure to start the game to the [Server](crate::Server) and
+    /// stops the core
+    fn fail_to_start(&self, error: EngineError) -> EventHandleResult {
+        error!("Failed to start the game: {:?}", error);
+        let _ = self.start_error_sender.send(error);
+        return EventHandleResult::StopThread;
+    }
+
     /*
     TODO:
     Server      Client
@@ -372,6 +914,83 @@ impl<Game: GameTrait> ServerCoreEventHandler<Game> {
         tcp_stream: TcpStream,
         tcp_reader: TcpReader,
     ) -> EventHandleResult {
+        // Connections are accepted after the game starts because a standby
+        // server can start following at any time.  Clients that join after
+        // the game starts are dropped.
+        info!("TcpStream accepted: {:?}", tcp_stream.get_peer_addr());
+
+        if self.pending_connections.is_full() {
+            // Dropping the connection closes it
+            warn!(
+                "Dropping {:?} because too many connections have not finished their handshake",
+                tcp_stream.get_peer_addr()
+            );
+            return EventHandleResult::TryForNextEvent;
+        }
+
+        let connection_id = self.next_connection_id;
+        self.next_connection_id += 1;
+
+        let tcp_input = match TcpInput::new(
+            &self.factory,
+            connection_id,
+            tcp_reader,
+            self.server_core.clone(),
+        ) {
+            Ok(tcp_input) => tcp_input,
+            Err(err) => {
+                error!("Failed to start TCP input thread: {:?}", err);
+                return EventHandleResult::StopThread;
+            }
+        };
+
+        // The players are added once the client says how many it has, or
+        // whether it is a standby server
+        self.pending_connections
+            .insert(connection_id, tcp_stream, tcp_input);
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_join(
+        &mut self,
+        connection_id: usize,
+        local_player_count: usize,
+        session_token: Option<SessionToken>,
+    ) -> EventHandleResult {
+        let (tcp_stream, tcp_input) = match self.pending_connections.remove(connection_id) {
+            Some(connection) => connection,
+            None => {
+                warn!(
+                    "Connection {:?} asked to join more than once",
+                    connection_id
+                );
+                return EventHandleResult::TryForNextEvent;
+            }
+        };
+
+        return self.add_players(
+            tcp_stream,
+            tcp_input,
+            connection_id,
+            local_player_count,
+            session_token,
+        );
+    }
+
+    /// Gives the next `local_player_count` player indices to the local
+    /// players of a client.  A client rejoining a resumed match gets back the
+    /// player indices saved with its [SessionToken].
+    fn add_players(
+        &mut self,
+        tcp_stream: TcpStream,
+        tcp_input: TcpInput,
+        connection_id: usize,
+        local_player_count: usize,
+        session_token: Option<SessionToken>,
+    ) -> EventHandleResult {
+        let first_player_index = self.get_remote_player_count();
+
         let listening_core = match &mut self.state {
             State::Listening(listening_core) => listening_core,
             _ => {
@@ -383,32 +1002,131 @@ impl<Game: GameTrait> ServerCoreEventHandler<Game> {
             }
         };
 
-        info!("TcpStream accepted: {:?}", tcp_stream.get_peer_addr());
+        if local_player_count == 0 || local_player_count > Game::MAX_LOCAL_PLAYERS {
+            // Dropping the connection closes it
+            warn!(
+                "Dropping {:?}, which asked to join with {:?} players",
+                tcp_stream.get_peer_addr(),
+                local_player_count
+            );
+            return EventHandleResult::TryForNextEvent;
+        }
 
-        let player_index = self.tcp_inputs.len();
+        if let Some(session_token) = session_token {
+            if self
+                .connections
+                .iter()
+                .any(|connection| connection.session_token == session_token)
+            {
+                warn!(
+                    "Dropping {:?}, whose session token is already connected",
+                    tcp_stream.get_peer_addr()
+                );
+                return EventHandleResult::TryForNextEvent;
+            }
+        }
 
-        let client_address = ClientAddress::new(player_index, tcp_stream.get_peer_addr().ip());
-        listening_core.udp_handler.on_client_address(client_address);
+        // The session token authenticates rejoining, so a fresh match always
+        // issues a new one instead of adopting the client's
+        let (player_indices, session_token): (Vec<usize>, SessionToken) =
+            match &self.resumed_snapshot {
+                Some(snapshot) => {
+                    let saved_client = session_token.and_then(|session_token| {
+                        snapshot
+                            .get_clients()
+                            .iter()
+                            .find(|saved_client| saved_client.get_session_token() == session_token)
+                    });
+
+                    match saved_client {
+                        Some(saved_client)
+                            if saved_client.get_player_indices().len() == local_player_count =>
+                        {
+                            (
+                                saved_client.get_player_indices().to_vec(),
+                                saved_client.get_session_token(),
+                            )
+                        }
+                        _ => {
+                            warn!(
+                                "Dropping {:?}, which is not a client of the resumed match",
+                                tcp_stream.get_peer_addr()
+                            );
+                            return EventHandleResult::TryForNextEvent;
+                        }
+                    }
+                }
+                None => (
+                    (first_player_index..first_player_index + local_player_count).collect(),
+                    SessionToken::new_random(),
+                ),
+            };
+
+        for player_index in player_indices.iter() {
+            let client_address = ClientAddress::new(*player_index, tcp_stream.get_peer_addr().ip());
+            listening_core.udp_handler.on_client_address(client_address);
+        }
 
-        match TcpInput::new(&self.factory, player_index, tcp_reader) {
-            Ok(tcp_input) => self.tcp_inputs.push(tcp_input),
+        let tcp_output = match TcpOutput::new(
+            &self.factory,
+            connection_id,
+            player_indices.clone(),
+            session_token,
+            tcp_stream,
+        ) {
+            Ok(tcp_output) => tcp_output,
             Err(err) => {
-                error!("Failed to start TCP input thread: {:?}", err);
+                error!("Failed to start TCP output thread: {:?}", err);
                 return EventHandleResult::StopThread;
             }
         };
 
-        match TcpOutput::new(&self.factory, player_index, tcp_stream) {
-            Ok(tcp_output) => self.tcp_outputs.push(tcp_output),
-            Err(err) => {
-                error!("Failed to start TCP output thread: {:?}", err);
-                return EventHandleResult::StopThread;
-            }
+        self.connections.push(RemoteConnection {
+            connection_id,
+            player_indices,
+            session_token,
+            _tcp_input: tcp_input,
+            tcp_output,
+        });
+
+        return self.start_resumed_match_if_rejoined();
+    }
+
+    /// Starts a resumed match once all of its clients, and its local player
+    /// if it had one, are back
+    fn start_resumed_match_if_rejoined(&mut self) -> EventHandleResult {
+        let snapshot = match &self.resumed_snapshot {
+            Some(snapshot) => snapshot,
+            None => return EventHandleResult::TryForNextEvent,
         };
 
+        let have_clients_rejoined = snapshot.get_clients().len() == self.connections.len();
+        let has_local_player_rejoined =
+            snapshot.get_local_player_index().is_none() || self.local_player.is_some();
+
+        if have_clients_rejoined && has_local_player_rejoined {
+            info!("All the players of the resumed match have rejoined");
+            return self.start_game();
+        }
+
         return EventHandleResult::TryForNextEvent;
     }
 
+    fn get_remote_player_count(&self) -> usize {
+        return self
+            .connections
+            .iter()
+            .map(|connection| connection.player_indices.len())
+            .sum();
+    }
+
+    fn get_connection_of_player(&self, player_index: usize) -> Option<&RemoteConnection<Game>> {
+        return self
+            .connections
+            .iter()
+            .find(|connection| connection.player_indices.contains(&player_index));
+    }
+
     fn on_game_timer_tick(&mut self) -> EventHandleResult {
         let frame_index = match &mut self.state {
             State::Running(running_core) => match running_core.game_timer.try_advance_frame_index()
@@ -434,6 +1152,92 @@ impl<Game: GameTrait> ServerCoreEventHandler<Game> {
             }
         };
 
+        // Every frame gets an authoritative set of server inputs, even if it is
+        // empty, including frames that were skipped by the game timer.
+        while running_core.next_server_input_frame_index <= frame_index {
+            let server_input_frame_index = running_core.next_server_input_frame_index;
+
+            let server_inputs = if server_input_frame_index == frame_index {
+                take(&mut self.pending_server_inputs)
+            } else {
+                Vec::new()
+            };
+
+            let send_result = running_core
+                .frame_manager
+                .insert_server_input(server_input_frame_index, server_inputs.clone());
+
+            if send_result.is_err() {
+                warn!("Failed to send ServerInput to Game Manager");
+                return EventHandleResult::StopThread;
+            }
+
+            running_core.open_server_inputs.push_back(server_inputs);
+            running_core.next_server_input_frame_index = server_input_frame_index.next();
+        }
+
+        // The server inputs of every open frame are resent, so that a client
+        // that misses a datagram gets them from the next one.  Closed frames
+        // are covered by the authoritative states.
+        let last_open_frame_index = running_core
+            .server_config
+            .get_last_open_frame_index(frame_index);
+
+        let mut first_open_frame_index =
+            running_core.next_server_input_frame_index - running_core.open_server_inputs.len();
+
+        while first_open_frame_index < last_open_frame_index
+            && running_core.open_server_inputs.pop_front().is_some()
+        {
+            first_open_frame_index = first_open_frame_index.next();
+        }
+
+        let server_input_message = ServerInputMessage::<Game>::new(
+            first_open_frame_index,
+            running_core.open_server_inputs.iter().cloned().collect(),
+        );
+
+        for udp_output in running_core.udp_output_senders.iter() {
+            let send_result = udp_output.send_server_input_message(server_input_message.clone());
+
+            if send_result.is_err() {
+                warn!("Failed to send ServerInputMessage to UdpOutput");
+                return EventHandleResult::StopThread;
+            }
+        }
+
+        // The local player's input goes straight into the FrameManager as
+        // authoritative and is broadcast to the clients like any other input.
+        if let (Some(local_player), Some(player_index)) =
+            (&mut self.local_player, running_core.local_player_index)
+        {
+            let input = local_player.get_input(frame_index);
+
+            let send_result = running_core.frame_manager.insert_input(
+                frame_index,
+                player_index,
+                input.clone(),
+                true,
+            );
+
+            if send_result.is_err() {
+                warn!("Failed to send local Input to Game Manager");
+                return EventHandleResult::StopThread;
+            }
+
+            let to_client_message =
+                ToClientInputMessage::<Game>::new(frame_index, player_index, Some(input));
+
+            for udp_output in running_core.udp_output_senders.iter() {
+                let send_result = udp_output.send_input_message(to_client_message.clone());
+
+                if send_result.is_err() {
+                    warn!("Failed to send local Input to UdpOutput");
+                    return EventHandleResult::StopThread;
+                }
+            }
+        }
+
         if running_core
             .frame_manager
             .advance_frame_index(frame_index)
@@ -472,31 +1276,387 @@ impl<Game: GameTrait> ServerCoreEventHandler<Game> {
             .server_config
             .get_last_open_frame_index(current_frame_index);
 
+        // The FrameManager sends the input to the clients once it is validated
         if last_open_frame_index <= input_message.get_frame_index() {
-            let send_result = running_core.frame_manager.insert_input(
+            let send_result = running_core.frame_manager.validate_and_insert_input(
                 input_message.get_frame_index(),
                 input_message.get_player_index(),
-                input_message.get_input().clone(),
-                true,
+                input_message.take_input(),
             );
 
             if send_result.is_err() {
                 warn!("Failed to send InputEvent to Game Manager");
                 return EventHandleResult::StopThread;
             }
+        }
 
-            let to_client_message = input_message.to_client_message();
+        return EventHandleResult::TryForNextEvent;
+    }
 
-            for udp_output in running_core.udp_output_senders.iter() {
-                let send_result = udp_output.send_input_message(to_client_message.clone());
+    fn on_server_input(&mut self, server_input: Game::ServerInput) -> EventHandleResult {
+        // Server inputs are applied to the next frame the server advances to.
+        // Inputs received before the game starts are applied to the first frame.
+        self.pending_server_inputs.push(server_input);
+        return EventHandleResult::TryForNextEvent;
+    }
 
-                if send_result.is_err() {
-                    warn!("Failed to send InputEvent to UdpOutput");
+    fn on_add_frame_observer(
+        &mut self,
+        frame_observer: Box<dyn FrameObserver<Game>>,
+    ) -> EventHandleResult {
+        match &self.state {
+            State::Running(running_core) => {
+                if running_core
+                    .frame_manager
+                    .add_frame_observer(frame_observer)
+                    .is_err()
+                {
+                    warn!("Failed to send FrameObserver to the FrameManager");
                     return EventHandleResult::StopThread;
                 }
             }
+            _ => self.pending_frame_observers.push(frame_observer),
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_set_local_player(
+        &mut self,
+        input_event_handler: Box<dyn InputEventHandler<Game = Game>>,
+    ) -> EventHandleResult {
+        match &self.state {
+            State::Listening(_) => {
+                if self.local_player.is_some() {
+                    warn!("The ServerCore already has a local player");
+                } else {
+                    self.local_player = Some(input_event_handler);
+                }
+            }
+            _ => warn!("A local player can only be set before the game starts"),
+        }
+
+        return self.start_resumed_match_if_rejoined();
+    }
+
+    fn on_local_input_event(
+        &mut self,
+        client_input_event: Game::ClientInputEvent,
+    ) -> EventHandleResult {
+        match &mut self.local_player {
+            Some(local_player) => local_player.handle_input_event(client_input_event),
+            None => warn!("Received a local input event without a local player"),
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_client_message(
+        &mut self,
+        connection_id: usize,
+        message: Game::ClientMessage,
+    ) -> EventHandleResult {
+        // Messages from a client with several local players are attributed to
+        // its first one
+        let player_index = match self
+            .connections
+            .iter()
+            .find(|connection| connection.connection_id == connection_id)
+        {
+            Some(connection) => connection.player_indices[0],
+            None => {
+                warn!(
+                    "Received a message from an unknown connection: {:?}",
+                    connection_id
+                );
+                return EventHandleResult::TryForNextEvent;
+            }
+        };
+
+        if self
+            .client_message_sender
+            .send((player_index, message))
+            .is_err()
+        {
+            debug!("The client message receiver has been dropped");
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_send_message(
+        &mut self,
+        player_index: usize,
+        message: Game::ServerMessage,
+    ) -> EventHandleResult {
+        match self.get_connection_of_player(player_index) {
+            Some(connection) => {
+                if connection.tcp_output.send_message(message).is_err() {
+                    warn!("Failed to send a message to player {:?}", player_index);
+                }
+            }
+            None => warn!(
+                "Cannot send a message to player {:?}, which is not a remote player",
+                player_index
+            ),
         }
 
         return EventHandleResult::TryForNextEvent;
     }
+
+    fn on_broadcast_message(&mut self, message: Game::ServerMessage) -> EventHandleResult {
+        for connection in self.connections.iter() {
+            if connection.tcp_output.send_message(message.clone()).is_err() {
+                warn!(
+                    "Failed to send a message to players {:?}",
+                    connection.player_indices
+                );
+            }
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_set_rate_limits(&mut self, rate_limits: RateLimits) -> EventHandleResult {
+        match self.state {
+            State::Listening(_) => self.rate_limits = rate_limits,
+            _ => warn!("Rate limits can only be set before the game starts"),
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_kick_player(&mut self, player_index: usize) -> EventHandleResult {
+        // Kicking a player closes its connection, which also disconnects the
+        // other local players of its client
+        match self.get_connection_of_player(player_index) {
+            Some(connection) => {
+                info!("Kicking players {:?}", connection.player_indices);
+
+                if connection.tcp_output.disconnect().is_err() {
+                    warn!("Failed to disconnect player {:?}", player_index);
+                }
+            }
+            None => warn!(
+                "Cannot kick player {:?}, which is not a remote player",
+                player_index
+            ),
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_dropped_traffic(&mut self, counts: DroppedTrafficCounts) -> EventHandleResult {
+        if let State::Running(running_core) = &self.state {
+            if running_core
+                .frame_manager
+                .report_dropped_traffic(counts)
+                .is_err()
+            {
+                warn!("Failed to send DroppedTrafficCounts to the FrameManager");
+                return EventHandleResult::StopThread;
+            }
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_enable_discovery(&mut self) -> EventHandleResult {
+        if let Transport::Room { .. } = self.transport {
+            warn!("Discovery is not supported for the rooms of a RoomServer");
+            return EventHandleResult::TryForNextEvent;
+        }
+
+        if self.discovery_responder.is_some() {
+            return EventHandleResult::TryForNextEvent;
+        }
+
+        match DiscoveryResponder::new(&self.factory, self.server_core.clone()) {
+            Ok(discovery_responder) => self.discovery_responder = Some(discovery_responder),
+            Err(error) => warn!("Failed to start the DiscoveryResponder: {:?}", error),
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_discovery_probe(&mut self, peer_addr: SocketAddr) -> EventHandleResult {
+        let remote_player_count = self.get_remote_player_count();
+
+        let discovery_responder = match &mut self.discovery_responder {
+            Some(discovery_responder) => discovery_responder,
+            None => return EventHandleResult::TryForNextEvent,
+        };
+
+        let state = match self.state {
+            State::Running(_) => ServerState::Running,
+            _ => ServerState::Listening,
+        };
+
+        let player_count = match self.local_player {
+            Some(_) => remote_player_count + 1,
+            None => remote_player_count,
+        };
+
+        let response = DiscoveryResponse::new(
+            Game::GAME_NAME.to_string(),
+            player_count,
+            state,
+            Game::TCP_PORT,
+        );
+
+        discovery_responder.send_response(peer_addr, &response);
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_authoritative_state(
+        &mut self,
+        state_message: FrameIndexAndState<Game>,
+    ) -> EventHandleResult {
+        if let State::Running(running_core) = &mut self.state {
+            running_core.latest_authoritative_state = Some(state_message.clone());
+        }
+
+        if self.standbys.is_empty() {
+            return EventHandleResult::TryForNextEvent;
+        }
+
+        // The clients of a running match don't change, so a standby that has
+        // a snapshot only needs the states that follow it
+        let snapshot = match self.standbys.iter().any(|standby| !standby.has_snapshot) {
+            true => self.get_snapshot(),
+            false => None,
+        };
+
+        // A standby whose connection closed is no longer followed
+        self.standbys.retain_mut(|standby| {
+            let result = match (standby.has_snapshot, &snapshot) {
+                (true, _) => standby.tcp_output.send_standby_state(state_message.clone()),
+                (false, Some(snapshot)) => {
+                    standby.has_snapshot = true;
+                    standby.tcp_output.send_snapshot(snapshot.clone())
+                }
+                (false, None) => Ok(()),
+            };
+
+            if result.is_err() {
+                warn!("Failed to send the match to a standby server");
+                return false;
+            }
+
+            return true;
+        });
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    /// Returns a snapshot of the latest authoritative state, or [None] if the
+    /// game has not started
+    fn get_snapshot(&self) -> Option<ServerSnapshot<Game>> {
+        let running_core = match &self.state {
+            State::Running(running_core) => running_core,
+            _ => return None,
+        };
+
+        let state_message = running_core.latest_authoritative_state.as_ref()?;
+
+        let clients = self
+            .connections
+            .iter()
+            .map(|connection| {
+                SavedClient::new(connection.session_token, connection.player_indices.clone())
+            })
+            .collect();
+
+        return Some(ServerSnapshot::<Game>::new(
+            running_core.server_config.clone(),
+            running_core.player_count,
+            clients,
+            running_core.local_player_index,
+            state_message.get_frame_index(),
+            state_message.get_state().clone(),
+        ));
+    }
+
+    fn on_save_snapshot(&mut self, path: PathBuf) -> EventHandleResult {
+        let snapshot = match self.get_snapshot() {
+            Some(snapshot) => snapshot,
+            None => {
+                warn!("A snapshot can only be saved while the game is running");
+                return EventHandleResult::TryForNextEvent;
+            }
+        };
+
+        match snapshot.write_to_file(&path) {
+            Ok(()) => info!(
+                "Saved a snapshot of FrameIndex {:?} to {:?}",
+                snapshot.get_frame_index(),
+                path
+            ),
+            Err(error) => warn!("Failed to save a snapshot to {:?}: {:?}", path, error),
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_enable_standby(&mut self, standby_token: SessionToken) -> EventHandleResult {
+        match self.transport {
+            Transport::Standalone { .. } => self.standby_token = Some(standby_token),
+            Transport::Room { .. } => {
+                warn!("The rooms of a RoomServer cannot have standby servers")
+            }
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn on_standby(
+        &mut self,
+        connection_id: usize,
+        standby_token: SessionToken,
+    ) -> EventHandleResult {
+        let (tcp_stream, tcp_input) = match self.pending_connections.remove(connection_id) {
+            Some(connection) => connection,
+            None => {
+                warn!(
+                    "Connection {:?} asked to follow as a standby after joining",
+                    connection_id
+                );
+                return EventHandleResult::TryForNextEvent;
+            }
+        };
+
+        if self.standby_token != Some(standby_token) {
+            // Dropping the connection closes it
+            warn!(
+                "Dropping {:?}, which is not an allowed standby server",
+                tcp_stream.get_peer_addr()
+            );
+            return EventHandleResult::TryForNextEvent;
+        }
+
+        info!("Standby server connected: {:?}", tcp_stream.get_peer_addr());
+
+        let tcp_output = match TcpOutput::new(
+            &self.factory,
+            connection_id,
+            Vec::new(),
+            standby_token,
+            tcp_stream,
+        ) {
+            Ok(tcp_output) => tcp_output,
+            Err(err) => {
+                error!("Failed to start TCP output thread: {:?}", err);
+                return EventHandleResult::StopThread;
+            }
+        };
+
+        self.standbys.push(StandbyConnection {
+            _tcp_input: tcp_input,
+            tcp_output,
+            has_snapshot: false,
+        });
+
+        return EventHandleResult::TryForNextEvent;
+    }
 }
@@ -5,6 +5,7 @@ use crate::messaging::{
     ToClientInputMessage,
 };
 use crate::server::udpoutput::UdpOutput;
+use crate::server::ServerCore;
 use crate::{
     FrameIndex,
     GameTrait,
@@ -14,16 +15,19 @@ use log::warn;
 use std::ops::ControlFlow;
 
 pub struct ServerManagerObserver<Game: GameTrait> {
+    server_core: ServerCore<Game>,
     udp_outputs: Vec<UdpOutput<Game>>,
     render_receiver_sender: Sender<RenderReceiverMessage<Game>>,
 }
 
 impl<Game: GameTrait> ServerManagerObserver<Game> {
     pub fn new(
+        server_core: ServerCore<Game>,
         udp_outputs: Vec<UdpOutput<Game>>,
         render_receiver_sender: Sender<RenderReceiverMessage<Game>>,
     ) -> Self {
         return Self {
+            server_core,
             udp_outputs,
             render_receiver_sender,
         };
@@ -42,7 +46,10 @@ impl<Game: GameTrait> ObserveFrames for ServerManagerObserver<Game> {
     ) -> ControlFlow<()> {
         let result = self
             .render_receiver_sender
-            .send(RenderReceiverMessage::StepMessage(state_message.clone()));
+            .send(RenderReceiverMessage::StepMessage {
+                is_authoritative: is_state_authoritative,
+                state_message: state_message.clone(),
+            });
 
         if result.is_err() {
             warn!("Failed to send StepMessage to Render Receiver");
@@ -58,6 +65,34 @@ impl<Game: GameTrait> ObserveFrames for ServerManagerObserver<Game> {
                     return ControlFlow::Break(());
                 }
             }
+
+            let result = self.server_core.handle_authoritative_state(state_message);
+
+            if result.is_err() {
+                warn!("Failed to send the authoritative state to the ServerCore");
+                return ControlFlow::Break(());
+            }
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn input_accepted(
+        &self,
+        frame_index: FrameIndex,
+        player_index: usize,
+        input: &Game::ClientInput,
+    ) -> ControlFlow<()> {
+        let input_message =
+            ToClientInputMessage::new(frame_index, player_index, Some(input.clone()));
+
+        for udp_output in self.udp_outputs.iter() {
+            let result = udp_output.send_input_message(input_message.clone());
+
+            if result.is_err() {
+                warn!("Failed to send InputMessage to UdpOutput");
+                return ControlFlow::Break(());
+            }
         }
 
         ControlFlow::Continue(())
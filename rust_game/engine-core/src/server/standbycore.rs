@@ -0,0 +1,212 @@
+use crate::interface::{
+    EngineError,
+    GameTrait,
+    Server,
+    ServerSnapshot,
+    SessionToken,
+};
+use crate::messaging::{
+    FrameIndexAndState,
+    ToClientMessageTCP,
+    ToServerMessageTCP,
+};
+use commons::real_time::net::tcp::{
+    HandleTcpRead,
+    TcpReadHandlerBuilder,
+    TcpStream,
+};
+use commons::real_time::{
+    EventHandleResult,
+    EventHandlerBuilder,
+    EventHandlerStopper,
+    EventSender,
+    Factory,
+    HandleEvent,
+    ReceiveMetaData,
+    Sender,
+};
+use log::{
+    info,
+    warn,
+};
+use std::net::{
+    Ipv4Addr,
+    SocketAddr,
+    SocketAddrV4,
+};
+use std::ops::ControlFlow;
+
+/// Follows a primary [Server] and takes over its match when the connection to
+/// it closes
+pub struct StandbyCore<Game: GameTrait> {
+    _sender: EventSender<StandbyCoreEvent<Game>>,
+}
+
+impl<Game: GameTrait> StandbyCore<Game> {
+    /// Connects to the primary [Server] at `primary_ip`.  The [Server] that
+    /// takes over, listening on `ip_addr`, is sent to `server_sender`.
+    pub fn new(
+        factory: Factory,
+        primary_ip: Ipv4Addr,
+        ip_addr: Ipv4Addr,
+        standby_token: SessionToken,
+        server_sender: Sender<Server<Game>>,
+    ) -> Result<Self, EngineError> {
+        let socket_addr = SocketAddr::V4(SocketAddrV4::new(primary_ip, Game::TCP_PORT));
+
+        let (mut tcp_stream, tcp_reader) = factory
+            .connect_tcp(socket_addr)
+            .map_err(EngineError::Connect)?;
+
+        tcp_stream
+            .write(&ToServerMessageTCP::<Game>::FollowAsStandby { standby_token })
+            .map_err(EngineError::Serialization)?;
+        tcp_stream.flush().map_err(EngineError::Io)?;
+
+        let builder = EventHandlerBuilder::new(&factory);
+        let sender = builder.get_sender().clone();
+
+        // The primary is gone once its connection closes, which stops the
+        // read handler
+        let primary_lost_sender = sender.clone();
+        let tcp_input = TcpReadHandlerBuilder::new(&factory)
+            .spawn_thread_with_call_back(
+                "StandbyTcpInput".to_string(),
+                tcp_reader,
+                ReadHandler {
+                    sender: sender.clone(),
+                },
+                move |_| {
+                    let _ = primary_lost_sender.send_event(StandbyCoreEvent::PrimaryLost);
+                },
+            )
+            .map_err(EngineError::Io)?;
+
+        let event_handler = StandbyCoreEventHandler {
+            factory,
+            ip_addr,
+            server_sender,
+            _tcp_stream: tcp_stream,
+            _tcp_input: tcp_input,
+            latest_snapshot: None,
+        };
+
+        builder
+            .spawn_thread("StandbyCore".to_string(), event_handler)
+            .map_err(EngineError::Io)?;
+
+        return Ok(Self { _sender: sender });
+    }
+}
+
+enum StandbyCoreEvent<Game: GameTrait> {
+    Snapshot(ServerSnapshot<Game>),
+    State(FrameIndexAndState<Game>),
+    PrimaryLost,
+}
+
+struct StandbyCoreEventHandler<Game: GameTrait> {
+    factory: Factory,
+    ip_addr: Ipv4Addr,
+    server_sender: Sender<Server<Game>>,
+    // Keeps the connection to the primary open
+    _tcp_stream: TcpStream,
+    _tcp_input: EventHandlerStopper,
+    latest_snapshot: Option<ServerSnapshot<Game>>,
+}
+
+impl<Game: GameTrait> HandleEvent for StandbyCoreEventHandler<Game> {
+    type Event = StandbyCoreEvent<Game>;
+    type ThreadReturn = ();
+
+    fn on_event(&mut self, _: ReceiveMetaData, event: Self::Event) -> EventHandleResult {
+        match event {
+            StandbyCoreEvent::Snapshot(snapshot) => {
+                self.latest_snapshot = Some(snapshot);
+                EventHandleResult::TryForNextEvent
+            }
+            StandbyCoreEvent::State(state_message) => self.on_state(state_message),
+            StandbyCoreEvent::PrimaryLost => self.take_over(),
+        }
+    }
+
+    fn on_stop_self(self) -> Self::ThreadReturn {
+        ()
+    }
+}
+
+impl<Game: GameTrait> StandbyCoreEventHandler<Game> {
+    fn on_state(&mut self, state_message: FrameIndexAndState<Game>) -> EventHandleResult {
+        match &mut self.latest_snapshot {
+            Some(snapshot) => snapshot.set_state(
+                state_message.get_frame_index(),
+                state_message.get_state().clone(),
+            ),
+            None => warn!("Received a state from the primary server before its snapshot"),
+        }
+
+        return EventHandleResult::TryForNextEvent;
+    }
+
+    fn take_over(&mut self) -> EventHandleResult {
+        let mut snapshot = match self.latest_snapshot.take() {
+            Some(snapshot) => snapshot,
+            None => {
+                warn!("The primary server stopped before its game started");
+                return EventHandleResult::StopThread;
+            }
+        };
+
+        info!(
+            "The primary server stopped.  Taking over at FrameIndex {:?}",
+            snapshot.get_frame_index()
+        );
+
+        // The local player was in the primary's process
+        snapshot.remove_local_player();
+
+        let server =
+            match Server::new_standalone(self.factory.clone(), self.ip_addr, Some(snapshot)) {
+                Ok(server) => server,
+                Err(error) => {
+                    warn!("Failed to take over from the primary server: {:?}", error);
+                    return EventHandleResult::StopThread;
+                }
+            };
+
+        if let Err(server) = self.server_sender.send(server) {
+            warn!("The StandbyServer was dropped before it took over");
+            let _ = server.stop();
+        }
+
+        return EventHandleResult::StopThread;
+    }
+}
+
+struct ReadHandler<Game: GameTrait> {
+    sender: EventSender<StandbyCoreEvent<Game>>,
+}
+
+impl<Game: GameTrait> HandleTcpRead for ReadHandler<Game> {
+    type ReadType = ToClientMessageTCP<Game>;
+
+    fn on_read(&mut self, read: Self::ReadType) -> ControlFlow<()> {
+        let event = match read {
+            ToClientMessageTCP::Snapshot(snapshot) => StandbyCoreEvent::Snapshot(snapshot),
+            ToClientMessageTCP::StandbyState(state_message) => {
+                StandbyCoreEvent::State(state_message)
+            }
+            _ => {
+                warn!("A standby server only expects snapshots and states from the primary");
+                return ControlFlow::Continue(());
+            }
+        };
+
+        if self.sender.send_event(event).is_err() {
+            warn!("Failed to send the match to the StandbyCore");
+            return ControlFlow::Break(());
+        }
+
+        return ControlFlow::Continue(());
+    }
+}
@@ -9,6 +9,13 @@ use commons::real_time::{
 };
 
 use crate::messaging::ToServerMessageTCP;
+use crate::server::roommanager::RoomManager;
+use crate::server::ServerCore;
+use crate::GameTrait;
+use log::{
+    info,
+    warn,
+};
 use std::io::Error;
 use std::ops::ControlFlow;
 
@@ -17,28 +24,148 @@ pub struct TcpInput {
 }
 
 impl TcpInput {
-    pub fn new(
+    pub fn new<Game: GameTrait>(
+        factory: &Factory,
+        connection_id: usize,
+        tcp_reader: TcpReader,
+        server_core: ServerCore<Game>,
+    ) -> Result<Self, Error> {
+        let stopper = TcpReadHandlerBuilder::new_thread(
+            factory,
+            format!("ServerTcpInput-Connection-{}", connection_id),
+            tcp_reader,
+            ReadHandler {
+                connection_id,
+                server_core,
+            },
+        )?;
+
+        Ok(TcpInput { _stopper: stopper })
+    }
+
+    /// Starts reading from a connection to a [RoomServer](crate::RoomServer)
+    /// that has not joined a room yet.  The room the client asks for is sent
+    /// to the [RoomManager].
+    pub fn new_room_connection<Game: GameTrait>(
         factory: &Factory,
-        player_index: usize,
+        connection_id: usize,
         tcp_reader: TcpReader,
+        room_manager: RoomManager<Game>,
     ) -> Result<Self, Error> {
         let stopper = TcpReadHandlerBuilder::new_thread(
             factory,
-            format!("ServerTcpInput-Player-{}", player_index),
+            format!("ServerTcpInput-Connection-{}", connection_id),
             tcp_reader,
-            ReadHandler,
+            RoomReadHandler {
+                connection_id,
+                room_manager,
+            },
         )?;
 
         Ok(TcpInput { _stopper: stopper })
     }
 }
 
-pub struct ReadHandler;
+struct ReadHandler<Game: GameTrait> {
+    connection_id: usize,
+    server_core: ServerCore<Game>,
+}
 
-impl HandleTcpRead for ReadHandler {
-    type ReadType = ToServerMessageTCP;
+impl<Game: GameTrait> HandleTcpRead for ReadHandler<Game> {
+    type ReadType = ToServerMessageTCP<Game>;
 
     fn on_read(&mut self, read: Self::ReadType) -> ControlFlow<()> {
-        match read {};
+        match read {
+            ToServerMessageTCP::JoinRoom {
+                room_id,
+                local_player_count,
+                session_token,
+            } => {
+                // A standalone server only has one room
+                if room_id.is_some() {
+                    info!("Ignoring request to join room: {:?}", room_id);
+                }
+
+                if self
+                    .server_core
+                    .handle_join(self.connection_id, local_player_count, session_token)
+                    .is_err()
+                {
+                    warn!("Failed to send JoinRoom to the Core");
+                    return ControlFlow::Break(());
+                }
+            }
+            ToServerMessageTCP::FollowAsStandby { standby_token } => {
+                if self
+                    .server_core
+                    .handle_standby(self.connection_id, standby_token)
+                    .is_err()
+                {
+                    warn!("Failed to send FollowAsStandby to the Core");
+                    return ControlFlow::Break(());
+                }
+            }
+            ToServerMessageTCP::Message(message) => {
+                if self
+                    .server_core
+                    .handle_client_message(self.connection_id, message)
+                    .is_err()
+                {
+                    warn!("Failed to send ClientMessage to the Core");
+                    return ControlFlow::Break(());
+                }
+            }
+        };
+
+        return ControlFlow::Continue(());
+    }
+}
+
+struct RoomReadHandler<Game: GameTrait> {
+    connection_id: usize,
+    room_manager: RoomManager<Game>,
+}
+
+impl<Game: GameTrait> HandleTcpRead for RoomReadHandler<Game> {
+    type ReadType = ToServerMessageTCP<Game>;
+
+    fn on_read(&mut self, read: Self::ReadType) -> ControlFlow<()> {
+        match read {
+            ToServerMessageTCP::JoinRoom {
+                room_id,
+                local_player_count,
+                session_token,
+            } => {
+                if self
+                    .room_manager
+                    .join_room(
+                        self.connection_id,
+                        room_id,
+                        local_player_count,
+                        session_token,
+                    )
+                    .is_err()
+                {
+                    warn!("Failed to send JoinRoom to the RoomManager");
+                    return ControlFlow::Break(());
+                }
+            }
+            ToServerMessageTCP::FollowAsStandby { .. } => {
+                warn!("The rooms of a RoomServer cannot have standby servers");
+                return ControlFlow::Break(());
+            }
+            ToServerMessageTCP::Message(message) => {
+                if self
+                    .room_manager
+                    .handle_client_message(self.connection_id, message)
+                    .is_err()
+                {
+                    warn!("Failed to send ClientMessage to the RoomManager");
+                    return ControlFlow::Break(());
+                }
+            }
+        };
+
+        return ControlFlow::Continue(());
     }
 }
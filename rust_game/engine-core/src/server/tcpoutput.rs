@@ -1,8 +1,14 @@
 use crate::interface::{
+    EngineError,
     GameTrait,
     InitialInformation,
+    ServerSnapshot,
+    SessionToken,
+};
+use crate::messaging::{
+    FrameIndexAndState,
+    ToClientMessageTCP,
 };
-use crate::messaging::ToClientMessageTCP;
 use crate::server::tcpoutput::Event::SendInitialInformation;
 use crate::server::ServerConfig;
 use commons::real_time::net::tcp::TcpStream;
@@ -14,8 +20,10 @@ use commons::real_time::{
     HandleEvent,
     ReceiveMetaData,
 };
-use commons::utils::unit_error;
-use log::debug;
+use log::{
+    debug,
+    warn,
+};
 use std::io::Error;
 use std::marker::PhantomData;
 
@@ -24,15 +32,19 @@ pub struct TcpOutput<Game: GameTrait> {
 }
 
 impl<Game: GameTrait> TcpOutput<Game> {
+    /// Starts writing to the connection of a client whose local players have
+    /// `player_indices`
     pub fn new(
         factory: &Factory,
-        player_index: usize,
+        connection_id: usize,
+        player_indices: Vec<usize>,
+        session_token: SessionToken,
         tcp_stream: TcpStream,
     ) -> Result<Self, Error> {
         let sender = EventHandlerBuilder::new_thread(
             factory,
-            format!("ServerTcpOutput-Player-{}", player_index),
-            EventHandler::<Game>::new(player_index, tcp_stream),
+            format!("ServerTcpOutput-Connection-{}", connection_id),
+            EventHandler::<Game>::new(player_indices, session_token, tcp_stream),
         )?;
 
         Ok(Self { sender })
@@ -43,27 +55,71 @@ impl<Game: GameTrait> TcpOutput<Game> {
         server_config: ServerConfig,
         player_count: usize,
         initial_state: Game::State,
-    ) -> Result<(), ()> {
+    ) -> Result<(), EngineError> {
         let event = Event::SendInitialInformation(server_config, player_count, initial_state);
 
-        self.sender.send_event(event).map_err(unit_error)
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn send_message(&self, message: Game::ServerMessage) -> Result<(), EngineError> {
+        self.sender
+            .send_event(Event::SendMessage(message))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Sends the latest state of the match to a
+    /// [StandbyServer](crate::StandbyServer)
+    pub fn send_snapshot(&self, snapshot: ServerSnapshot<Game>) -> Result<(), EngineError> {
+        self.sender
+            .send_event(Event::SendSnapshot(snapshot))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Sends the authoritative state of a frame to a
+    /// [StandbyServer](crate::StandbyServer) that has been sent a snapshot
+    pub fn send_standby_state(
+        &self,
+        state_message: FrameIndexAndState<Game>,
+    ) -> Result<(), EngineError> {
+        self.sender
+            .send_event(Event::SendStandbyState(state_message))
+            .map_err(EngineError::thread_stopped)
+    }
+
+    /// Closes the connection to the client
+    pub fn disconnect(&self) -> Result<(), EngineError> {
+        self.sender
+            .send_event(Event::Disconnect)
+            .map_err(EngineError::thread_stopped)
     }
 }
 
 enum Event<Game: GameTrait> {
     SendInitialInformation(ServerConfig, usize, Game::State),
+    SendMessage(Game::ServerMessage),
+    SendSnapshot(ServerSnapshot<Game>),
+    SendStandbyState(FrameIndexAndState<Game>),
+    Disconnect,
 }
 
 struct EventHandler<Game: GameTrait> {
-    player_index: usize,
+    player_indices: Vec<usize>,
+    session_token: SessionToken,
     tcp_stream: TcpStream,
     phantom: PhantomData<Game>,
 }
 
 impl<Game: GameTrait> EventHandler<Game> {
-    pub fn new(player_index: usize, tcp_stream: TcpStream) -> Self {
+    pub fn new(
+        player_indices: Vec<usize>,
+        session_token: SessionToken,
+        tcp_stream: TcpStream,
+    ) -> Self {
         return EventHandler {
-            player_index,
+            player_indices,
+            session_token,
             tcp_stream,
             phantom: PhantomData,
         };
@@ -75,19 +131,44 @@ impl<Game: GameTrait> EventHandler<Game> {
         player_count: usize,
         initial_state: Game::State,
     ) -> EventHandleResult {
-        let initial_information = InitialInformation::<Game>::new(
+        let mut initial_information = InitialInformation::<Game>::new(
             server_config,
             player_count,
-            self.player_index,
+            self.player_indices.clone(),
             initial_state,
         );
+        initial_information.set_session_token(self.session_token);
 
         let message = ToClientMessageTCP::<Game>::InitialInformation(initial_information);
-        self.tcp_stream.write(&message).unwrap();
-        self.tcp_stream.flush().unwrap();
+        let result = self.write(&message);
 
         debug!("Sent InitialInformation");
 
+        return result;
+    }
+
+    fn send_message(&mut self, message: Game::ServerMessage) -> EventHandleResult {
+        let message = ToClientMessageTCP::<Game>::Message(message);
+        return self.write(&message);
+    }
+
+    fn write(&mut self, message: &ToClientMessageTCP<Game>) -> EventHandleResult {
+        if let Err(error) = self.tcp_stream.write(message) {
+            warn!(
+                "Failed to write to players {:?}: {:?}",
+                self.player_indices, error
+            );
+            return EventHandleResult::StopThread;
+        }
+
+        if let Err(error) = self.tcp_stream.flush() {
+            warn!(
+                "Failed to flush to players {:?}: {:?}",
+                self.player_indices, error
+            );
+            return EventHandleResult::StopThread;
+        }
+
         return EventHandleResult::TryForNextEvent;
     }
 }
@@ -101,6 +182,17 @@ impl<Game: GameTrait> HandleEvent for EventHandler<Game> {
             SendInitialInformation(server_config, player_count, initial_state) => {
                 self.send_initial_information(server_config, player_count, initial_state)
             }
+            Event::SendMessage(message) => self.send_message(message),
+            Event::SendSnapshot(snapshot) => {
+                self.write(&ToClientMessageTCP::<Game>::Snapshot(snapshot))
+            }
+            Event::SendStandbyState(state_message) => {
+                self.write(&ToClientMessageTCP::<Game>::StandbyState(state_message))
+            }
+            Event::Disconnect => {
+                debug!("Disconnecting players {:?}", self.player_indices);
+                EventHandleResult::StopThread
+            }
         }
     }
 
@@ -0,0 +1,1272 @@
+use crate::interface::{
+    RateLimits,
+    SavedClient,
+    ServerState,
+};
+use crate::messaging::{
+    FrameIndexAndState,
+    ToClientInputMessage,
+    ToServerInputMessage,
+    UdpToClientMessage,
+    UdpToServerMessage,
+    UdpToServerPacket,
+};
+use crate::server::pendingconnections::{
+    HANDSHAKE_TIMEOUT,
+    MAX_PENDING_CONNECTIONS,
+};
+use crate::server::ratelimiter::{
+    InputBudget,
+    PlayerRateLimiter,
+    SourceRateLimiter,
+};
+use crate::server::{
+    DroppedTrafficCounts,
+    RoomId,
+    ServerConfig,
+};
+use crate::test_utils::{
+    ConfigurableSumGame,
+    FilteredSumGame,
+    FuzzedDatagrams,
+    SumGame,
+    SumInputEventHandler,
+};
+use crate::{
+    Client,
+    ConnectConfig,
+    ConnectionState,
+    EngineError,
+    FrameIndex,
+    FrameObserver,
+    GameTrait,
+    RoomServer,
+    Server,
+    ServerSnapshot,
+    SessionToken,
+    StandbyServer,
+};
+use commons::logging;
+use commons::real_time::net::tcp::{
+    TcpReadHandler,
+    TcpReadHandlerBuilder,
+    TcpStream,
+};
+use commons::real_time::net::LOCAL_IP_V4;
+use commons::real_time::simulation::net::FIRST_EPHEMERAL_UDP_PORT;
+use commons::real_time::simulation::SingleThreadedFactory;
+use commons::real_time::Factory;
+use commons::time::TimeDuration;
+use std::collections::HashMap;
+use std::net::{
+    IpAddr,
+    Ipv4Addr,
+    SocketAddr,
+};
+use std::ops::ControlFlow;
+use std::sync::{
+    Arc,
+    Mutex,
+};
+
+#[test]
+fn test_discovery() {
+    logging::setup_test_logging();
+
+    let server_factory = SingleThreadedFactory::new();
+    let client_factory = server_factory.clone_for_new_host(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)));
+
+    let server = Server::<SumGame>::new(server_factory.clone().into()).unwrap();
+    server.enable_discovery().unwrap();
+    server_factory.get_time_queue().run_events();
+
+    let mut discovery =
+        Client::<SumGame>::discover(&client_factory.into(), TimeDuration::ONE_SECOND).unwrap();
+    server_factory.get_time_queue().run_events();
+
+    let discovered_servers = discovery.get_discovered_servers().clone();
+    assert_eq!(1, discovered_servers.len());
+
+    let discovered_server = &discovered_servers[0];
+    assert_eq!(SumGame::GAME_NAME, discovered_server.get_game_name());
+    assert_eq!(0, discovered_server.get_player_count());
+    assert_eq!(ServerState::Listening, discovered_server.get_state());
+    assert_eq!(
+        SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), SumGame::TCP_PORT),
+        discovered_server.get_tcp_address()
+    );
+    assert!(!discovery.is_complete());
+
+    server_factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+
+    assert_eq!(1, discovery.get_discovered_servers().len());
+    assert!(discovery.is_complete());
+}
+
+#[test]
+fn test_join_discovered_server() {
+    logging::setup_test_logging();
+
+    let server_ip = Ipv4Addr::new(127, 0, 0, 3);
+    let factory = SingleThreadedFactory::new();
+    let server_factory = factory.clone_for_new_host(IpAddr::V4(server_ip));
+
+    // The server listens on every address of its host, like a LAN server
+    let mut server =
+        Server::<SumGame>::new_with_ip_addr(server_factory.clone().into(), Ipv4Addr::UNSPECIFIED)
+            .unwrap();
+    server.enable_discovery().unwrap();
+    factory.get_time_queue().run_events();
+
+    let mut discovery =
+        Client::<SumGame>::discover(&factory.clone().into(), TimeDuration::ONE_SECOND).unwrap();
+    factory.get_time_queue().run_events();
+
+    let discovered_server = discovery.get_discovered_servers()[0].clone();
+    assert_eq!(server_ip, discovered_server.get_ip_addr());
+
+    // The discovery's socket is closed before the client binds its own
+    drop(discovery);
+    factory.get_time_queue().run_events();
+
+    let mut connect_config = ConnectConfig::default();
+    connect_config.set_server_ip(discovered_server.get_ip_addr());
+
+    let (mut client, _render_receiver) = Client::<SumGame>::new_with_connect_config(
+        factory.clone().into(),
+        SumInputEventHandler,
+        None,
+        connect_config,
+    )
+    .unwrap();
+    let latest_state = Arc::new(Mutex::new(None));
+    client
+        .add_frame_observer(AuthoritativeStateObserver(latest_state.clone()))
+        .unwrap();
+    factory.get_time_queue().run_events();
+
+    server.start_game().unwrap();
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND.mul_f64(3.0));
+
+    assert!(matches!(
+        client.get_connection_state(),
+        ConnectionState::InGame
+    ));
+
+    // The client's inputs reach the discovered server, which sums them
+    let (frame_index, state) = latest_state.lock().unwrap().unwrap();
+    assert!(FrameIndex::zero() < frame_index);
+    assert!(0 < state);
+}
+
+#[test]
+fn test_discovery_disabled() {
+    logging::setup_test_logging();
+
+    let server_factory = SingleThreadedFactory::new();
+    let client_factory = server_factory.clone_for_new_host(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)));
+
+    let _server = Server::<SumGame>::new(server_factory.clone().into()).unwrap();
+    server_factory.get_time_queue().run_events();
+
+    let mut discovery =
+        Client::<SumGame>::discover(&client_factory.into(), TimeDuration::ONE_SECOND).unwrap();
+    server_factory.get_time_queue().run_events();
+
+    assert!(discovery.get_discovered_servers().is_empty());
+}
+
+#[test]
+fn test_messages() {
+    logging::setup_test_logging();
+
+    let server_factory = SingleThreadedFactory::new();
+    let client_factory = server_factory.clone_for_new_host(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)));
+
+    let mut server = Server::<SumGame>::new(server_factory.clone().into()).unwrap();
+    let mut server_message_receiver = server.take_message_receiver().unwrap();
+    server_factory.get_time_queue().run_events();
+
+    let (mut client, _render_receiver) =
+        Client::<SumGame>::new(client_factory.into(), SumInputEventHandler).unwrap();
+    let mut client_message_receiver = client.take_message_receiver().unwrap();
+    server_factory.get_time_queue().run_events();
+
+    client.send_message("hello".to_string()).unwrap();
+    server_factory.get_time_queue().run_events();
+
+    assert_eq!(
+        (0, "hello".to_string()),
+        server_message_receiver.try_recv().unwrap()
+    );
+    assert!(server_message_receiver.try_recv().is_err());
+
+    server.send_message(0, "direct".to_string()).unwrap();
+    server.broadcast_message("everyone".to_string()).unwrap();
+    server_factory.get_time_queue().run_events();
+
+    assert_eq!("direct", client_message_receiver.try_recv().unwrap());
+    assert_eq!("everyone", client_message_receiver.try_recv().unwrap());
+    assert!(client_message_receiver.try_recv().is_err());
+}
+
+#[test]
+fn test_source_rate_limit() {
+    logging::setup_test_logging();
+
+    let simulated_factory = SingleThreadedFactory::new();
+    let factory: Factory = simulated_factory.clone().into();
+
+    let mut rate_limits = RateLimits::default();
+    rate_limits.set_max_packets_per_frame(2);
+    rate_limits.set_max_bytes_per_frame(100);
+
+    let mut rate_limiter =
+        SourceRateLimiter::<SumGame>::new(factory.get_time_source().clone(), rate_limits);
+
+    let source = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)), 1);
+    let other_source = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 3)), 1);
+
+    assert!(rate_limiter.allow_datagram(source, 10));
+    assert!(rate_limiter.allow_datagram(source, 10));
+    assert!(!rate_limiter.allow_datagram(source, 10));
+
+    // Each source has its own budget
+    assert!(!rate_limiter.allow_datagram(other_source, 101));
+    assert!(rate_limiter.allow_datagram(other_source, 100));
+
+    simulated_factory
+        .get_time_queue()
+        .advance_time_for_duration(SumGame::STEP_PERIOD);
+
+    assert!(rate_limiter.allow_datagram(source, 10));
+    assert_eq!(2, rate_limiter.get_total_dropped_count());
+}
+
+#[test]
+fn test_input_rate_limit() {
+    logging::setup_test_logging();
+
+    let factory: Factory = SingleThreadedFactory::new().into();
+
+    let mut rate_limits = RateLimits::default();
+    rate_limits.set_max_inputs_per_frame(1);
+    rate_limits.set_kick_threshold(Some(1));
+
+    let mut rate_limiter =
+        PlayerRateLimiter::<SumGame>::new(factory.get_time_source().clone(), rate_limits);
+
+    assert_eq!(InputBudget::Allow, rate_limiter.check_input(0));
+    assert_eq!(InputBudget::Allow, rate_limiter.check_input(1));
+    assert_eq!(InputBudget::Drop, rate_limiter.check_input(0));
+    assert_eq!(InputBudget::Kick, rate_limiter.check_input(0));
+    assert_eq!(2, rate_limiter.get_dropped_count(0));
+    assert_eq!(0, rate_limiter.get_dropped_count(1));
+}
+
+#[test]
+fn test_dropped_traffic_observed() {
+    logging::setup_test_logging();
+
+    let factory = SingleThreadedFactory::new();
+
+    let mut server = Server::<SumGame>::new(factory.clone().into()).unwrap();
+    let dropped_traffic = Arc::new(Mutex::new(None));
+    server
+        .add_frame_observer(DroppedTrafficObserver(dropped_traffic.clone()))
+        .unwrap();
+
+    let mut rate_limits = RateLimits::default();
+    rate_limits.set_max_inputs_per_frame(0);
+    rate_limits.set_kick_threshold(None);
+    server.set_rate_limits(rate_limits).unwrap();
+
+    let (_client, _render_receiver) =
+        Client::<SumGame>::new(factory.clone().into(), SumInputEventHandler).unwrap();
+    factory.get_time_queue().run_events();
+
+    server.start_game().unwrap();
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+
+    let counts = dropped_traffic.lock().unwrap().clone().unwrap();
+    assert_eq!(0, counts.get_datagram_count());
+    assert!(counts.get_input_count(0) > 0);
+    assert_eq!(counts.get_input_count(0), counts.get_total_input_count());
+}
+
+#[test]
+fn test_fuzzed_datagrams() {
+    logging::setup_test_logging();
+
+    let server_ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+    let client_ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2));
+
+    let server_factory = SingleThreadedFactory::new();
+    let client_factory = server_factory.clone_for_new_host(client_ip);
+
+    let mut server = Server::<SumGame>::new(server_factory.clone().into()).unwrap();
+    server_factory.get_time_queue().run_events();
+
+    let (mut client, _render_receiver) =
+        Client::<SumGame>::new(client_factory.clone().into(), SumInputEventHandler).unwrap();
+    let mut client_message_receiver = client.take_message_receiver().unwrap();
+    server_factory.get_time_queue().run_events();
+
+    server.start_game().unwrap();
+    server_factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+
+    let frame_index = FrameIndex::from(5);
+
+    let to_server_messages = vec![rmp_serde::to_vec(&UdpToServerPacket::<SumGame>::new(
+        RoomId::default(),
+        UdpToServerMessage::Input(ToServerInputMessage::new(frame_index, 0, 1)),
+    ))
+    .unwrap()];
+
+    let to_client_messages = vec![
+        rmp_serde::to_vec(&UdpToClientMessage::<SumGame>::StateMessage(
+            FrameIndexAndState::new(frame_index, 5),
+        ))
+        .unwrap(),
+        rmp_serde::to_vec(&UdpToClientMessage::<SumGame>::InputMessage(
+            ToClientInputMessage::new(frame_index, 0, Some(1)),
+        ))
+        .unwrap(),
+    ];
+
+    // Datagrams to the server come from the client's IP address so that
+    // they are not dropped as coming from an unexpected source
+    let client_host: Factory = client_factory.into();
+    let server_host: Factory = server_factory.clone().into();
+
+    let mut to_server_socket = client_host
+        .bind_udp_socket(SocketAddr::new(client_ip, 1))
+        .unwrap();
+    let mut to_client_socket = server_host
+        .bind_udp_socket(SocketAddr::new(server_ip, 1))
+        .unwrap();
+
+    let server_addr = SocketAddr::new(server_ip, SumGame::UDP_PORT);
+    // The client's socket is the first one its host binds to an ephemeral port
+    let client_addr = SocketAddr::new(IpAddr::V4(LOCAL_IP_V4), FIRST_EPHEMERAL_UDP_PORT);
+
+    let mut to_server_datagrams = FuzzedDatagrams::new(1, to_server_messages);
+    let mut to_client_datagrams = FuzzedDatagrams::new(2, to_client_messages);
+
+    for i in 0..2_000 {
+        to_server_socket
+            .send_to(&to_server_datagrams.next_datagram(), &server_addr)
+            .unwrap();
+        to_client_socket
+            .send_to(&to_client_datagrams.next_datagram(), &client_addr)
+            .unwrap();
+
+        if i % 10 == 0 {
+            server_factory
+                .get_time_queue()
+                .advance_time_for_duration(SumGame::STEP_PERIOD);
+        }
+    }
+
+    server_factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+
+    server
+        .broadcast_message("still running".to_string())
+        .unwrap();
+    server_factory.get_time_queue().run_events();
+
+    assert_eq!("still running", client_message_receiver.try_recv().unwrap());
+}
+
+#[test]
+fn test_connection_refused() {
+    logging::setup_test_logging();
+
+    let simulated_factory = SingleThreadedFactory::new();
+
+    let mut connect_config = ConnectConfig::default();
+    connect_config.set_max_attempts(Some(3));
+
+    let (mut client, _render_receiver) = Client::<SumGame>::new_with_connect_config(
+        simulated_factory.clone().into(),
+        SumInputEventHandler,
+        None,
+        connect_config,
+    )
+    .unwrap();
+
+    simulated_factory.get_time_queue().run_events();
+    assert!(matches!(
+        client.get_connection_state(),
+        ConnectionState::Connecting { attempt: 2 }
+    ));
+
+    simulated_factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+    assert!(matches!(
+        client.get_connection_state(),
+        ConnectionState::Failed(EngineError::Connect(_))
+    ));
+}
+
+#[test]
+fn test_connect_before_server() {
+    logging::setup_test_logging();
+
+    let server_factory = SingleThreadedFactory::new();
+    let client_factory = server_factory.clone_for_new_host(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)));
+
+    let (mut client, _render_receiver) =
+        Client::<SumGame>::new(client_factory.into(), SumInputEventHandler).unwrap();
+    client.send_message("early".to_string()).unwrap();
+
+    server_factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+    assert!(matches!(
+        client.get_connection_state(),
+        ConnectionState::Connecting { .. }
+    ));
+
+    let mut server = Server::<SumGame>::new(server_factory.clone().into()).unwrap();
+    let mut server_message_receiver = server.take_message_receiver().unwrap();
+
+    server_factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND.mul_f64(2.0));
+    assert!(matches!(
+        client.get_connection_state(),
+        ConnectionState::WaitingForGameStart
+    ));
+
+    // Messages sent while connecting are delivered once connected
+    assert_eq!(
+        (0, "early".to_string()),
+        server_message_receiver.try_recv().unwrap()
+    );
+
+    server.start_game().unwrap();
+    server_factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+    assert!(matches!(
+        client.get_connection_state(),
+        ConnectionState::InGame
+    ));
+}
+
+/// Opens a TCP connection to the server that never sends anything.  The
+/// returned flag is set once the server closes the connection.
+fn connect_silently(client_factory: &SingleThreadedFactory) -> (TcpStream, Arc<Mutex<bool>>) {
+    let factory: Factory = client_factory.clone().into();
+    let server_socket_addr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), SumGame::TCP_PORT);
+    let (tcp_stream, tcp_reader) = factory.connect_tcp(server_socket_addr).unwrap();
+
+    let is_closed = Arc::new(Mutex::new(false));
+    let is_closed_clone = is_closed.clone();
+    let stopper = TcpReadHandlerBuilder::new(&factory)
+        .spawn_thread_with_call_back(
+            "SilentClient".to_string(),
+            tcp_reader,
+            TcpReadHandler::new(|_: Vec<u8>| ControlFlow::Continue(())),
+            move |_| *is_closed_clone.lock().unwrap() = true,
+        )
+        .unwrap();
+
+    // The reader stops when the server closes the connection
+    std::mem::forget(stopper);
+
+    return (tcp_stream, is_closed);
+}
+
+#[test]
+fn test_silent_connection_dropped() {
+    logging::setup_test_logging();
+
+    let server_factory = SingleThreadedFactory::new();
+    let client_factory = server_factory.clone_for_new_host(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)));
+
+    let _server = Server::<SumGame>::new(server_factory.clone().into()).unwrap();
+    server_factory.get_time_queue().run_events();
+
+    let (mut client, _render_receiver) =
+        Client::<SumGame>::new(client_factory.clone().into(), SumInputEventHandler).unwrap();
+    let (_tcp_stream, is_closed) = connect_silently(&client_factory);
+
+    server_factory
+        .get_time_queue()
+        .advance_time_for_duration(HANDSHAKE_TIMEOUT.mul_f64(0.5));
+    assert!(!*is_closed.lock().unwrap());
+
+    server_factory
+        .get_time_queue()
+        .advance_time_for_duration(HANDSHAKE_TIMEOUT);
+    assert!(*is_closed.lock().unwrap());
+
+    // The client that finished its handshake stays connected
+    assert!(matches!(
+        client.get_connection_state(),
+        ConnectionState::WaitingForGameStart
+    ));
+}
+
+#[test]
+fn test_pending_connection_limit() {
+    logging::setup_test_logging();
+
+    let server_factory = SingleThreadedFactory::new();
+    let client_factory = server_factory.clone_for_new_host(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 2)));
+
+    let _server = Server::<SumGame>::new(server_factory.clone().into()).unwrap();
+    server_factory.get_time_queue().run_events();
+
+    let connections: Vec<(TcpStream, Arc<Mutex<bool>>)> = (0..MAX_PENDING_CONNECTIONS + 1)
+        .map(|_| connect_silently(&client_factory))
+        .collect();
+    server_factory.get_time_queue().run_events();
+
+    // Only the connection over the limit is closed before the timeout
+    for (_, is_closed) in connections[..MAX_PENDING_CONNECTIONS].iter() {
+        assert!(!*is_closed.lock().unwrap());
+    }
+    assert!(*connections[MAX_PENDING_CONNECTIONS].1.lock().unwrap());
+}
+
+#[test]
+fn test_port_in_use() {
+    logging::setup_test_logging();
+
+    let server_factory = SingleThreadedFactory::new();
+
+    let _server = Server::<SumGame>::new(server_factory.clone().into()).unwrap();
+    server_factory.get_time_queue().run_events();
+
+    let result = Server::<SumGame>::new(server_factory.into());
+
+    assert!(matches!(result, Err(EngineError::Bind(_))));
+}
+
+#[test]
+fn test_udp_port_in_use() {
+    logging::setup_test_logging();
+
+    let server_factory = SingleThreadedFactory::new();
+    let factory: Factory = server_factory.clone().into();
+
+    let _udp_socket = factory
+        .bind_udp_socket(SocketAddr::new(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            SumGame::UDP_PORT,
+        ))
+        .unwrap();
+
+    let result = Server::<SumGame>::new(factory);
+
+    assert!(matches!(result, Err(EngineError::Bind(_))));
+
+    // The failed server did not keep the TCP port
+    drop(_udp_socket);
+
+    assert!(Server::<SumGame>::new(server_factory.into()).is_ok());
+}
+
+#[test]
+fn test_local_players() {
+    logging::setup_test_logging();
+
+    // The client shares the server's host, which the simulated UDP socket of
+    // the client is bound to
+    let factory = SingleThreadedFactory::new();
+
+    let mut server = Server::<SumGame>::new(factory.clone().into()).unwrap();
+    let missing_inputs = Arc::new(Mutex::new(Vec::new()));
+    server
+        .add_frame_observer(MissingInputObserver(missing_inputs.clone()))
+        .unwrap();
+
+    let (client, mut render_receiver) = Client::<SumGame>::new_with_local_players(
+        factory.clone().into(),
+        vec![SumInputEventHandler, SumInputEventHandler],
+        None,
+        ConnectConfig::default(),
+    )
+    .unwrap();
+    factory.get_time_queue().run_events();
+
+    server.start_game().unwrap();
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND.mul_f64(3.0));
+    render_receiver.get_step_message();
+
+    assert_eq!(&[0, 1], render_receiver.get_local_player_indices());
+
+    let initial_information = render_receiver.get_initial_information().as_ref().unwrap();
+    assert_eq!(2, initial_information.get_player_count());
+
+    assert!(client.send_local_player_input_event(1, ()).is_ok());
+
+    // The inputs of both local players reach the server before the grace
+    // period ends
+    assert_eq!(0, missing_inputs.lock().unwrap().len());
+}
+
+#[test]
+fn test_listen_server_local_player() {
+    logging::setup_test_logging();
+
+    let factory = SingleThreadedFactory::new();
+
+    // The local player is added before the client connects, and still takes
+    // the player index after the remote players
+    let mut server = Server::<SumGame>::new(factory.clone().into()).unwrap();
+    let (_local_player, mut server_render_receiver) =
+        server.add_local_player(SumInputEventHandler).unwrap();
+    let missing_inputs = Arc::new(Mutex::new(Vec::new()));
+    server
+        .add_frame_observer(MissingInputObserver(missing_inputs.clone()))
+        .unwrap();
+
+    let (client, mut render_receiver) =
+        Client::<SumGame>::new(factory.clone().into(), SumInputEventHandler).unwrap();
+    let state_increments = Arc::new(Mutex::new(Vec::new()));
+    client
+        .add_frame_observer(StateIncrementObserver {
+            states: HashMap::new(),
+            increments: state_increments.clone(),
+        })
+        .unwrap();
+    factory.get_time_queue().run_events();
+
+    server.start_game().unwrap();
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+
+    // Until the client receives the local player's first input, it has
+    // nothing to predict it from
+    state_increments.lock().unwrap().clear();
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND.mul_f64(2.0));
+    render_receiver.get_step_message();
+    server_render_receiver.get_step_message();
+
+    assert_eq!(&[0], render_receiver.get_local_player_indices());
+    assert_eq!(&[1], server_render_receiver.get_local_player_indices());
+
+    let initial_information = render_receiver.get_initial_information().as_ref().unwrap();
+    assert_eq!(2, initial_information.get_player_count());
+
+    // The client predicts the local player's inputs from the ones it receives,
+    // so every state it computes adds both players' inputs
+    let state_increments = state_increments.lock().unwrap();
+    assert!(10 < state_increments.len());
+    assert!(state_increments.iter().all(|increment| *increment == 2));
+
+    assert_eq!(0, missing_inputs.lock().unwrap().len());
+}
+
+#[test]
+fn test_invalid_local_player() {
+    logging::setup_test_logging();
+
+    // Only one local player can be added
+    let mut server = Server::<SumGame>::new(SingleThreadedFactory::new().into()).unwrap();
+    assert!(server.add_local_player(SumInputEventHandler).is_ok());
+    assert!(matches!(
+        server.add_local_player(SumInputEventHandler),
+        Err(EngineError::InvalidCall(_))
+    ));
+
+    // The render receiver is kept when the game has already started
+    let mut server = Server::<SumGame>::new(SingleThreadedFactory::new().into()).unwrap();
+    server.start_game().unwrap();
+    assert!(matches!(
+        server.add_local_player(SumInputEventHandler),
+        Err(EngineError::InvalidCall(_))
+    ));
+    assert!(server.take_render_receiver().is_some());
+
+    // The local player needs the server's render receiver
+    let mut server = Server::<SumGame>::new(SingleThreadedFactory::new().into()).unwrap();
+    server.take_render_receiver().unwrap();
+    assert!(matches!(
+        server.add_local_player(SumInputEventHandler),
+        Err(EngineError::InvalidCall(_))
+    ));
+}
+
+#[test]
+fn test_filtered_state_for_local_players() {
+    logging::setup_test_logging();
+
+    let factory = SingleThreadedFactory::new();
+
+    let mut server = Server::<FilteredSumGame>::new(factory.clone().into()).unwrap();
+
+    // The state is only visible to player 1, which is the second local player
+    // of the client
+    let (client, _render_receiver) = Client::<FilteredSumGame>::new_with_local_players(
+        factory.clone().into(),
+        vec![SumInputEventHandler, SumInputEventHandler],
+        None,
+        ConnectConfig::default(),
+    )
+    .unwrap();
+    let latest_state = Arc::new(Mutex::new(None));
+    client
+        .add_frame_observer(AuthoritativeStateObserver(latest_state.clone()))
+        .unwrap();
+    factory.get_time_queue().run_events();
+
+    server.start_game().unwrap();
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND.mul_f64(3.0));
+
+    let (frame_index, state) = latest_state.lock().unwrap().unwrap();
+    assert!(FrameIndex::zero() < frame_index);
+    assert!(0 < state);
+}
+
+#[test]
+fn test_resume_snapshot() {
+    logging::setup_test_logging();
+
+    let snapshot_path =
+        std::env::temp_dir().join(format!("test_resume_snapshot_{}", std::process::id()));
+
+    let factory = SingleThreadedFactory::new();
+    let mut server = Server::<SumGame>::new(factory.clone().into()).unwrap();
+    let (_client, mut render_receiver) =
+        Client::<SumGame>::new(factory.clone().into(), SumInputEventHandler).unwrap();
+    factory.get_time_queue().run_events();
+
+    server.start_game().unwrap();
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND.mul_f64(3.0));
+    render_receiver.get_step_message();
+
+    let session_token = render_receiver
+        .get_initial_information()
+        .as_ref()
+        .unwrap()
+        .get_session_token()
+        .unwrap();
+
+    server.save_snapshot(snapshot_path.clone()).unwrap();
+    factory.get_time_queue().run_events();
+
+    let snapshot = ServerSnapshot::<SumGame>::read_from_file(&snapshot_path).unwrap();
+    std::fs::remove_file(&snapshot_path).unwrap();
+
+    let saved_frame_index = snapshot.get_frame_index();
+    let saved_state = *snapshot.get_state();
+    assert!(FrameIndex::zero() < saved_frame_index);
+    assert_eq!(1, snapshot.get_player_count());
+
+    // The match resumes on a new server, which starts once the client rejoins
+    // with its session token
+    let factory = SingleThreadedFactory::new();
+    let server = Server::<SumGame>::resume(factory.clone().into(), snapshot).unwrap();
+    let missing_inputs = Arc::new(Mutex::new(Vec::new()));
+    server
+        .add_frame_observer(MissingInputObserver(missing_inputs.clone()))
+        .unwrap();
+    let latest_state = Arc::new(Mutex::new(None));
+    server
+        .add_frame_observer(AuthoritativeStateObserver(latest_state.clone()))
+        .unwrap();
+
+    let mut connect_config = ConnectConfig::default();
+    connect_config.set_session_token(Some(session_token));
+
+    let (mut client, mut render_receiver) = Client::<SumGame>::new_with_connect_config(
+        factory.clone().into(),
+        SumInputEventHandler,
+        None,
+        connect_config,
+    )
+    .unwrap();
+
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND.mul_f64(3.0));
+    render_receiver.get_step_message();
+
+    assert!(matches!(
+        client.get_connection_state(),
+        ConnectionState::InGame
+    ));
+    assert_eq!(&[0], render_receiver.get_local_player_indices());
+
+    let initial_information = render_receiver.get_initial_information().as_ref().unwrap();
+    assert_eq!(
+        saved_frame_index,
+        initial_information
+            .get_server_config()
+            .get_first_frame_index()
+    );
+    assert_eq!(&saved_state, initial_information.get_state());
+    assert_eq!(Some(session_token), initial_information.get_session_token());
+
+    // The state keeps summing the client's inputs from where it was saved
+    let (frame_index, state) = latest_state.lock().unwrap().unwrap();
+    assert!(saved_frame_index < frame_index);
+    assert_eq!(
+        (frame_index.usize() - saved_frame_index.usize()) as i64,
+        state - saved_state
+    );
+    assert_eq!(0, missing_inputs.lock().unwrap().len());
+}
+
+#[test]
+fn test_new_match_ignores_session_token() {
+    logging::setup_test_logging();
+
+    let factory = SingleThreadedFactory::new();
+    let mut server = Server::<SumGame>::new(factory.clone().into()).unwrap();
+
+    let presented_session_token = SessionToken::from_value(42);
+    let mut connect_config = ConnectConfig::default();
+    connect_config.set_session_token(Some(presented_session_token));
+
+    let (_client, mut render_receiver) = Client::<SumGame>::new_with_connect_config(
+        factory.clone().into(),
+        SumInputEventHandler,
+        None,
+        connect_config,
+    )
+    .unwrap();
+    factory.get_time_queue().run_events();
+
+    server.start_game().unwrap();
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+    render_receiver.get_step_message();
+
+    let initial_information = render_receiver.get_initial_information().as_ref().unwrap();
+    assert_ne!(
+        presented_session_token,
+        initial_information.get_session_token().unwrap()
+    );
+}
+
+#[test]
+fn test_resume_invalid_snapshot() {
+    logging::setup_test_logging();
+
+    let factory = SingleThreadedFactory::new();
+    let new_snapshot = |clients: Vec<SavedClient>, local_player_index: Option<usize>| {
+        ServerSnapshot::<SumGame>::new(
+            ServerConfig::new::<SumGame>(&factory.clone().into(), RoomId::default()),
+            2,
+            clients,
+            local_player_index,
+            FrameIndex::from(10),
+            0,
+        )
+    };
+
+    let invalid_snapshots = [
+        // A client's player index is out of range
+        new_snapshot(
+            vec![SavedClient::new(SessionToken::new_random(), vec![2])],
+            None,
+        ),
+        // The local player's index is out of range
+        new_snapshot(Vec::new(), Some(2)),
+        // Two clients share a player index
+        new_snapshot(
+            vec![
+                SavedClient::new(SessionToken::new_random(), vec![0]),
+                SavedClient::new(SessionToken::new_random(), vec![0]),
+            ],
+            None,
+        ),
+        // A client and the local player share a player index
+        new_snapshot(
+            vec![SavedClient::new(SessionToken::new_random(), vec![1])],
+            Some(1),
+        ),
+    ];
+
+    for snapshot in invalid_snapshots {
+        assert!(matches!(
+            Server::resume(factory.clone().into(), snapshot),
+            Err(EngineError::InvalidSnapshot(_))
+        ));
+    }
+
+    let snapshot = new_snapshot(
+        vec![SavedClient::new(SessionToken::new_random(), vec![0])],
+        Some(1),
+    );
+    assert!(Server::resume(factory.clone().into(), snapshot).is_ok());
+}
+
+#[test]
+fn test_resume_with_missing_client() {
+    logging::setup_test_logging();
+
+    let snapshot_path = std::env::temp_dir().join(format!(
+        "test_resume_with_missing_client_{}",
+        std::process::id()
+    ));
+
+    let factory = SingleThreadedFactory::new();
+    let mut server = Server::<SumGame>::new(factory.clone().into()).unwrap();
+    let (_client, mut render_receiver) =
+        Client::<SumGame>::new(factory.clone().into(), SumInputEventHandler).unwrap();
+    let (_other_client, _other_render_receiver) =
+        Client::<SumGame>::new(factory.clone().into(), SumInputEventHandler).unwrap();
+    factory.get_time_queue().run_events();
+
+    server.start_game().unwrap();
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+    render_receiver.get_step_message();
+
+    let initial_information = render_receiver.get_initial_information().as_ref().unwrap();
+    let session_token = initial_information.get_session_token().unwrap();
+    let rejoined_player_index = render_receiver.get_local_player_indices()[0];
+
+    server.save_snapshot(snapshot_path.clone()).unwrap();
+    factory.get_time_queue().run_events();
+
+    let snapshot = ServerSnapshot::<SumGame>::read_from_file(&snapshot_path).unwrap();
+    std::fs::remove_file(&snapshot_path).unwrap();
+    assert_eq!(2, snapshot.get_player_count());
+
+    // Only one of the clients rejoins, so the match waits for the other one
+    let factory = SingleThreadedFactory::new();
+    let server = Server::<SumGame>::resume(factory.clone().into(), snapshot).unwrap();
+    let missing_inputs = Arc::new(Mutex::new(Vec::new()));
+    server
+        .add_frame_observer(MissingInputObserver(missing_inputs.clone()))
+        .unwrap();
+
+    let mut connect_config = ConnectConfig::default();
+    connect_config.set_session_token(Some(session_token));
+
+    let (mut client, _render_receiver) = Client::<SumGame>::new_with_connect_config(
+        factory.clone().into(),
+        SumInputEventHandler,
+        None,
+        connect_config,
+    )
+    .unwrap();
+
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(SumGame::REJOIN_TIMEOUT - &TimeDuration::ONE_SECOND);
+
+    assert!(matches!(
+        client.get_connection_state(),
+        ConnectionState::WaitingForGameStart
+    ));
+
+    // Once the rejoin timeout passes, the match continues without the other
+    // client, whose inputs are missing
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND.mul_f64(3.0));
+
+    assert!(matches!(
+        client.get_connection_state(),
+        ConnectionState::InGame
+    ));
+
+    let missing_player_index = 1 - rejoined_player_index;
+    let missing_inputs = missing_inputs.lock().unwrap();
+    assert!(!missing_inputs.is_empty());
+    assert!(missing_inputs
+        .iter()
+        .all(|(_, player_index)| *player_index == missing_player_index));
+}
+
+#[test]
+fn test_rooms_share_udp_socket() {
+    logging::setup_test_logging();
+
+    // The clients reach the room server from another host
+    let server_ip = Ipv4Addr::new(127, 0, 0, 3);
+    let factory = SingleThreadedFactory::new();
+    let server_factory = factory.clone_for_new_host(IpAddr::V4(server_ip));
+    let mut room_server =
+        RoomServer::<SumGame>::new_with_ip_addr(server_factory.into(), Ipv4Addr::UNSPECIFIED)
+            .unwrap();
+
+    let mut connect_config = ConnectConfig::default();
+    connect_config.set_server_ip(server_ip);
+
+    // Each room records the increments of its own states, so inputs routed to
+    // the wrong room show up as missing inputs or wrong increments
+    let mut rooms = Vec::new();
+    for client_count in [1, 2] {
+        let (room_id, server) = room_server.create_room().unwrap();
+
+        let missing_inputs = Arc::new(Mutex::new(Vec::new()));
+        server
+            .add_frame_observer(MissingInputObserver(missing_inputs.clone()))
+            .unwrap();
+        let state_increments = Arc::new(Mutex::new(Vec::new()));
+        server
+            .add_frame_observer(StateIncrementObserver {
+                states: HashMap::new(),
+                increments: state_increments.clone(),
+            })
+            .unwrap();
+
+        let clients: Vec<_> = (0..client_count)
+            .map(|_| {
+                Client::<SumGame>::new_with_connect_config(
+                    factory.clone().into(),
+                    SumInputEventHandler,
+                    Some(room_id),
+                    connect_config,
+                )
+                .unwrap()
+            })
+            .collect();
+
+        rooms.push((server, clients, missing_inputs, state_increments));
+    }
+    factory.get_time_queue().run_events();
+
+    for (server, _, _, _) in rooms.iter_mut() {
+        server.start_game().unwrap();
+    }
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+
+    for (_, _, _, state_increments) in rooms.iter() {
+        state_increments.lock().unwrap().clear();
+    }
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND.mul_f64(2.0));
+
+    for (_, clients, missing_inputs, state_increments) in rooms.iter_mut() {
+        let player_count = clients.len();
+
+        for (client, render_receiver) in clients.iter_mut() {
+            assert!(matches!(
+                client.get_connection_state(),
+                ConnectionState::InGame
+            ));
+
+            render_receiver.get_step_message();
+            let initial_information = render_receiver.get_initial_information().as_ref().unwrap();
+            assert_eq!(player_count, initial_information.get_player_count());
+        }
+
+        let state_increments = state_increments.lock().unwrap();
+        assert!(10 < state_increments.len());
+        assert!(state_increments
+            .iter()
+            .all(|increment| *increment == player_count as i64));
+
+        assert_eq!(0, missing_inputs.lock().unwrap().len());
+    }
+}
+
+#[test]
+fn test_stopped_room_removed() {
+    logging::setup_test_logging();
+
+    let factory = SingleThreadedFactory::new();
+    let mut room_server = RoomServer::<SumGame>::new(factory.clone().into()).unwrap();
+    let (stopped_room_id, stopped_server) = room_server.create_room().unwrap();
+    let (_, mut server) = room_server.create_room().unwrap();
+    let mut server_message_receiver = server.take_message_receiver().unwrap();
+
+    stopped_server.stop().unwrap();
+    factory.get_time_queue().run_events();
+
+    // A client asking for the stopped room is refused, so its message is
+    // delivered nowhere
+    let (refused_client, _refused_render_receiver) = Client::<SumGame>::join_room(
+        factory.clone().into(),
+        SumInputEventHandler,
+        stopped_room_id,
+    )
+    .unwrap();
+    refused_client.send_message("refused".to_string()).unwrap();
+    factory.get_time_queue().run_events();
+
+    // A client that doesn't ask for a room is assigned to the open one
+    let (mut client, _render_receiver) =
+        Client::<SumGame>::new(factory.clone().into(), SumInputEventHandler).unwrap();
+    client.send_message("hello".to_string()).unwrap();
+
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+
+    assert!(matches!(
+        client.get_connection_state(),
+        ConnectionState::WaitingForGameStart
+    ));
+    assert_eq!(
+        (0, "hello".to_string()),
+        server_message_receiver.try_recv().unwrap()
+    );
+    assert!(server_message_receiver.try_recv().is_err());
+}
+
+#[test]
+fn test_standby_takes_over() {
+    logging::setup_test_logging();
+
+    // The client shares the primary's host, which the simulated UDP socket of
+    // the client is bound to
+    let factory = SingleThreadedFactory::new();
+    let standby_ip = Ipv4Addr::new(127, 0, 0, 2);
+    let standby_factory = factory.clone_for_new_host(IpAddr::V4(standby_ip));
+
+    let standby_token = SessionToken::new_random();
+    let mut server = Server::<SumGame>::new(factory.clone().into()).unwrap();
+    server.enable_standby(standby_token).unwrap();
+    let primary_state = Arc::new(Mutex::new(None));
+    server
+        .add_frame_observer(AuthoritativeStateObserver(primary_state.clone()))
+        .unwrap();
+    factory.get_time_queue().run_events();
+
+    let mut standby = StandbyServer::<SumGame>::new(
+        standby_factory.into(),
+        Ipv4Addr::LOCALHOST,
+        standby_ip,
+        standby_token,
+    )
+    .unwrap();
+
+    let mut connect_config = ConnectConfig::default();
+    connect_config.set_standby_server_ip(Some(standby_ip));
+
+    let (mut client, mut render_receiver) = Client::<SumGame>::new_with_connect_config(
+        factory.clone().into(),
+        SumInputEventHandler,
+        None,
+        connect_config,
+    )
+    .unwrap();
+    factory.get_time_queue().run_events();
+
+    server.start_game().unwrap();
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND.mul_f64(3.0));
+    assert!(standby.take_server().is_none());
+    let (primary_frame_index, primary_state) = primary_state.lock().unwrap().unwrap();
+
+    // The primary fails, and the client follows the match to the standby
+    server.stop().unwrap();
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND);
+
+    let standby_server = standby.take_server().unwrap();
+    let missing_inputs = Arc::new(Mutex::new(Vec::new()));
+    standby_server
+        .add_frame_observer(MissingInputObserver(missing_inputs.clone()))
+        .unwrap();
+    let latest_state = Arc::new(Mutex::new(None));
+    standby_server
+        .add_frame_observer(AuthoritativeStateObserver(latest_state.clone()))
+        .unwrap();
+
+    factory
+        .get_time_queue()
+        .advance_time_for_duration(TimeDuration::ONE_SECOND.mul_f64(2.0));
+    render_receiver.get_step_message();
+
+    assert!(matches!(
+        client.get_connection_state(),
+        ConnectionState::InGame
+    ));
+    assert_eq!(&[0], render_receiver.get_local_player_indices());
+
+    // The standby continues from the last authoritative frame of the primary
+    let initial_information = render_receiver.get_initial_information().as_ref().unwrap();
+    let first_frame_index = initial_information
+        .get_server_config()
+        .get_first_frame_index();
+    let first_state = *initial_information.get_state();
+    assert!(FrameIndex::zero() < first_frame_index);
+    assert_eq!(primary_frame_index, first_frame_index);
+    assert_eq!(primary_state, first_state);
+
+    let (frame_index, state) = latest_state.lock().unwrap().unwrap();
+    assert!(first_frame_index < frame_index);
+    assert_eq!(
+        (frame_index.usize() - first_frame_index.usize()) as i64,
+        state - first_state
+    );
+    assert_eq!(0, missing_inputs.lock().unwrap().len());
+}
+
+struct AuthoritativeStateObserver(Arc<Mutex<Option<(FrameIndex, i64)>>>);
+
+impl<const IS_FILTERED: bool, const HAS_SERVER_BONUS: bool>
+    FrameObserver<ConfigurableSumGame<IS_FILTERED, HAS_SERVER_BONUS>>
+    for AuthoritativeStateObserver
+{
+    fn on_new_state(&mut self, frame_index: FrameIndex, state: &i64, is_authoritative: bool) {
+        if is_authoritative {
+            *self.0.lock().unwrap() = Some((frame_index, *state));
+        }
+    }
+}
+
+/// Records how much each state computed or received adds to the latest state
+/// of the frame before it
+struct StateIncrementObserver {
+    states: HashMap<FrameIndex, i64>,
+    increments: Arc<Mutex<Vec<i64>>>,
+}
+
+impl FrameObserver<SumGame> for StateIncrementObserver {
+    fn on_new_state(&mut self, frame_index: FrameIndex, state: &i64, _is_authoritative: bool) {
+        if frame_index > FrameIndex::zero() {
+            if let Some(previous_state) = self.states.get(&(frame_index - 1)) {
+                self.increments.lock().unwrap().push(state - previous_state);
+            }
+        }
+
+        self.states.insert(frame_index, *state);
+    }
+}
+
+struct DroppedTrafficObserver(Arc<Mutex<Option<DroppedTrafficCounts>>>);
+
+impl FrameObserver<SumGame> for DroppedTrafficObserver {
+    fn on_traffic_dropped(&mut self, counts: &DroppedTrafficCounts) {
+        *self.0.lock().unwrap() = Some(counts.clone());
+    }
+}
+
+struct MissingInputObserver(Arc<Mutex<Vec<(FrameIndex, usize)>>>);
+
+impl FrameObserver<SumGame> for MissingInputObserver {
+    fn on_input_missing(&mut self, frame_index: FrameIndex, player_index: usize) {
+        self.0.lock().unwrap().push((frame_index, player_index));
+    }
+}
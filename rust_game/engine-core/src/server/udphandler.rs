@@ -2,6 +2,7 @@ use crate::messaging::{
     FragmentAssembler,
     MessageFragment,
     UdpToServerMessage,
+    UdpToServerPacket,
 };
 use crate::server::clientaddress::ClientAddress;
 use crate::server::remoteudppeer::RemoteUdpPeer;
@@ -75,9 +76,9 @@ impl<Game: GameTrait> UdpHandler<Game> {
         }
 
         if let Some(assembled) = self.handle_fragment(source, &buf) {
-            match rmp_serde::from_slice(assembled.as_slice()) {
-                Ok(message) => {
-                    return (self.handle_message(&message, source), Some(message));
+            match rmp_serde::from_slice::<UdpToServerPacket<Game>>(assembled.as_slice()) {
+                Ok(packet) => {
+                    return self.handle_message(packet.take_message(), source);
                 }
                 Err(error) => {
                     //TODO: is removing the fragement assembler on error right?
@@ -91,6 +92,46 @@ impl<Game: GameTrait> UdpHandler<Game> {
         }
     }
 
+    /// Handles a message that was already assembled and deserialized by a
+    /// [RoomUdpInput](crate::server::RoomUdpInput)
+    pub fn on_message(
+        &mut self,
+        message: UdpToServerMessage<Game>,
+        source: SocketAddr,
+    ) -> (Option<RemoteUdpPeer>, Option<UdpToServerMessage<Game>>) {
+        if !self.client_ip_set.contains(&source.ip()) {
+            warn!("Unexpected UDP packet received from {:?}", source);
+            return (None, None);
+        }
+
+        return self.handle_message(message, source);
+    }
+
+    /// Returns true if `source` has the IP address of a client
+    pub fn is_client_source(&self, source: &SocketAddr) -> bool {
+        return self.client_ip_set.contains(&source.ip());
+    }
+
+    /// Stops accepting messages from the player with `player_index`
+    pub fn remove_client(&mut self, player_index: usize) {
+        if let Some(client_address) = self.client_addresses.get_mut(player_index) {
+            *client_address = None;
+        }
+
+        if let Some(Some(remote_peer)) = self.remote_peers.get_mut(player_index).map(Option::take) {
+            self.fragment_assemblers
+                .remove(&remote_peer.get_socket_addr());
+        }
+
+        // Other clients may share the IP address
+        self.client_ip_set = self
+            .client_addresses
+            .iter()
+            .flatten()
+            .map(|client_address| client_address.get_ip_address())
+            .collect();
+    }
+
     fn handle_fragment(&mut self, source: SocketAddr, fragment: &[u8]) -> Option<Vec<u8>> {
         let assembler = match self.fragment_assemblers.get_mut(&source) {
             None => {
@@ -102,14 +143,26 @@ impl<Game: GameTrait> UdpHandler<Game> {
             Some(assembler) => assembler,
         };
 
-        return assembler.add_fragment(MessageFragment::from_vec(fragment.to_vec()));
+        let result = MessageFragment::from_vec(fragment.to_vec())
+            .and_then(|fragment| assembler.add_fragment(fragment));
+
+        return match result {
+            Ok(assembled) => assembled,
+            Err(error) => {
+                warn!(
+                    "Dropping a malformed fragment from {:?}: {:?}",
+                    source, error
+                );
+                None
+            }
+        };
     }
 
     fn handle_message(
         &mut self,
-        message: &UdpToServerMessage<Game>,
+        message: UdpToServerMessage<Game>,
         source: SocketAddr,
-    ) -> Option<RemoteUdpPeer> {
+    ) -> (Option<RemoteUdpPeer>, Option<UdpToServerMessage<Game>>) {
         let player_index = message.get_player_index();
 
         let source_is_valid = match self.client_addresses.get(player_index) {
@@ -125,10 +178,10 @@ impl<Game: GameTrait> UdpHandler<Game> {
                 player_index,
                 source.ip()
             );
-            return None;
+            return (None, None);
         }
 
-        return self.handle_remote_peer(message.get_player_index(), source);
+        return (self.handle_remote_peer(player_index, source), Some(message));
     }
 
     fn handle_remote_peer(
@@ -1,11 +1,21 @@
 use crate::game_time::PingRequest;
+use crate::interface::RateLimits;
 use crate::messaging::{
     ToServerInputMessage,
     UdpToServerMessage,
 };
+use crate::server::ratelimiter::{
+    InputBudget,
+    PlayerRateLimiter,
+    SourceRateLimiter,
+};
+use crate::server::remoteudppeer::RemoteUdpPeer;
 use crate::server::udphandler::UdpHandler;
 use crate::server::udpoutput::UdpOutput;
-use crate::server::ServerCore;
+use crate::server::{
+    DroppedTrafficCounts,
+    ServerCore,
+};
 use crate::GameTrait;
 use commons::real_time::net::udp::{
     HandleUdpRead,
@@ -17,11 +27,13 @@ use commons::real_time::{
     Factory,
     TimeSource,
 };
+use commons::time::TimeValue;
 use log::{
     error,
     info,
     warn,
 };
+use std::collections::HashMap;
 use std::io::Error;
 use std::net::SocketAddr;
 use std::ops::ControlFlow;
@@ -33,44 +45,46 @@ pub struct UdpInput {
 impl UdpInput {
     pub fn new<Game: GameTrait>(
         factory: &Factory,
-        server_core: ServerCore<Game>,
         udp_socket: &UdpSocket,
-        udp_handler: UdpHandler<Game>,
-        udp_outputs: Vec<UdpOutput<Game>>,
+        udp_input_handler: UdpInputHandler<Game>,
     ) -> Result<Self, Error> {
-        let udp_input = ReadHandler::<Game>::new(
-            factory.get_time_source().clone(),
-            server_core,
-            udp_handler,
-            udp_outputs,
-        );
-
         let stopper = UdpReadHandlerBuilder::new_thread(
             factory,
             "ServerUdpInput".to_string(),
             udp_socket.try_clone()?,
-            udp_input,
+            udp_input_handler,
         )?;
 
         Ok(Self { _stopper: stopper })
     }
 }
 
-struct ReadHandler<Game: GameTrait> {
+/// Handles the messages received from the clients of one room
+pub struct UdpInputHandler<Game: GameTrait> {
     time_source: TimeSource,
     server_core: ServerCore<Game>,
     udp_handler: UdpHandler<Game>,
-    udp_output_senders: Vec<UdpOutput<Game>>,
+    // Keyed by player index
+    udp_output_senders: HashMap<usize, UdpOutput<Game>>,
+    source_rate_limiter: SourceRateLimiter<Game>,
+    player_rate_limiter: PlayerRateLimiter<Game>,
+    has_unreported_drops: bool,
+    next_report_time: Option<TimeValue>,
 }
 
-impl<Game: GameTrait> ReadHandler<Game> {
+impl<Game: GameTrait> UdpInputHandler<Game> {
     pub fn new(
         time_source: TimeSource,
         server_core: ServerCore<Game>,
         udp_handler: UdpHandler<Game>,
-        udp_output_senders: Vec<UdpOutput<Game>>,
+        udp_output_senders: HashMap<usize, UdpOutput<Game>>,
+        rate_limits: RateLimits,
     ) -> Self {
         return Self {
+            source_rate_limiter: SourceRateLimiter::new(time_source.clone(), rate_limits),
+            player_rate_limiter: PlayerRateLimiter::new(time_source.clone(), rate_limits),
+            has_unreported_drops: false,
+            next_report_time: None,
             time_source,
             server_core,
             udp_handler,
@@ -79,17 +93,85 @@ impl<Game: GameTrait> ReadHandler<Game> {
     }
 
     fn on_input_message(&mut self, input_message: ToServerInputMessage<Game>) -> ControlFlow<()> {
+        let player_index = input_message.get_player_index();
+
+        match self.player_rate_limiter.check_input(player_index) {
+            InputBudget::Allow => {}
+            InputBudget::Drop => {
+                self.has_unreported_drops = true;
+                return ControlFlow::Continue(());
+            }
+            InputBudget::Kick => {
+                self.has_unreported_drops = true;
+                return self.kick_player(player_index);
+            }
+        }
+
         match self.server_core.handle_input_message(input_message) {
             Ok(()) => ControlFlow::Continue(()),
-            Err(()) => {
+            Err(_) => {
                 warn!("Error sending InputMessage");
                 ControlFlow::Break(())
             }
         }
     }
 
+    fn kick_player(&mut self, player_index: usize) -> ControlFlow<()> {
+        warn!(
+            "Kicking player {:?} after {:?} inputs over the rate limit",
+            player_index,
+            self.player_rate_limiter.get_dropped_count(player_index)
+        );
+
+        self.udp_handler.remove_client(player_index);
+
+        match self.server_core.kick_player(player_index) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(_) => {
+                warn!("Error sending KickPlayer");
+                ControlFlow::Break(())
+            }
+        }
+    }
+
+    /// Reports the dropped traffic to the [ServerCore] at most once per frame,
+    /// so that a flood of dropped datagrams is not passed on.  Drops are
+    /// reported when the next datagram is received.
+    fn report_dropped_traffic(&mut self) -> ControlFlow<()> {
+        if !self.has_unreported_drops {
+            return ControlFlow::Continue(());
+        }
+
+        let now = self.time_source.now();
+
+        if let Some(next_report_time) = self.next_report_time {
+            if now.is_before(&next_report_time) {
+                return ControlFlow::Continue(());
+            }
+        }
+
+        self.has_unreported_drops = false;
+        self.next_report_time = Some(now + Game::STEP_PERIOD);
+
+        let counts = DroppedTrafficCounts::new(
+            self.source_rate_limiter.get_total_dropped_count(),
+            self.player_rate_limiter.get_dropped_counts().to_vec(),
+        );
+
+        match self.server_core.handle_dropped_traffic(counts) {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(_) => {
+                warn!("Error sending DroppedTrafficCounts");
+                ControlFlow::Break(())
+            }
+        }
+    }
+
     fn on_ping_request(&mut self, ping_request: PingRequest) -> ControlFlow<()> {
-        let udp_output_sender = match self.udp_output_senders.get(ping_request.get_player_index()) {
+        let udp_output_sender = match self
+            .udp_output_senders
+            .get(&ping_request.get_player_index())
+        {
             Some(udp_output_sender) => udp_output_sender,
             None => {
                 warn!(
@@ -104,26 +186,40 @@ impl<Game: GameTrait> ReadHandler<Game> {
 
         match result {
             Ok(()) => ControlFlow::Continue(()),
-            Err(()) => {
+            Err(_) => {
                 error!("Failed to send PingRequest to Udp Output");
                 ControlFlow::Break(())
             }
         }
     }
-}
 
-impl<Game: GameTrait> HandleUdpRead for ReadHandler<Game> {
-    fn on_read(&mut self, peer_addr: SocketAddr, buf: &[u8]) -> ControlFlow<()> {
+    /// Handles a message that was already assembled and deserialized by a
+    /// [RoomUdpInput](crate::server::RoomUdpInput)
+    pub fn on_message(
+        &mut self,
+        peer_addr: SocketAddr,
+        message: UdpToServerMessage<Game>,
+    ) -> ControlFlow<()> {
+        self.report_dropped_traffic()?;
+
         let (remote_udp_peer_option, message_option) =
-            self.udp_handler.on_udp_packet(buf, peer_addr);
+            self.udp_handler.on_message(message, peer_addr);
+
+        return self.handle_message(remote_udp_peer_option, message_option);
+    }
 
+    fn handle_message(
+        &mut self,
+        remote_udp_peer_option: Option<RemoteUdpPeer>,
+        message_option: Option<UdpToServerMessage<Game>>,
+    ) -> ControlFlow<()> {
         if let Some(message) = message_option {
             //TODO: clean up this nested if let.  If we got a message, we definitly have a remote peer
             if let Some(remote_udp_peer) = remote_udp_peer_option {
                 info!("Received UDP remote peer");
 
                 let udp_output_sender =
-                    match self.udp_output_senders.get(message.get_player_index()) {
+                    match self.udp_output_senders.get(&message.get_player_index()) {
                         Some(udp_output_sender) => udp_output_sender,
                         None => {
                             warn!("Invalid player index: {:?}", message.get_player_index());
@@ -147,3 +243,25 @@ impl<Game: GameTrait> HandleUdpRead for ReadHandler<Game> {
         return ControlFlow::Continue(());
     }
 }
+
+impl<Game: GameTrait> HandleUdpRead for UdpInputHandler<Game> {
+    fn on_read(&mut self, peer_addr: SocketAddr, buf: &[u8]) -> ControlFlow<()> {
+        self.report_dropped_traffic()?;
+
+        // Datagrams from unknown sources are dropped by the UdpHandler without
+        // being counted
+        if self.udp_handler.is_client_source(&peer_addr)
+            && !self
+                .source_rate_limiter
+                .allow_datagram(peer_addr, buf.len())
+        {
+            self.has_unreported_drops = true;
+            return ControlFlow::Continue(());
+        }
+
+        let (remote_udp_peer_option, message_option) =
+            self.udp_handler.on_udp_packet(buf, peer_addr);
+
+        return self.handle_message(remote_udp_peer_option, message_option);
+    }
+}
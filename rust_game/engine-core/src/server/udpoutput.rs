@@ -2,10 +2,14 @@ use crate::game_time::{
     PingRequest,
     PingResponse,
 };
-use crate::interface::GameTrait;
+use crate::interface::{
+    EngineError,
+    GameTrait,
+};
 use crate::messaging::{
     Fragmenter,
     FrameIndexAndState,
+    ServerInputMessage,
     ToClientInputMessage,
     UdpToClientMessage,
 };
@@ -22,7 +26,6 @@ use commons::real_time::{
     TimeSource,
 };
 use commons::time::TimeValue;
-use commons::utils::unit_error;
 use log::{
     error,
     info,
@@ -38,22 +41,21 @@ pub struct UdpOutput<Game: GameTrait> {
 }
 
 impl<Game: GameTrait> UdpOutput<Game> {
+    /// Starts sending to the client whose local players have `player_indices`
     pub fn new(
         factory: Factory,
-        player_index: usize,
+        player_indices: Vec<usize>,
         udp_socket: &UdpSocket,
     ) -> Result<Self, Error> {
+        let thread_name = format!("ServerUdpOutput-Player-{}", player_indices[0]);
+
         let event_handler = EventHandler::<Game>::new(
             factory.get_time_source().clone(),
-            player_index,
+            player_indices,
             &udp_socket,
         )?;
 
-        let sender = EventHandlerBuilder::new_thread(
-            &factory,
-            format!("ServerUdpOutput-Player-{}", player_index),
-            event_handler,
-        )?;
+        let sender = EventHandlerBuilder::new_thread(&factory, thread_name, event_handler)?;
 
         Ok(Self { sender })
     }
@@ -62,28 +64,52 @@ impl<Game: GameTrait> UdpOutput<Game> {
         &self,
         time_received: TimeValue,
         ping_request: PingRequest,
-    ) -> Result<(), ()> {
+    ) -> Result<(), EngineError> {
         let event = Event::PingRequest {
             time_received,
             ping_request,
         };
 
-        self.sender.send_event(event).map_err(unit_error)
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
     }
 
-    pub fn set_remote_peer(&self, remote_udp_peer: RemoteUdpPeer) -> Result<(), ()> {
+    pub fn set_remote_peer(&self, remote_udp_peer: RemoteUdpPeer) -> Result<(), EngineError> {
         let event = Event::RemotePeer(remote_udp_peer);
-        self.sender.send_event(event).map_err(unit_error)
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
     }
 
-    pub fn send_input_message(&self, input_message: ToClientInputMessage<Game>) -> Result<(), ()> {
+    pub fn send_input_message(
+        &self,
+        input_message: ToClientInputMessage<Game>,
+    ) -> Result<(), EngineError> {
         let event = Event::SendInputMessage(input_message);
-        self.sender.send_event(event).map_err(unit_error)
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
     }
 
-    pub fn send_completed_step(&self, step_message: FrameIndexAndState<Game>) -> Result<(), ()> {
+    pub fn send_server_input_message(
+        &self,
+        server_input_message: ServerInputMessage<Game>,
+    ) -> Result<(), EngineError> {
+        let event = Event::SendServerInputMessage(server_input_message);
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
+    }
+
+    pub fn send_completed_step(
+        &self,
+        step_message: FrameIndexAndState<Game>,
+    ) -> Result<(), EngineError> {
         let event = Event::SendCompletedStep(step_message);
-        self.sender.send_event(event).map_err(unit_error)
+        self.sender
+            .send_event(event)
+            .map_err(EngineError::thread_stopped)
     }
 }
 
@@ -94,12 +120,13 @@ enum Event<Game: GameTrait> {
         ping_request: PingRequest,
     },
     SendInputMessage(ToClientInputMessage<Game>),
+    SendServerInputMessage(ServerInputMessage<Game>),
     SendCompletedStep(FrameIndexAndState<Game>),
 }
 
 struct EventHandler<Game: GameTrait> {
     time_source: TimeSource,
-    player_index: usize,
+    player_indices: Vec<usize>,
     socket: UdpSocket,
     remote_peer: Option<RemoteUdpPeer>,
     fragmenter: Fragmenter,
@@ -109,11 +136,11 @@ struct EventHandler<Game: GameTrait> {
 impl<Game: GameTrait> EventHandler<Game> {
     pub fn new(
         time_source: TimeSource,
-        player_index: usize,
+        player_indices: Vec<usize>,
         socket: &UdpSocket,
     ) -> Result<Self, Error> {
         Ok(EventHandler {
-            player_index,
+            player_indices,
             remote_peer: None,
             //TODO: move clone outside
             socket: socket.try_clone()?,
@@ -126,7 +153,10 @@ impl<Game: GameTrait> EventHandler<Game> {
 
     fn on_remote_peer(&mut self, remote_peer: RemoteUdpPeer) -> EventHandleResult {
         //TODO: could this be checked before calling udpoutput?
-        if self.player_index == remote_peer.get_player_index() {
+        if self
+            .player_indices
+            .contains(&remote_peer.get_player_index())
+        {
             info!("Setting remote peer: {:?}", remote_peer);
             self.remote_peer = Some(remote_peer);
         }
@@ -135,6 +165,14 @@ impl<Game: GameTrait> EventHandler<Game> {
     }
 
     fn on_completed_step(&mut self, state_message: FrameIndexAndState<Game>) -> EventHandleResult {
+        let state_message = if Game::IS_STATE_FILTERED {
+            let state =
+                Game::filter_state_for_player(state_message.get_state(), &self.player_indices);
+            FrameIndexAndState::new(state_message.get_frame_index(), state)
+        } else {
+            state_message
+        };
+
         let message = UdpToClientMessage::<Game>::StateMessage(state_message);
         self.send_message(&message);
         return EventHandleResult::TryForNextEvent;
@@ -147,6 +185,17 @@ impl<Game: GameTrait> EventHandler<Game> {
         return EventHandleResult::TryForNextEvent;
     }
 
+    fn on_server_input_message(
+        &mut self,
+        server_input_message: ServerInputMessage<Game>,
+    ) -> EventHandleResult {
+        let message = UdpToClientMessage::<Game>::ServerInputMessage(server_input_message);
+        match self.send_message(&message) {
+            ControlFlow::Continue(()) => EventHandleResult::TryForNextEvent,
+            ControlFlow::Break(()) => EventHandleResult::StopThread,
+        }
+    }
+
     fn send_ping_response(
         &mut self,
         time_received: TimeValue,
@@ -206,6 +255,9 @@ impl<Game: GameTrait> HandleEvent for EventHandler<Game> {
         match event {
             Event::RemotePeer(remote_udp_peer) => self.on_remote_peer(remote_udp_peer),
             Event::SendInputMessage(input_message) => self.on_input_message(input_message),
+            Event::SendServerInputMessage(server_input_message) => {
+                self.on_server_input_message(server_input_message)
+            }
             Event::SendCompletedStep(state_message) => self.on_completed_step(state_message),
             Event::PingRequest {
                 time_received,
@@ -0,0 +1,70 @@
+use crate::messaging::MessageFragment;
+use crate::DeterministicRng;
+
+/// Generates arbitrary datagrams from a seed.  Besides random bytes, it
+/// produces fragments with small, colliding header values and single
+/// fragments of corrupted copies of `valid_messages`, so that the datagrams
+/// reach the fragment assembler and the deserializer.
+pub struct FuzzedDatagrams {
+    rng: DeterministicRng,
+    valid_messages: Vec<Vec<u8>>,
+}
+
+impl FuzzedDatagrams {
+    pub fn new(seed: u64, valid_messages: Vec<Vec<u8>>) -> Self {
+        return Self {
+            rng: DeterministicRng::new(seed),
+            valid_messages,
+        };
+    }
+
+    pub fn next_datagram(&mut self) -> Vec<u8> {
+        return match self.rng.range_usize(0, 3) {
+            0 => self.random_bytes(32),
+            1 => {
+                let id = self.rng.range_usize(0, 4) as u32;
+                let index = self.rng.range_usize(0, 4) as u16;
+                let count = self.rng.range_usize(0, 4) as u16;
+                let body = self.random_bytes(16);
+                MessageFragment::new(id, index, count, body)
+                    .get_whole_buf()
+                    .clone()
+            }
+            _ => {
+                let id = self.rng.next_u64() as u32;
+                let body = self.corrupted_message();
+                MessageFragment::new(id, 0, 1, body).get_whole_buf().clone()
+            }
+        };
+    }
+
+    fn random_bytes(&mut self, max_length: usize) -> Vec<u8> {
+        let length = self.rng.range_usize(0, max_length + 1);
+        return (0..length).map(|_| self.rng.next_u64() as u8).collect();
+    }
+
+    fn corrupted_message(&mut self) -> Vec<u8> {
+        if self.valid_messages.is_empty() {
+            return self.random_bytes(64);
+        }
+
+        let index = self.rng.range_usize(0, self.valid_messages.len());
+        let mut message = self.valid_messages[index].clone();
+
+        for _ in 0..self.rng.range_usize(1, 4) {
+            if message.is_empty() {
+                break;
+            }
+
+            let position = self.rng.range_usize(0, message.len());
+            message[position] = self.rng.next_u64() as u8;
+        }
+
+        if self.rng.next_bool() {
+            let length = self.rng.range_usize(0, message.len() + 1);
+            message.truncate(length);
+        }
+
+        return message;
+    }
+}
@@ -0,0 +1,12 @@
+pub use self::fuzzeddatagrams::FuzzedDatagrams;
+pub use self::sumgame::{
+    ConfigurableSumGame,
+    FilteredSumGame,
+    ServerBonusSumGame,
+    SumGame,
+};
+pub use self::suminputeventhandler::SumInputEventHandler;
+
+mod fuzzeddatagrams;
+mod sumgame;
+mod suminputeventhandler;
@@ -0,0 +1,101 @@
+use crate::interface::{
+    GameTrait,
+    InitialInformation,
+    InputValidation,
+    InterpolationArg,
+    UpdateArg,
+};
+use commons::time::TimeDuration;
+
+/// A game whose state is the sum of every player's inputs.  The server rejects
+/// negative inputs and clamps inputs above 10.
+///
+/// Interpolation returns the interpolated sum and whether the states rolled
+/// back.
+///
+/// With `IS_FILTERED`, the sum is hidden from every client without player 1.
+/// With `HAS_SERVER_BONUS`, the server adds 100 to the sum on every frame.
+#[derive(Clone)]
+pub struct ConfigurableSumGame<const IS_FILTERED: bool, const HAS_SERVER_BONUS: bool>;
+
+pub type SumGame = ConfigurableSumGame<false, false>;
+
+pub type FilteredSumGame = ConfigurableSumGame<true, false>;
+
+pub type ServerBonusSumGame = ConfigurableSumGame<false, true>;
+
+impl<const IS_FILTERED: bool, const HAS_SERVER_BONUS: bool> GameTrait
+    for ConfigurableSumGame<IS_FILTERED, HAS_SERVER_BONUS>
+{
+    type State = i64;
+    type ClientInput = i64;
+    type ServerInput = ();
+    type InterpolationResult = (f64, bool);
+    type ClientInputEvent = ();
+    type ClientMessage = String;
+    type ServerMessage = String;
+
+    const GAME_NAME: &'static str = "SumGame";
+    const TCP_PORT: u16 = 1000;
+    const UDP_PORT: u16 = 1001;
+    const DISCOVERY_PORT: u16 = 1002;
+    const STEP_PERIOD: TimeDuration = TimeDuration::new(0, 100_000_000);
+    const GRACE_PERIOD: TimeDuration = TimeDuration::new(1, 0);
+    const PING_PERIOD: TimeDuration = TimeDuration::new(1, 0);
+    const CLOCK_AVERAGE_SIZE: usize = 10;
+    const MAX_SIMULATED_FRAMES_PER_PASS: usize = 2;
+    const MAX_FUTURE_FRAMES: usize = 5;
+    const MAX_PAST_FRAMES: usize = 20;
+    const IS_STATE_FILTERED: bool = IS_FILTERED;
+    const HAS_SERVER_ONLY_LOGIC: bool = HAS_SERVER_BONUS;
+
+    fn get_initial_state(_player_count: usize) -> Self::State {
+        0
+    }
+
+    fn get_next_state(arg: &UpdateArg<Self>) -> Self::State {
+        let mut state = *arg.get_state();
+
+        for player_index in 0..arg.get_player_count() {
+            if let Some(input) = arg.get_input_or_prediction(player_index) {
+                state += input;
+            }
+        }
+
+        if HAS_SERVER_BONUS && arg.is_server() {
+            state += 100;
+        }
+
+        state
+    }
+
+    fn validate_input(
+        _state: &Self::State,
+        _player_index: usize,
+        input: &Self::ClientInput,
+    ) -> InputValidation<Self> {
+        match *input {
+            input if input < 0 => InputValidation::Reject,
+            input if input > 10 => InputValidation::Clamp(10),
+            _ => InputValidation::Accept,
+        }
+    }
+
+    fn filter_state_for_player(state: &Self::State, player_indices: &[usize]) -> Self::State {
+        match player_indices.contains(&1) {
+            true => *state,
+            false => 0,
+        }
+    }
+
+    fn interpolate(
+        _initial_information: &InitialInformation<Self>,
+        _previous_result: Option<&Self::InterpolationResult>,
+        first: &Self::State,
+        second: &Self::State,
+        arg: &InterpolationArg,
+    ) -> Self::InterpolationResult {
+        let sum = *first as f64 + (*second - *first) as f64 * arg.get_weight();
+        (sum, arg.has_rolled_back())
+    }
+}
@@ -0,0 +1,21 @@
+use crate::interface::InputEventHandler;
+use crate::test_utils::sumgame::ConfigurableSumGame;
+use crate::FrameIndex;
+
+/// Adds one to the sum on every frame
+pub struct SumInputEventHandler<
+    const IS_FILTERED: bool = false,
+    const HAS_SERVER_BONUS: bool = false,
+>;
+
+impl<const IS_FILTERED: bool, const HAS_SERVER_BONUS: bool> InputEventHandler
+    for SumInputEventHandler<IS_FILTERED, HAS_SERVER_BONUS>
+{
+    type Game = ConfigurableSumGame<IS_FILTERED, HAS_SERVER_BONUS>;
+
+    fn handle_input_event(&mut self, _input_event: ()) {}
+
+    fn get_input(&mut self, _frame_index: FrameIndex) -> i64 {
+        1
+    }
+}
@@ -15,7 +15,7 @@ use serde::{
 
 const MAX_RANGE: f64 = 5000 as f64;
 
-#[derive(Serialize, Deserialize, Debug, Clone, Hash)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Hash)]
 pub struct Bullet {
     start_frame_index: FrameIndex,
     start_position: Vector2,
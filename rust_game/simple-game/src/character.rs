@@ -15,7 +15,7 @@ use serde::{
     Serialize,
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Character {
     player_index: usize,
     velocity: Vector2,
@@ -61,7 +61,7 @@ impl Character {
     }
 
     pub fn move_character(&mut self, arg: &UpdateArg<SimpleGameImpl>) {
-        if let Input::Authoritative(input) = arg.get_input(self.player_index) {
+        if let Some(input) = arg.get_input_or_prediction(self.player_index) {
             self.velocity = input.get_velocity();
         }
 
@@ -2,6 +2,7 @@ use crate::simplegameimpl::SimpleGameImpl;
 use crate::simpleinput::SimpleInput;
 use crate::simpleinputevent::SimpleInputEvent;
 use crate::simpleinputeventhandler::SimpleInputEventHandler;
+use crate::simpleplayer::SimplePlayer;
 use crate::simpleserverinput::SimpleServerInput;
 use crate::simplestate::*;
 use crate::simplewindow::SimpleWindow;
@@ -10,6 +11,8 @@ use commons::real_time::Factory;
 use commons::time::TimeDuration;
 use engine_core::{
     Client,
+    ExtrapolationPolicy,
+    RenderDelay,
     Server,
 };
 use log::{
@@ -31,12 +34,14 @@ mod simplegameimpl;
 mod simpleinput;
 mod simpleinputevent;
 mod simpleinputeventhandler;
+mod simpleplayer;
 mod simpleserverinput;
 mod simplestate;
 mod simplewindow;
 
 pub fn main() {
     let mut run_client = None;
+    let mut host_plays = false;
     let mut window_name: String = String::from("Server");
 
     let args: Vec<String> = std::env::args().collect();
@@ -55,6 +60,15 @@ pub fn main() {
                 run_client = Some(false);
                 current_arg = current_arg + 1;
             }
+            "-l" => {
+                if let Some(true) = run_client {
+                    panic!("Only a server can have a local player");
+                }
+
+                run_client = Some(false);
+                host_plays = true;
+                current_arg = current_arg + 1;
+            }
             "-c" => {
                 if let Some(false) = run_client {
                     panic!("This execution cannot run both a server and a client");
@@ -94,13 +108,47 @@ pub fn main() {
     let factory = Factory::new();
 
     if let Some(true) = run_client {
-        let (client, render_receiver) = Client::<SimpleGameImpl>::new(factory.clone());
-
-        let client_window = SimpleWindow::new(window_name, render_receiver, Some(client));
+        let (client, mut render_receiver) =
+            match Client::<SimpleGameImpl>::new(factory.clone(), SimpleInputEventHandler::new()) {
+                Ok(client) => client,
+                Err(error) => {
+                    error!("Failed to connect to the server: {}", error);
+                    return;
+                }
+            };
+
+        // Clients render a frame behind so late states can still be interpolated,
+        // and briefly extrapolate when the buffer runs dry.
+        render_receiver.set_render_delay(RenderDelay::Frames(1.0));
+        render_receiver
+            .set_extrapolation_policy(ExtrapolationPolicy::Extrapolate { max_frames: 2.0 });
+
+        let client_window = SimpleWindow::new(
+            window_name,
+            render_receiver,
+            Some(SimplePlayer::Client(client)),
+        );
 
         client_window.run();
     } else {
-        let mut server = Server::<SimpleGameImpl>::new(factory.clone()).unwrap();
+        let mut server = match Server::<SimpleGameImpl>::new(factory.clone()) {
+            Ok(server) => server,
+            Err(error) => {
+                error!("Failed to start the server: {}", error);
+                return;
+            }
+        };
+
+        // The local player has to be added before the game starts
+        let local_player_option = if host_plays {
+            Some(
+                server
+                    .add_local_player(SimpleInputEventHandler::new())
+                    .unwrap(),
+            )
+        } else {
+            None
+        };
 
         info!("Hit enter to start the game.");
         let stdin = stdin();
@@ -111,8 +159,16 @@ pub fn main() {
 
         server.start_game().unwrap();
 
-        let server_window =
-            SimpleWindow::new(window_name, server.take_render_receiver().unwrap(), None);
+        let server_window = if host_plays {
+            let (local_player, render_receiver) = local_player_option.unwrap();
+            SimpleWindow::new(
+                window_name,
+                render_receiver,
+                Some(SimplePlayer::LocalPlayer(local_player)),
+            )
+        } else {
+            SimpleWindow::new(window_name, server.take_render_receiver().unwrap(), None)
+        };
 
         server_window.run();
     }
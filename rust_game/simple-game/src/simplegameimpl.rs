@@ -1,13 +1,14 @@
 use crate::{
     SimpleInput,
     SimpleInputEvent,
-    SimpleInputEventHandler,
+    SimpleServerInput,
     SimpleState,
     TimeDuration,
 };
 use engine_core::{
     GameTrait,
     InitialInformation,
+    InputValidation,
     InterpolationArg,
     UpdateArg,
 };
@@ -22,12 +23,16 @@ pub struct SimpleGameImpl {}
 impl GameTrait for SimpleGameImpl {
     type State = SimpleState;
     type ClientInput = SimpleInput;
+    type ServerInput = SimpleServerInput;
     type InterpolationResult = SimpleState;
     type ClientInputEvent = SimpleInputEvent;
-    type ClientInputEventHandler = SimpleInputEventHandler;
+    type ClientMessage = ();
+    type ServerMessage = ();
 
+    const GAME_NAME: &'static str = "SimpleGame";
     const TCP_PORT: u16 = 3456;
     const UDP_PORT: u16 = 3457;
+    const DISCOVERY_PORT: u16 = 3458;
     const STEP_PERIOD: TimeDuration = TimeDuration::new(0, 100_000_000);
     const GRACE_PERIOD: TimeDuration = TimeDuration::new(1, 0);
     const PING_PERIOD: TimeDuration = TimeDuration::new(1, 0);
@@ -41,27 +46,21 @@ impl GameTrait for SimpleGameImpl {
         return SimpleState::get_next_state(arg);
     }
 
+    fn validate_input(
+        _state: &Self::State,
+        _player_index: usize,
+        input: &Self::ClientInput,
+    ) -> InputValidation<Self> {
+        return SimpleInput::validate(input);
+    }
+
     fn interpolate(
         initial_information: &InitialInformation<Self>,
+        previous_result: Option<&Self::InterpolationResult>,
         first: &Self::State,
         second: &Self::State,
         arg: &InterpolationArg,
     ) -> Self::InterpolationResult {
-        return SimpleState::interpolate(initial_information, first, second, arg);
-    }
-
-    fn new_input_event_handler() -> Self::ClientInputEventHandler {
-        SimpleInputEventHandler::new()
-    }
-
-    fn handle_input_event(
-        input_event_handler: &mut Self::ClientInputEventHandler,
-        input_event: Self::ClientInputEvent,
-    ) {
-        input_event_handler.handle_event(input_event);
-    }
-
-    fn get_input(input_event_handler: &mut Self::ClientInputEventHandler) -> Self::ClientInput {
-        input_event_handler.get_input()
+        return SimpleState::interpolate(initial_information, previous_result, first, second, arg);
     }
 }
@@ -1,10 +1,12 @@
+use crate::SimpleGameImpl;
 use commons::geometry::twod::Vector2;
+use engine_core::InputValidation;
 use serde::{
     Deserialize,
     Serialize,
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SimpleInput {
     aim_point: Vector2,
     velocity: Vector2,
@@ -31,4 +33,27 @@ impl SimpleInput {
     pub fn should_fire(&self) -> bool {
         return self.should_fire;
     }
+
+    /// Rejects inputs with values that aren't finite and clamps the velocity
+    /// to the unit length produced by
+    /// [SimpleInputEventHandler](crate::simpleinputeventhandler::SimpleInputEventHandler)
+    pub fn validate(&self) -> InputValidation<SimpleGameImpl> {
+        let (aim_x, aim_y) = self.aim_point.get();
+        let (velocity_x, velocity_y) = self.velocity.get();
+
+        if ![aim_x, aim_y, velocity_x, velocity_y]
+            .iter()
+            .all(|value| value.is_finite())
+        {
+            return InputValidation::Reject;
+        }
+
+        // Allow for rounding in the client's normalization
+        if self.velocity.get_length() > 1.0 + 1e-9 {
+            let velocity = self.velocity.normalize();
+            return InputValidation::Clamp(Self::new(self.aim_point, velocity, self.should_fire));
+        }
+
+        return InputValidation::Accept;
+    }
 }
@@ -1,6 +1,11 @@
+use crate::simplegameimpl::SimpleGameImpl;
 use crate::simpleinput::SimpleInput;
 use crate::simpleinputevent::SimpleInputEvent;
 use commons::geometry::twod::Vector2;
+use engine_core::{
+    FrameIndex,
+    InputEventHandler,
+};
 use piston::input::Input as PistonInput;
 use piston::{
     Button,
@@ -109,3 +114,15 @@ impl SimpleInputEventHandler {
         }
     }
 }
+
+impl InputEventHandler for SimpleInputEventHandler {
+    type Game = SimpleGameImpl;
+
+    fn handle_input_event(&mut self, input_event: SimpleInputEvent) {
+        self.handle_event(input_event);
+    }
+
+    fn get_input(&mut self, _frame_index: FrameIndex) -> SimpleInput {
+        return SimpleInputEventHandler::get_input(self);
+    }
+}
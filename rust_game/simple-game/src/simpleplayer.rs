@@ -0,0 +1,23 @@
+use crate::simplegameimpl::SimpleGameImpl;
+use crate::simpleinputevent::SimpleInputEvent;
+use engine_core::{
+    Client,
+    LocalPlayer,
+};
+
+/// The player whose input events a [SimpleWindow](crate::simplewindow::SimpleWindow) forwards
+pub enum SimplePlayer {
+    Client(Client<SimpleGameImpl>),
+    LocalPlayer(LocalPlayer<SimpleGameImpl>),
+}
+
+impl SimplePlayer {
+    pub fn send_input_event(&self, input_event: SimpleInputEvent) -> Result<(), SimpleInputEvent> {
+        return match self {
+            SimplePlayer::Client(client) => client.send_client_input_event(input_event),
+            SimplePlayer::LocalPlayer(local_player) => {
+                local_player.send_client_input_event(input_event)
+            }
+        };
+    }
+}
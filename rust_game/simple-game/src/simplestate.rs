@@ -8,6 +8,7 @@ use commons::time::TimeDuration;
 use engine_core::{
     GameTrait,
     InitialInformation,
+    Input,
     InterpolationArg,
     UpdateArg,
 };
@@ -18,10 +19,17 @@ use serde::{
     Serialize,
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+// The part of a rollback correction's offset that is kept on each drawn frame
+const ROLLBACK_SMOOTHING_DECAY: f64 = 0.8;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct SimpleState {
     player_characters: Vec<Character>,
     bullets: Vec<Bullet>,
+    // Only set on interpolation results: the offset from its true position at
+    // which each character is drawn while a rollback correction is smoothed out
+    #[serde(skip)]
+    smoothing_offsets: Vec<Vector2>,
 }
 
 impl SimpleState {
@@ -29,6 +37,7 @@ impl SimpleState {
         let mut new = Self {
             player_characters: Vec::new(),
             bullets: Vec::new(),
+            smoothing_offsets: Vec::new(),
         };
 
         for i in 0..player_count {
@@ -63,10 +72,18 @@ impl SimpleState {
     }
 
     fn update(&mut self, arg: &UpdateArg<SimpleGameImpl>) {
-        //TODO: This should probably check the authoritativeness of the input to do server-only logic
+        // Hit detection is deterministic, so it runs on clients as well as the
+        // server.  Server-only logic can be gated with arg.is_server() once
+        // HAS_SERVER_ONLY_LOGIC is set.
         let server_input = Self::get_server_input(arg);
         server_input.apply_to_state(self);
 
+        if let Input::Authoritative(server_inputs) = arg.get_server_input() {
+            for server_input in server_inputs {
+                server_input.apply_to_state(self);
+            }
+        }
+
         //TODO: refactor this time calculation
         let duration_of_start_to_current =
             SimpleGameImpl::STEP_PERIOD.mul_f64(arg.get_current_step().usize() as f64);
@@ -91,6 +108,7 @@ impl SimpleState {
 
     pub fn interpolate(
         _initial_information: &InitialInformation<SimpleGameImpl>,
+        previous_result: Option<&Self>,
         first: &Self,
         second: &Self,
         arg: &InterpolationArg,
@@ -107,6 +125,31 @@ impl SimpleState {
             }
         }
 
+        // When a rollback corrects a prediction, characters are drawn from
+        // where they were drawn last instead of teleporting, and the offset
+        // decays over the next frames.
+        if let Some(previous_result) = previous_result {
+            for i in 0..second_clone.player_characters.len() {
+                let position = *second_clone.player_characters[i].get_position();
+
+                let offset = if arg.has_rolled_back() {
+                    match previous_result.player_characters.get(i) {
+                        Some(previous_character) => *previous_character.get_position() - position,
+                        None => Vector2::zero(),
+                    }
+                } else {
+                    match previous_result.smoothing_offsets.get(i) {
+                        Some(previous_offset) => *previous_offset,
+                        None => Vector2::zero(),
+                    }
+                };
+
+                let offset = offset * ROLLBACK_SMOOTHING_DECAY;
+                second_clone.smoothing_offsets.push(offset);
+                second_clone.player_characters[i].set_position(position + offset);
+            }
+        }
+
         return second_clone;
     }
 
@@ -1,9 +1,7 @@
 use crate::simplegameimpl::SimpleGameImpl;
 use crate::simpleinputevent::SimpleInputEvent;
-use engine_core::{
-    Client,
-    RenderReceiver,
-};
+use crate::simpleplayer::SimplePlayer;
+use engine_core::RenderReceiver;
 use glutin_window::GlutinWindow as Window;
 use graphics::*;
 use log::info;
@@ -26,7 +24,7 @@ pub struct SimpleWindow {
     window_name: String,
     render_receiver: RenderReceiver<SimpleGameImpl>,
     //TODO: don't expose eventhandling, sender or ClientCore, or ClientCoreEvent, or GameFactoryTrait, or RealGameFactory
-    client_option: Option<Client<SimpleGameImpl>>,
+    player_option: Option<SimplePlayer>,
     mouse_position: [f64; 2],
 }
 
@@ -34,12 +32,12 @@ impl SimpleWindow {
     pub fn new(
         window_name: String,
         render_receiver: RenderReceiver<SimpleGameImpl>,
-        client_option: Option<Client<SimpleGameImpl>>,
+        player_option: Option<SimplePlayer>,
     ) -> Self {
         return Self {
             window_name,
             render_receiver,
-            client_option,
+            player_option,
             mouse_position: [0.0, 0.0],
         };
     }
@@ -104,9 +102,9 @@ impl SimpleWindow {
             _ => {}
         }
 
-        if let Some(client) = self.client_option.as_ref() {
-            client
-                .send_client_input_event(SimpleInputEvent::new(input))
+        if let Some(player) = self.player_option.as_ref() {
+            player
+                .send_input_event(SimpleInputEvent::new(input))
                 .unwrap();
         }
     }